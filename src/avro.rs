@@ -0,0 +1,833 @@
+use crate::diagnostic::Diagnostic;
+use crate::symbols::Symbols;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use tree_sitter::Node;
+
+// Builds Avro types from TypeScript type expressions. Logical types are always attached to
+// the type they annotate, so they stay correct once wrapped in a union or an array.
+
+pub fn timestamp_millis() -> Value {
+    json!({ "type": "long", "logicalType": "timestamp-millis" })
+}
+
+// maps a TypeScript type name; unknown names are kept as references to other records
+pub fn named_type(name: &str) -> Value {
+    match name {
+        "string" => json!("string"),
+        "number" => json!("double"),
+        "bigint" => json!("long"),
+        "boolean" => json!("boolean"),
+        "null" | "undefined" | "void" => json!("null"),
+        "Date" => timestamp_millis(),
+        "Buffer" | "Uint8Array" => json!("bytes"),
+        // the types Prisma generates for `Decimal` and `Json` columns, at Prisma's default
+        // precision for decimals
+        "Prisma.Decimal" => json!({
+            "type": "bytes",
+            "logicalType": "decimal",
+            "precision": 65,
+            "scale": 30
+        }),
+        "Prisma.JsonValue" => json!("string"),
+        _ => json!(name),
+    }
+}
+
+pub fn from_node(node: &Node, code: &str) -> Value {
+    let text = node.utf8_text(code.as_bytes()).unwrap_or_default();
+    match node.kind() {
+        "predefined_type" | "type_identifier" | "nested_type_identifier" => named_type(text),
+        "literal_type" => match node.named_child(0).map(|literal| literal.kind()) {
+            Some("null") | Some("undefined") => json!("null"),
+            Some("string") | Some("template_string") => json!("string"),
+            Some("number") | Some("unary_expression") => json!("double"),
+            Some("true") | Some("false") => json!("boolean"),
+            _ => json!(text),
+        },
+        "union_type" => {
+            let mut cursor = node.walk();
+            let members = node
+                .named_children(&mut cursor)
+                .map(|member| from_node(&member, code))
+                .collect();
+            union(members)
+        }
+        "array_type" => match node.named_child(0) {
+            Some(item) => json!({ "type": "array", "items": from_node(&item, code) }),
+            None => json!(text),
+        },
+        "generic_type" => generic(node, code).unwrap_or(json!(text)),
+        "conditional_type" => conditional(node, code).unwrap_or(json!(text)),
+        "lookup_type" => const_member(node, code).unwrap_or(json!(text)),
+        // named by `hoist_anonymous` once the declaring record is known
+        "object_type" => {
+            let mut cursor = node.walk();
+            let fields: Vec<Value> = node
+                .named_children(&mut cursor)
+                .filter(|member| member.kind() == "property_signature")
+                .filter_map(|member| crate::get_prop_type(&member, code).ok().flatten())
+                .collect();
+            json!({ "type": "record", "fields": fields })
+        }
+        "flow_maybe_type" | "parenthesized_type" | "readonly_type" => match node.named_child(0) {
+            Some(inner) if node.kind() == "flow_maybe_type" => {
+                union(vec![json!("null"), from_node(&inner, code)])
+            }
+            Some(inner) => from_node(&inner, code),
+            None => json!(text),
+        },
+        _ => json!(text),
+    }
+}
+
+// collections translate their element type recursively, so `Array<Map<string, Foo>>`
+// becomes an array of maps of Foo records
+fn generic(node: &Node, code: &str) -> Option<Value> {
+    let name = node
+        .child_by_field_name("name")?
+        .utf8_text(code.as_bytes())
+        .ok()?;
+    let arguments = node.child_by_field_name("type_arguments")?;
+    let mut cursor = arguments.walk();
+    let last = arguments.named_children(&mut cursor).last()?;
+
+    match name {
+        "Array" | "ReadonlyArray" | "Set" | "ReadonlySet" => {
+            Some(json!({ "type": "array", "items": from_node(&last, code) }))
+        }
+        "Map" | "ReadonlyMap" | "Record" => {
+            Some(json!({ "type": "map", "values": from_node(&last, code) }))
+        }
+        _ => None,
+    }
+}
+
+// `typeof STATUSES[number]` is a member of the enum declared by `STATUSES = [...] as const`
+fn const_member(node: &Node, code: &str) -> Option<Value> {
+    let mut query = node.named_child(0)?;
+    if query.kind() == "parenthesized_type" {
+        query = query.named_child(0)?;
+    }
+    let index = node.named_child(1)?.utf8_text(code.as_bytes()).ok()?;
+    match (query.kind(), index) {
+        ("type_query", "number") => Some(json!(query
+            .named_child(0)?
+            .utf8_text(code.as_bytes())
+            .ok()?)),
+        _ => None,
+    }
+}
+
+// `A extends B ? C : D` is decided here when both sides are primitive or literal types.
+// Otherwise it is kept, with where it was written, until `substitute` binds its type
+// parameter to the parameter's default; `undecided` finds the ones left after that. Uses
+// such as `Box<string>` don't instantiate the record, so their arguments decide nothing.
+fn conditional(node: &Node, code: &str) -> Option<Value> {
+    let check = checked(&node.child_by_field_name("left")?, code);
+    let extends = checked(&node.child_by_field_name("right")?, code);
+    let then = from_node(&node.child_by_field_name("consequence")?, code);
+    let otherwise = from_node(&node.child_by_field_name("alternative")?, code);
+    Some(match holds(&check, &extends) {
+        Some(true) => then,
+        Some(false) => otherwise,
+        None => json!({
+            "conditional": node.utf8_text(code.as_bytes()).ok()?,
+            "line": node.start_position().row + 1,
+            "column": node.start_position().column + 1,
+            "check": check,
+            "extends": extends,
+            "then": then,
+            "else": otherwise
+        }),
+    })
+}
+
+// a literal side keeps its text, so that `"a" extends "b"` can be told apart
+fn checked(node: &Node, code: &str) -> Value {
+    match node.kind() {
+        "literal_type" => json!({
+            "literal": node.utf8_text(code.as_bytes()).unwrap_or_default(),
+            "type": from_node(node, code)
+        }),
+        _ => from_node(node, code),
+    }
+}
+
+// whether `check extends extends`, when the two types say so without an instantiation
+fn holds(check: &Value, extends: &Value) -> Option<bool> {
+    fn primitive(ptype: &Value) -> Option<&str> {
+        ptype.as_str().filter(|name| is_primitive(name))
+    }
+    if extends == "any" || extends == "unknown" {
+        return Some(true);
+    }
+    match (check.get("literal"), extends.get("literal")) {
+        (Some(check), Some(extends)) => Some(check == extends),
+        (Some(_), None) => Some(primitive(extends)? == primitive(&check["type"])?),
+        (None, Some(_)) => primitive(check).map(|_| false),
+        (None, None) => Some(primitive(check)? == primitive(extends)?),
+    }
+}
+
+// the first conditional type in `ptype` that is still to be decided
+pub fn undecided(ptype: &Value) -> Option<&Value> {
+    match ptype {
+        Value::Array(members) => members.iter().find_map(undecided),
+        Value::Object(object) if object.contains_key("conditional") => Some(ptype),
+        Value::Object(object) => object
+            .get("fields")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .find_map(|field| undecided(&field["type"]))
+            .or_else(|| {
+                ["items", "values"]
+                    .iter()
+                    .find_map(|key| undecided(object.get(*key)?))
+            }),
+        _ => None,
+    }
+}
+
+// Deduplicates members and orders them null first, then primitives, then other types
+// alphabetically, so that `number | string` and `string | number` produce the same schema.
+// A union left with a single member collapses to it.
+pub fn union(members: Vec<Value>) -> Value {
+    let mut unique: Vec<Value> = Vec::new();
+    for member in members {
+        let flattened = match member {
+            Value::Array(inner) => inner,
+            member => vec![member],
+        };
+        for member in flattened {
+            if !unique.contains(&member) {
+                unique.push(member);
+            }
+        }
+    }
+
+    unique.sort_by_key(|member| {
+        let name = match member {
+            Value::String(name) => name.to_owned(),
+            other => other.to_string(),
+        };
+        let rank = [
+            "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+        ]
+        .iter()
+        .position(|x| *x == name)
+        .unwrap_or(if member.is_object() { 8 } else { 9 });
+        (rank, name)
+    });
+
+    match unique.len() {
+        1 => unique.remove(0),
+        _ => Value::Array(unique),
+    }
+}
+
+const PRIMITIVES: [&str; 7] = [
+    "boolean", "int", "long", "float", "double", "bytes", "string",
+];
+
+// How a union of several primitives, such as `string | number | boolean`, is emitted.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum UnionStrategy {
+    #[default]
+    Union,
+    String,
+    Strict,
+}
+
+impl UnionStrategy {
+    pub fn parse(name: &str) -> Option<UnionStrategy> {
+        match name {
+            "union" => Some(UnionStrategy::Union),
+            "string" => Some(UnionStrategy::String),
+            "strict" => Some(UnionStrategy::Strict),
+            _ => None,
+        }
+    }
+}
+
+// Limits on unions from the `[unions]` table of the config: `max_branches` counts the
+// branches besides null, and `mixed = false` rejects unions of named or complex types with
+// primitives, such as `Address | string`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UnionPolicy {
+    pub max_branches: Option<usize>,
+    pub mixed: bool,
+}
+
+impl UnionPolicy {
+    pub fn parse(unions: &Value) -> Result<UnionPolicy, String> {
+        let max_branches = match &unions["max_branches"] {
+            Value::Null => None,
+            max => Some(
+                max.as_u64()
+                    .filter(|max| *max > 0)
+                    .ok_or("unions.max_branches: expected a positive number")?
+                    as usize,
+            ),
+        };
+        let mixed = match &unions["mixed"] {
+            Value::Null => true,
+            mixed => mixed
+                .as_bool()
+                .ok_or("unions.mixed: expected true or false")?,
+        };
+        Ok(UnionPolicy {
+            max_branches,
+            mixed,
+        })
+    }
+
+    // every field of the records with a union breaking the policy
+    pub fn check(&self, schemas: &[Value]) -> Vec<Diagnostic> {
+        let mut violations = Vec::new();
+        for schema in schemas {
+            let record = schema["name"].as_str().unwrap_or_default();
+            for field in schema["fields"].as_array().into_iter().flatten() {
+                if let Some(message) = self.violation(&field["type"]) {
+                    violations.push(Diagnostic {
+                        record: record.to_owned(),
+                        field: field["name"].as_str().unwrap_or_default().to_owned(),
+                        message,
+                    });
+                }
+            }
+        }
+        violations
+    }
+
+    fn violation(&self, ptype: &Value) -> Option<String> {
+        let members = match ptype {
+            Value::Array(members) => members,
+            Value::Object(object) => {
+                return ["items", "values"]
+                    .iter()
+                    .find_map(|key| self.violation(object.get(*key)?))
+            }
+            _ => return None,
+        };
+        let branches: Vec<&Value> = members.iter().filter(|member| *member != "null").collect();
+        let names: Vec<String> = branches
+            .iter()
+            .map(|member| match member {
+                Value::String(name) => name.clone(),
+                member => member["name"]
+                    .as_str()
+                    .or(member["type"].as_str())
+                    .unwrap_or_default()
+                    .to_owned(),
+            })
+            .collect();
+        let primitive = |member: &&&Value| match member {
+            Value::String(name) => is_primitive(name),
+            member => member.get("logicalType").is_some(),
+        };
+        match self.max_branches {
+            Some(max) if branches.len() > max => {
+                return Some(format!(
+                    "union of {} has {} branches, more than the {} allowed",
+                    names.join(" | "),
+                    branches.len(),
+                    max
+                ))
+            }
+            _ => {}
+        }
+        let primitives = branches.iter().filter(primitive).count();
+        if !self.mixed && primitives > 0 && primitives < branches.len() {
+            return Some(format!(
+                "union of {} mixes primitives with other types",
+                names.join(" | ")
+            ));
+        }
+        None
+    }
+}
+
+// Applies the strategy to the fields of every record, returning a warning for each
+// collapsed union, or the first offending field in strict mode.
+pub fn collapse_unions(
+    schemas: &mut [Value],
+    strategy: UnionStrategy,
+) -> Result<Vec<Diagnostic>, Diagnostic> {
+    let mut warnings = Vec::new();
+    if strategy == UnionStrategy::Union {
+        return Ok(warnings);
+    }
+
+    for schema in schemas.iter_mut() {
+        let record = schema["name"].as_str().unwrap_or_default().to_owned();
+        for field in schema["fields"].as_array_mut().into_iter().flatten() {
+            let name = field["name"].as_str().unwrap_or_default().to_owned();
+            let diagnostic = |message| Diagnostic {
+                record: record.clone(),
+                field: name.clone(),
+                message,
+            };
+            let mut messages = Vec::new();
+            collapse(&mut field["type"], strategy, &mut messages).map_err(diagnostic)?;
+            warnings.extend(messages.into_iter().map(diagnostic));
+        }
+    }
+    Ok(warnings)
+}
+
+fn collapse(
+    ptype: &mut Value,
+    strategy: UnionStrategy,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    if let Value::Object(object) = ptype {
+        for key in ["items", "values"] {
+            if let Some(inner) = object.get_mut(key) {
+                collapse(inner, strategy, warnings)?;
+            }
+        }
+        return Ok(());
+    }
+
+    let members = match ptype.as_array() {
+        Some(members) => members,
+        None => return Ok(()),
+    };
+    let rest: Vec<&str> = members
+        .iter()
+        .filter(|member| **member != "null")
+        .map(|member| member.as_str().unwrap_or_default())
+        .collect();
+    if rest.len() < 2 || !rest.iter().all(|member| PRIMITIVES.contains(member)) {
+        return Ok(());
+    }
+
+    let members = rest.join(" | ");
+    if strategy == UnionStrategy::Strict {
+        return Err(format!(
+            "union of {} is not allowed in strict mode",
+            members
+        ));
+    }
+    warnings.push(format!("collapsed {} to string", members));
+    *ptype = match is_nullable(ptype) {
+        true => json!(["null", "string"]),
+        false => json!("string"),
+    };
+    Ok(())
+}
+
+// Inline object types, as in `address: { city: string }`, become records named after the
+// record and field declaring them, added after the declarations. Object types of the same
+// shape share the record added for the first one, so that the schema stays small and
+// defines each name only once.
+pub fn hoist_anonymous(schemas: &mut Vec<Value>) {
+    let mut taken: BTreeSet<String> = schemas
+        .iter()
+        .filter_map(|schema| schema["name"].as_str())
+        .map(|name| name.to_owned())
+        .collect();
+    let mut shapes = BTreeMap::new();
+    // hoisted records are visited in turn, which names the object types nested in them
+    let mut i = 0;
+    while i < schemas.len() {
+        let record = schemas[i]["name"].as_str().unwrap_or_default().to_owned();
+        let mut hoisted = Vec::new();
+        let fields = schemas[i].get_mut("fields").and_then(Value::as_array_mut);
+        for field in fields.into_iter().flatten() {
+            let name = format!(
+                "{}{}",
+                record,
+                pascal_case(field["name"].as_str().unwrap_or_default())
+            );
+            let mut anonymous = Anonymous {
+                name: &name,
+                taken: &mut taken,
+                shapes: &mut shapes,
+                hoisted: &mut hoisted,
+            };
+            anonymous.hoist(&mut field["type"]);
+        }
+        schemas.extend(hoisted);
+        i += 1;
+    }
+}
+
+struct Anonymous<'a> {
+    name: &'a str,
+    taken: &'a mut BTreeSet<String>,
+    shapes: &'a mut BTreeMap<String, String>,
+    hoisted: &'a mut Vec<Value>,
+}
+
+impl Anonymous<'_> {
+    fn hoist(&mut self, ptype: &mut Value) {
+        match ptype {
+            Value::Array(members) => {
+                for member in members.iter_mut() {
+                    self.hoist(member);
+                }
+                // `{ a: string } | { a: string }` is one branch once named
+                let mut unique: Vec<Value> = Vec::new();
+                for member in members.drain(..) {
+                    if !unique.contains(&member) {
+                        unique.push(member);
+                    }
+                }
+                *members = unique;
+            }
+            Value::Object(object)
+                if object.contains_key("fields") && !object.contains_key("name") =>
+            {
+                let shape = object["fields"].to_string();
+                let name = match self.shapes.get(&shape) {
+                    Some(name) => name.to_owned(),
+                    None => {
+                        let mut name = self.name.to_owned();
+                        let mut n = 1;
+                        while self.taken.contains(&name) {
+                            n += 1;
+                            name = format!("{}{}", self.name, n);
+                        }
+                        self.taken.insert(name.clone());
+                        self.shapes.insert(shape, name.clone());
+                        let mut record = ptype.clone();
+                        record["name"] = json!(name);
+                        self.hoisted.push(record);
+                        name
+                    }
+                };
+                *ptype = json!(name);
+            }
+            Value::Object(object) => {
+                for key in ["items", "values"] {
+                    if let Some(inner) = object.get_mut(key) {
+                        self.hoist(inner);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// `shipping_address` -> `ShippingAddress`
+fn pascal_case(name: &str) -> String {
+    name.split(['_', '-'])
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+pub fn is_primitive(name: &str) -> bool {
+    name == "null" || PRIMITIVES.contains(&name)
+}
+
+// the names of other types a field type refers to
+pub fn references(ptype: &Value) -> Vec<String> {
+    match ptype {
+        Value::String(name) if !is_primitive(name) => vec![name.to_owned()],
+        Value::Array(members) => members.iter().flat_map(references).collect(),
+        Value::Object(object) => ["items", "values"]
+            .iter()
+            .filter_map(|key| object.get(*key))
+            .flat_map(references)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// referenced names that match no declaration
+pub fn unresolved(schemas: &[Value]) -> BTreeSet<String> {
+    let symbols = Symbols::new(schemas);
+    schemas
+        .iter()
+        .flat_map(|schema| schema["fields"].as_array().into_iter().flatten())
+        .flat_map(|field| references(&field["type"]))
+        .filter(|name| !symbols.contains(name))
+        .collect()
+}
+
+// the first schema with every declaration it refers to, directly or not, in their original
+// order; its `@avro.key` record is kept for `--key-out`
+pub fn reachable(schemas: Vec<Value>) -> Vec<Value> {
+    let root = match schemas.first() {
+        Some(root) => root["name"].as_str().unwrap_or_default().to_owned(),
+        None => return schemas,
+    };
+    let mut reached = BTreeSet::from([root.clone(), format!("{}Key", root)]);
+    let symbols = Symbols::new(&schemas);
+    let mut pending = vec![root];
+    while let Some(name) = pending.pop() {
+        let fields = symbols
+            .lookup(&name, "")
+            .and_then(|(_, schema)| schema["fields"].as_array())
+            .into_iter()
+            .flatten();
+        for reference in fields.flat_map(|field| references(&field["type"])) {
+            // a qualified `Geo.Point` reaches the declaration of `Point`
+            let reference = match symbols.lookup(&reference, "") {
+                Some((_, schema)) => schema["name"].as_str().unwrap_or_default().to_owned(),
+                None => reference,
+            };
+            if reached.insert(reference.clone()) {
+                pending.push(reference);
+            }
+        }
+    }
+    schemas
+        .into_iter()
+        .filter(|schema| {
+            schema["name"]
+                .as_str()
+                .is_some_and(|name| reached.contains(name))
+        })
+        .collect()
+}
+
+// replaces the type parameters named in `bindings` by their type, such as `T` in `T[]`
+pub fn substitute(ptype: &mut Value, bindings: &[(String, Value)]) {
+    if ptype.get("conditional").is_some() {
+        for key in ["check", "extends", "then", "else"] {
+            substitute(&mut ptype[key], bindings);
+        }
+        match holds(&ptype["check"], &ptype["extends"]) {
+            Some(true) => *ptype = ptype["then"].take(),
+            Some(false) => *ptype = ptype["else"].take(),
+            None => {}
+        }
+        return;
+    }
+    match ptype {
+        Value::String(name) => {
+            if let Some((_, bound)) = bindings.iter().find(|(parameter, _)| parameter == name) {
+                *ptype = bound.clone();
+            }
+        }
+        Value::Array(members) => {
+            for member in members.iter_mut() {
+                substitute(member, bindings);
+            }
+            *ptype = union(members.clone());
+        }
+        Value::Object(object) => {
+            if let Some(Value::Array(fields)) = object.get_mut("fields") {
+                for field in fields {
+                    substitute(&mut field["type"], bindings);
+                }
+            }
+            for key in ["items", "values"] {
+                if let Some(inner) = object.get_mut(key) {
+                    substitute(inner, bindings);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn is_nullable(ptype: &Value) -> bool {
+    ptype
+        .as_array()
+        .is_some_and(|members| members.first() == Some(&json!("null")))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::{collapse_unions, named_type, reachable, union, UnionPolicy, UnionStrategy};
+    use crate::{get_schema, Grammar};
+    use serde_json::json;
+
+    #[test]
+    fn test_union() {
+        assert_eq!(
+            union(vec![json!("string"), json!("null"), json!("null")]),
+            json!(["null", "string"])
+        );
+        assert_eq!(
+            union(vec![json!("Location"), named_type("Date"), json!("null")]),
+            json!([
+                "null",
+                { "type": "long", "logicalType": "timestamp-millis" },
+                "Location"
+            ])
+        );
+        assert_eq!(
+            union(vec![json!("string"), json!("string")]),
+            json!("string")
+        );
+    }
+
+    #[test]
+    fn test_collapse_unions() {
+        let code = r#"
+        interface Setting {
+            value: string | number | boolean;
+            fallback?: string | number;
+            location: Location | string;
+            name: string | null;
+        }
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        let mut unions = schemas.clone();
+        assert!(collapse_unions(&mut unions, UnionStrategy::Union)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            unions[0]["fields"][0]["type"],
+            json!(["boolean", "double", "string"])
+        );
+
+        let mut collapsed = schemas.clone();
+        let warnings = collapse_unions(&mut collapsed, UnionStrategy::String).unwrap();
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "Setting.value: collapsed boolean | double | string to string",
+                "Setting.fallback: collapsed double | string to string"
+            ]
+        );
+        assert_eq!(collapsed[0]["fields"][0]["type"], "string");
+        assert_eq!(collapsed[0]["fields"][1]["type"], json!(["null", "string"]));
+        assert_eq!(
+            collapsed[0]["fields"][2]["type"],
+            json!(["string", "Location"])
+        );
+
+        let mut strict = schemas;
+        assert_eq!(
+            collapse_unions(&mut strict, UnionStrategy::Strict)
+                .unwrap_err()
+                .to_string(),
+            "Setting.value: union of boolean | double | string is not allowed in strict mode"
+        );
+    }
+
+    #[test]
+    fn test_reachable() {
+        let code = r#"
+        interface Order {
+            /** @avro.key */
+            id: string;
+            lines: Array<Line>;
+        }
+
+        interface Audit {
+            by: string;
+        }
+
+        interface Line {
+            product: Product;
+            status: Status;
+        }
+
+        interface Product {
+            sku: string;
+        }
+
+        enum Status {
+            Open,
+        }
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        let names: Vec<String> = reachable(schemas)
+            .iter()
+            .map(|schema| schema["name"].as_str().unwrap().to_owned())
+            .collect();
+
+        assert_eq!(
+            names,
+            vec!["Order", "OrderKey", "Line", "Product", "Status"]
+        );
+    }
+
+    #[test]
+    fn test_hoist_anonymous() {
+        let code = r#"
+        interface Order {
+            billing: { city: string; zip?: string };
+            shipping: { city: string; zip?: string } | null;
+            lines: { sku: string; discount: { percent: number } }[];
+        }
+
+        interface OrderBilling {
+            id: string;
+        }
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let names: Vec<&str> = schemas
+            .iter()
+            .map(|x| x["name"].as_str().unwrap())
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "Order",
+                "OrderBilling",
+                "OrderBilling2",
+                "OrderLines",
+                "OrderLinesDiscount"
+            ]
+        );
+        assert_eq!(schemas[0]["fields"][0]["type"], "OrderBilling2");
+        assert_eq!(
+            schemas[0]["fields"][1]["type"],
+            json!(["null", "OrderBilling2"])
+        );
+        assert_eq!(schemas[0]["fields"][2]["type"]["items"], "OrderLines");
+        assert_eq!(schemas[3]["fields"][1]["type"], "OrderLinesDiscount");
+
+        let schema = crate::merger(schemas).unwrap();
+        assert_eq!(crate::verify::verify(&schema, 10), Ok(()));
+    }
+
+    #[test]
+    fn test_union_policy() {
+        let code = r#"
+        interface Payment {
+            method: Card | BankTransfer | Voucher | string | null;
+            reference: string | number;
+            lines: (Line | string)[];
+        }
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let unions = crate::config::parse("max_branches = 3\nmixed = false\n").unwrap();
+        let policy = UnionPolicy::parse(&unions).unwrap();
+
+        let violations: Vec<String> = policy
+            .check(&schemas)
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+
+        assert_eq!(
+            violations,
+            vec![
+                "Payment.method: union of string | BankTransfer | Card | Voucher has 4 branches, more than the 3 allowed",
+                "Payment.lines: union of string | Line mixes primitives with other types"
+            ]
+        );
+        assert_eq!(
+            UnionPolicy::parse(&json!({})),
+            Ok(UnionPolicy {
+                max_branches: None,
+                mixed: true
+            })
+        );
+        assert!(UnionPolicy::parse(&json!({ "max_branches": 0 })).is_err());
+    }
+}
@@ -0,0 +1,615 @@
+use crate::symbols::Symbols;
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Problem {
+    // the reader has a field the writer never wrote, and no default to fill it with
+    MissingDefault {
+        added: bool,
+        schema: String,
+        record: String,
+    },
+    TypeMismatch {
+        writer: String,
+        reader: String,
+    },
+    BranchRemoved {
+        branch: String,
+    },
+    SymbolRemoved {
+        symbol: String,
+    },
+    // a removed field the new schema declares as an alias of `to`, as a renames file does;
+    // only readers resolve aliases, so the old ones still miss the field
+    Renamed {
+        to: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Issue {
+    pub path: String,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.problem {
+            Problem::MissingDefault { added: true, .. } => {
+                write!(f, "{}: field added without a default", self.path)
+            }
+            Problem::MissingDefault { added: false, .. } => {
+                write!(f, "{}: field removed without a default", self.path)
+            }
+            Problem::TypeMismatch { writer, reader } => {
+                write!(
+                    f,
+                    "{}: `{}` cannot be read as `{}`",
+                    self.path, writer, reader
+                )
+            }
+            Problem::BranchRemoved { branch } => {
+                write!(f, "{}: union no longer accepts `{}`", self.path, branch)
+            }
+            Problem::SymbolRemoved { symbol } => {
+                write!(
+                    f,
+                    "{}: enum symbol `{}` is not known to the reader",
+                    self.path, symbol
+                )
+            }
+            Problem::Renamed { to } => {
+                write!(
+                    f,
+                    "{}: field renamed to `{}` without a default",
+                    self.path, to
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compatibility {
+    Full,
+    Backward,
+    Forward,
+    None,
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Compatibility::Full => "FULL",
+            Compatibility::Backward => "BACKWARD",
+            Compatibility::Forward => "FORWARD",
+            Compatibility::None => "NONE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub struct Report {
+    pub name: String,
+    // problems new readers have with old data
+    pub backward: Vec<Issue>,
+    // problems old readers have with new data
+    pub forward: Vec<Issue>,
+}
+
+impl Report {
+    pub fn compatibility(&self) -> Compatibility {
+        match (self.backward.is_empty(), self.forward.is_empty()) {
+            (true, true) => Compatibility::Full,
+            (true, false) => Compatibility::Backward,
+            (false, true) => Compatibility::Forward,
+            (false, false) => Compatibility::None,
+        }
+    }
+
+    pub fn remediation(&self, issue: &Issue) -> String {
+        let field = issue.path.rsplit('.').next().unwrap_or_default();
+        match &issue.problem {
+            Problem::MissingDefault { added, .. } => {
+                if let Some(other) = self.rename_counterpart(issue) {
+                    let other = other.path.rsplit('.').next().unwrap_or_default();
+                    let (from, to) = if *added {
+                        (other, field)
+                    } else {
+                        (field, other)
+                    };
+                    return format!(
+                        "if `{}` was renamed to `{}`, add alias `{}` to `{}`",
+                        from, to, from, to
+                    );
+                }
+                if *added {
+                    format!("add default null (declare `{}` as `T | null`)", field)
+                } else {
+                    format!(
+                        "keep `{}`, or publish it with a default before removing it",
+                        field
+                    )
+                }
+            }
+            Problem::TypeMismatch { writer, reader } => format!(
+                "keep `{}` as `{}`, or add a new field of type `{}` and deprecate the old one \
+                 (only int -> long -> float -> double and string <-> bytes promote)",
+                field, writer, reader
+            ),
+            Problem::BranchRemoved { branch } if branch == "null" => {
+                format!("keep `{}` nullable (add `| null` back)", field)
+            }
+            Problem::BranchRemoved { branch } => {
+                format!("keep `{}` in the union of `{}`", branch, field)
+            }
+            Problem::SymbolRemoved { symbol } => format!(
+                "keep symbol `{}`, or give the enum a default symbol for unknown values",
+                symbol
+            ),
+            Problem::Renamed { to } => format!(
+                "readers of the old schema don't know `{}` as `{}`: publish `{}` with a default before renaming it",
+                field, to, field
+            ),
+        }
+    }
+
+    // a field missing on one side and a same-typed field missing on the other side of the
+    // same record usually means a rename
+    fn rename_counterpart(&self, issue: &Issue) -> Option<&Issue> {
+        let (added, schema) = match &issue.problem {
+            Problem::MissingDefault { added, schema, .. } => (*added, schema),
+            _ => return None,
+        };
+        let parent = issue.path.rsplit_once('.')?.0;
+        let others = if added { &self.forward } else { &self.backward };
+
+        others.iter().find(|other| match &other.problem {
+            Problem::MissingDefault {
+                added: other_added,
+                schema: other_schema,
+                ..
+            } => {
+                *other_added != added
+                    && other_schema == schema
+                    && other.path.rsplit_once('.').map(|(p, _)| p) == Some(parent)
+            }
+            _ => false,
+        })
+    }
+}
+
+// Declarations of one version of the model, used to look up named references.
+pub struct Version<'a> {
+    symbols: Symbols<'a>,
+}
+
+impl<'a> Version<'a> {
+    pub fn new(schemas: &'a [Value]) -> Version<'a> {
+        Version {
+            symbols: Symbols::new(schemas),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&'a Value> {
+        self.symbols.lookup(name, "").map(|(_, schema)| schema)
+    }
+}
+
+// the field that lists `name` among its aliases
+fn renamed(fields: &[Value], name: &str) -> Option<String> {
+    fields
+        .iter()
+        .find(|field| {
+            field["aliases"]
+                .as_array()
+                .is_some_and(|aliases| aliases.iter().any(|alias| alias == name))
+        })
+        .and_then(|field| field["name"].as_str())
+        .map(str::to_owned)
+}
+
+pub fn compare(name: &str, old: &Version, new: &Version) -> Option<Report> {
+    let new_schema = new.lookup(name)?;
+    let old_schema = old.lookup(name).or_else(|| {
+        new_schema["aliases"]
+            .as_array()?
+            .iter()
+            .find_map(|alias| old.lookup(alias.as_str()?))
+    })?;
+
+    let mut backward = Vec::new();
+    Checker::new(old, new, false).check(old_schema, new_schema, name, &mut backward);
+
+    let mut forward = Vec::new();
+    Checker::new(new, old, true).check(new_schema, old_schema, name, &mut forward);
+
+    Some(Report {
+        name: name.to_owned(),
+        backward,
+        forward,
+    })
+}
+
+struct Checker<'a, 'b> {
+    writer: &'b Version<'a>,
+    reader: &'b Version<'a>,
+    // when the old schema is the reader, a reader-only field is one that was removed
+    reader_is_old: bool,
+    visiting: Vec<(String, String)>,
+}
+
+enum Kind<'a> {
+    Union(Vec<&'a Value>),
+    Record(&'a Value),
+    Enum(&'a Value),
+    Array(&'a Value),
+    Map(&'a Value),
+    Named(String),
+}
+
+impl<'a, 'b> Checker<'a, 'b> {
+    fn new(writer: &'b Version<'a>, reader: &'b Version<'a>, reader_is_old: bool) -> Self {
+        Checker {
+            writer,
+            reader,
+            reader_is_old,
+            visiting: Vec::new(),
+        }
+    }
+
+    fn check(&mut self, writer: &'a Value, reader: &'a Value, path: &str, issues: &mut Vec<Issue>) {
+        let writer_kind = kind(writer, self.writer);
+        let reader_kind = kind(reader, self.reader);
+
+        match (writer_kind, reader_kind) {
+            (Kind::Union(writer_branches), Kind::Union(reader_branches)) => {
+                for branch in writer_branches {
+                    self.check_branch(branch, &reader_branches, path, issues);
+                }
+            }
+            (_, Kind::Union(reader_branches)) => {
+                self.check_branch(writer, &reader_branches, path, issues);
+            }
+            (Kind::Union(writer_branches), _) => {
+                for branch in writer_branches {
+                    if !self.matches(branch, reader) {
+                        issues.push(Issue {
+                            path: path.to_owned(),
+                            problem: Problem::BranchRemoved {
+                                branch: type_name(branch),
+                            },
+                        });
+                    }
+                }
+            }
+            (Kind::Record(writer_record), Kind::Record(reader_record)) => {
+                let aliased = reader_record["aliases"]
+                    .as_array()
+                    .is_some_and(|aliases| aliases.contains(&writer_record["name"]));
+                if writer_record["name"] != reader_record["name"] && !aliased {
+                    issues.push(self.mismatch(writer, reader, path));
+                    return;
+                }
+                self.check_record(writer_record, reader_record, path, issues);
+            }
+            (Kind::Enum(writer_enum), Kind::Enum(reader_enum)) => {
+                let reader_symbols = reader_enum["symbols"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default();
+                let has_default = reader_enum.get("default").is_some();
+                for symbol in writer_enum["symbols"].as_array().into_iter().flatten() {
+                    if !has_default && !reader_symbols.contains(symbol) {
+                        issues.push(Issue {
+                            path: path.to_owned(),
+                            problem: Problem::SymbolRemoved {
+                                symbol: symbol.as_str().unwrap_or_default().to_owned(),
+                            },
+                        });
+                    }
+                }
+            }
+            (Kind::Array(writer_items), Kind::Array(reader_items)) => {
+                self.check(writer_items, reader_items, &format!("{}[]", path), issues);
+            }
+            (Kind::Map(writer_values), Kind::Map(reader_values)) => {
+                self.check(
+                    writer_values,
+                    reader_values,
+                    &format!("{}{{}}", path),
+                    issues,
+                );
+            }
+            (Kind::Named(writer_name), Kind::Named(reader_name)) => {
+                if !promotes(&writer_name, &reader_name) {
+                    issues.push(self.mismatch(writer, reader, path));
+                }
+            }
+            _ => issues.push(self.mismatch(writer, reader, path)),
+        }
+    }
+
+    fn check_record(
+        &mut self,
+        writer: &'a Value,
+        reader: &'a Value,
+        path: &str,
+        issues: &mut Vec<Issue>,
+    ) {
+        let key = (type_name(writer), type_name(reader));
+        if self.visiting.contains(&key) {
+            return;
+        }
+        self.visiting.push(key);
+
+        let writer_fields = writer["fields"].as_array().cloned().unwrap_or_default();
+        for reader_field in reader["fields"].as_array().into_iter().flatten() {
+            let name = reader_field["name"].as_str().unwrap_or_default();
+            let field_path = format!("{}.{}", path, name);
+            let writer_field = writer_fields.iter().position(|field| {
+                field["name"] == name
+                    || reader_field["aliases"]
+                        .as_array()
+                        .is_some_and(|aliases| aliases.contains(&field["name"]))
+            });
+
+            match writer_field {
+                Some(i) => {
+                    let writer_type = &writer["fields"][i]["type"];
+                    self.check(writer_type, &reader_field["type"], &field_path, issues);
+                }
+                None if reader_field.get("default").is_some() => {}
+                None => {
+                    let problem = match renamed(&writer_fields, name) {
+                        Some(to) if self.reader_is_old => Problem::Renamed { to },
+                        _ => Problem::MissingDefault {
+                            added: !self.reader_is_old,
+                            schema: type_name(&reader_field["type"]),
+                            record: type_name(reader),
+                        },
+                    };
+                    issues.push(Issue {
+                        path: field_path,
+                        problem,
+                    });
+                }
+            }
+        }
+
+        self.visiting.pop();
+    }
+
+    // a writer value is read by the reader branch of the same kind and name, falling
+    // back to the first branch it can be promoted to
+    fn check_branch(
+        &mut self,
+        writer: &'a Value,
+        reader_branches: &[&'a Value],
+        path: &str,
+        issues: &mut Vec<Issue>,
+    ) {
+        let writer_name = type_name(writer);
+        if let Some(reader) = reader_branches
+            .iter()
+            .find(|branch| type_name(branch) == writer_name)
+        {
+            self.check(writer, reader, path, issues);
+            return;
+        }
+
+        if !reader_branches
+            .iter()
+            .any(|branch| self.matches(writer, branch))
+        {
+            issues.push(Issue {
+                path: path.to_owned(),
+                problem: Problem::BranchRemoved {
+                    branch: writer_name,
+                },
+            });
+        }
+    }
+
+    fn matches(&mut self, writer: &'a Value, reader: &'a Value) -> bool {
+        let mut issues = Vec::new();
+        self.check(writer, reader, "", &mut issues);
+        issues.is_empty()
+    }
+
+    fn mismatch(&self, writer: &Value, reader: &Value, path: &str) -> Issue {
+        Issue {
+            path: path.to_owned(),
+            problem: Problem::TypeMismatch {
+                writer: type_name(writer),
+                reader: type_name(reader),
+            },
+        }
+    }
+}
+
+fn kind<'a>(schema: &'a Value, version: &Version<'a>) -> Kind<'a> {
+    match schema {
+        Value::Array(branches) => Kind::Union(branches.iter().collect()),
+        Value::String(name) => match version.lookup(name) {
+            Some(declaration) => kind(declaration, version),
+            None => Kind::Named(name.to_owned()),
+        },
+        Value::Object(object) => match object.get("type") {
+            Some(Value::String(ty)) => match ty.as_str() {
+                "record" => Kind::Record(schema),
+                "enum" => Kind::Enum(schema),
+                "array" => Kind::Array(&schema["items"]),
+                "map" => Kind::Map(&schema["values"]),
+                // logical types are read through their underlying type
+                _ => Kind::Named(ty.to_owned()),
+            },
+            Some(inner) => kind(inner, version),
+            None => Kind::Named(schema.to_string()),
+        },
+        _ => Kind::Named(schema.to_string()),
+    }
+}
+
+fn type_name(schema: &Value) -> String {
+    match schema {
+        Value::String(name) => name.to_owned(),
+        Value::Object(object) => match (object.get("name"), object.get("type")) {
+            (Some(Value::String(name)), _) => name.to_owned(),
+            (_, Some(ty)) => type_name(ty),
+            _ => schema.to_string(),
+        },
+        _ => schema.to_string(),
+    }
+}
+
+// Avro's primitive promotions, plus plain equality for everything else
+fn promotes(writer: &str, reader: &str) -> bool {
+    writer == reader
+        || matches!(
+            (writer, reader),
+            ("int", "long" | "float" | "double")
+                | ("long", "float" | "double")
+                | ("float", "double")
+                | ("string", "bytes")
+                | ("bytes", "string")
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compat::{compare, Compatibility, Problem, Version};
+    use crate::{get_schema, Grammar};
+
+    fn report(old: &str, new: &str) -> crate::compat::Report {
+        let old = get_schema(old, Grammar::TypeScript).unwrap();
+        let new = get_schema(new, Grammar::TypeScript).unwrap();
+        compare("Person", &Version::new(&old), &Version::new(&new)).unwrap()
+    }
+
+    #[test]
+    fn test_unchanged_is_full() {
+        let code = "interface Person { age: number; location: string | null; }";
+
+        assert_eq!(report(code, code).compatibility(), Compatibility::Full);
+    }
+
+    #[test]
+    fn test_added_field_breaks_backward() {
+        let report = report(
+            "interface Person { age: number; }",
+            "interface Person { age: number; name: string; }",
+        );
+
+        assert_eq!(report.compatibility(), Compatibility::Forward);
+        assert_eq!(report.backward[0].path, "Person.name");
+        assert_eq!(
+            report.backward[0].problem,
+            Problem::MissingDefault {
+                added: true,
+                schema: "string".to_owned(),
+                record: "Person".to_owned()
+            }
+        );
+        assert_eq!(
+            report.remediation(&report.backward[0]),
+            "add default null (declare `name` as `T | null`)"
+        );
+    }
+
+    #[test]
+    fn test_rename_hint() {
+        let report = report(
+            "interface Person { name: string; }",
+            "interface Person { fullName: string; }",
+        );
+
+        assert_eq!(report.compatibility(), Compatibility::None);
+        assert_eq!(
+            report.remediation(&report.backward[0]),
+            "if `name` was renamed to `fullName`, add alias `name` to `fullName`"
+        );
+        assert_eq!(
+            report.remediation(&report.forward[0]),
+            "if `name` was renamed to `fullName`, add alias `name` to `fullName`"
+        );
+    }
+
+    #[test]
+    fn test_renamed_field_hint() {
+        let old = get_schema("interface Person { name: string; }", Grammar::TypeScript).unwrap();
+        let code = "interface Person { fullName: string; email: string; }";
+        let mut new = get_schema(code, Grammar::TypeScript).unwrap();
+        let renames = crate::config::parse("[fields.Person]\nname = \"fullName\"\n").unwrap();
+        crate::renames::apply(&mut new, &renames);
+
+        let report = compare("Person", &Version::new(&old), &Version::new(&new)).unwrap();
+
+        assert_eq!(report.backward.len(), 1);
+        assert_eq!(
+            report.remediation(&report.backward[0]),
+            "add default null (declare `email` as `T | null`)"
+        );
+        assert_eq!(
+            report.forward[0].to_string(),
+            "Person.name: field renamed to `fullName` without a default"
+        );
+        assert_eq!(
+            report.remediation(&report.forward[0]),
+            "readers of the old schema don't know `name` as `fullName`: publish `name` with a default before renaming it"
+        );
+    }
+
+    #[test]
+    fn test_narrowed_union_breaks_backward() {
+        let report = report(
+            "interface Person { location: string | null; }",
+            "interface Person { location: string; }",
+        );
+
+        assert_eq!(report.compatibility(), Compatibility::Forward);
+        assert_eq!(
+            report.backward[0].problem,
+            Problem::BranchRemoved {
+                branch: "null".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn test_nested_record_changes() {
+        let report = report(
+            "interface Person { location: Location | null; } interface Location { city: string; }",
+            "interface Person { location: Location | null; } interface Location { city: number; }",
+        );
+
+        assert_eq!(report.compatibility(), Compatibility::None);
+        assert_eq!(report.backward[0].path, "Person.location.city");
+        assert_eq!(report.forward[0].path, "Person.location.city");
+    }
+
+    #[test]
+    fn test_aliases_resolve_renames() {
+        let old = get_schema("interface OldPerson { name: string; }", Grammar::TypeScript).unwrap();
+        let mut new = get_schema(
+            "interface Person { fullName: string; }",
+            Grammar::TypeScript,
+        )
+        .unwrap();
+        new[0]["aliases"] = serde_json::json!(["OldPerson"]);
+        new[0]["fields"][0]["aliases"] = serde_json::json!(["name"]);
+
+        let report = compare("Person", &Version::new(&old), &Version::new(&new)).unwrap();
+
+        // aliases only help readers, so old readers still see an unknown record name
+        assert!(report.backward.is_empty());
+        assert_eq!(
+            report.forward[0].problem,
+            Problem::TypeMismatch {
+                writer: "Person".to_owned(),
+                reader: "OldPerson".to_owned()
+            }
+        );
+    }
+}
@@ -0,0 +1,274 @@
+// Completion scripts for the shells people actually run the tool from, generated from the
+// option table below, which is checked against the usage text in the tests.
+
+pub const SUBCOMMANDS: &[&str] = &[
+    "evolve",
+    "list",
+    "graph",
+    "verify",
+    "snapshot",
+    "fixtures",
+    "sample",
+    "lint",
+    "publish",
+    "drift",
+    "batch",
+    "pull",
+    "explain",
+    "completions",
+];
+
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell"];
+
+// (flag, value) where the value is "" for switches, "file" for paths, "value" for free
+// text, or the accepted choices separated by spaces
+pub const OPTIONS: &[(&str, &str)] = &[
+    ("--jsx", ""),
+    ("--config", "file"),
+    ("--profile", "value"),
+    (
+        "--format",
+        "avro arrow parquet bigquery sql iceberg thrift fbs",
+    ),
+    ("--dialect", "postgres snowflake"),
+    ("--flatten-nested", ""),
+    ("--field-ids", "file"),
+    ("--schema-version", "value"),
+    ("--schema-version-from-git", ""),
+    ("--version-property", "value"),
+    ("--renames", "file"),
+    ("--overlay", "file"),
+    ("--plugin", "value"),
+    ("--transform", "value"),
+    ("--envelope", "cloudevents"),
+    ("--key-out", "file"),
+    (
+        "--emit",
+        "ts-const rust-serde python csharp java examples dictionary json-schema",
+    ),
+    ("--pydantic", ""),
+    ("--html", ""),
+    ("--emit-topic-union", "value"),
+    ("--root", "value"),
+    ("--keep-unreachable", ""),
+    ("--include-private", ""),
+    ("--include-classes", ""),
+    ("--unions", "union string strict"),
+    ("--infer-timestamps", ""),
+    ("--ids-as-long", ""),
+    ("--type-map", "file"),
+    ("--java-string", ""),
+    ("--methods", "skip strict getters"),
+    ("--accessors-as-fields", ""),
+    ("--evolution-safe", ""),
+    ("--strip-fields", "value"),
+    ("--namespace", "value"),
+    ("--namespace-from-path", "file"),
+    ("--namespace-template", "value"),
+    ("--require-namespace", ""),
+    ("--summary", ""),
+    ("--interactive", ""),
+    ("--quiet", ""),
+    ("--fail-on-warnings", ""),
+    ("--diagnostics", "human json"),
+    ("--fix", ""),
+    ("--field", "value"),
+    ("--count", "value"),
+    ("--wire-format", "file"),
+    ("--out-dir", "file"),
+    ("--filter", "value"),
+    ("--schema-id", "value"),
+    ("--all", ""),
+    ("--subject-map", "file"),
+    ("--subject", "value"),
+    ("--registry", "value"),
+    ("--registry-user", "value"),
+    ("--registry-token", "value"),
+    ("--registry-cert", "file"),
+    ("--registry-key", "file"),
+    ("--registry-ca", "file"),
+    ("--registry-timeout", "value"),
+    ("--registry-retries", "value"),
+    ("--registry-allow-http", ""),
+    ("--keep-going", ""),
+    ("--dry-run", ""),
+    ("--use-references", ""),
+    ("--verify-level", ""),
+];
+
+pub fn generate(shell: &str, program: &str) -> Option<String> {
+    match shell {
+        "bash" => Some(bash(program)),
+        "zsh" => Some(zsh(program)),
+        "fish" => Some(fish(program)),
+        "powershell" => Some(powershell(program)),
+        _ => None,
+    }
+}
+
+fn words() -> String {
+    let flags: Vec<&str> = OPTIONS.iter().map(|(flag, _)| *flag).collect();
+    format!("{} {}", SUBCOMMANDS.join(" "), flags.join(" "))
+}
+
+fn bash(program: &str) -> String {
+    let function = format!("_{}", program.replace('-', "_"));
+    let mut cases = String::new();
+    for (flag, value) in OPTIONS {
+        let reply = match *value {
+            "" => continue,
+            "file" => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_owned(),
+            "value" => "COMPREPLY=()".to_owned(),
+            choices => format!("COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))", choices),
+        };
+        cases.push_str(&format!("        {}) {}; return ;;\n", flag, reply));
+    }
+    cases.push_str(&format!(
+        "        completions) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;\n",
+        SHELLS.join(" ")
+    ));
+
+    format!(
+        "{function}() {{
+    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"
+    local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+    case \"$prev\" in
+{cases}    esac
+    COMPREPLY=($(compgen -W \"{words}\" -- \"$cur\") $(compgen -f -- \"$cur\"))
+}}
+complete -F {function} {program}
+",
+        words = words(),
+    )
+}
+
+fn zsh(program: &str) -> String {
+    let mut arguments = String::new();
+    for (flag, value) in OPTIONS {
+        let action = match *value {
+            "" => String::new(),
+            "file" => ":file:_files".to_owned(),
+            "value" => ":value: ".to_owned(),
+            choices => format!(":value:({})", choices),
+        };
+        arguments.push_str(&format!("    '{}{}' \\\n", flag, action));
+    }
+
+    format!(
+        "#compdef {program}
+
+_arguments \\
+{arguments}    '1:command:({subcommands})' \\
+    '*:file:_files'
+",
+        subcommands = SUBCOMMANDS.join(" "),
+    )
+}
+
+fn fish(program: &str) -> String {
+    let mut out = format!(
+        "complete -c {} -n __fish_use_subcommand -f -a \"{}\"\n",
+        program,
+        SUBCOMMANDS.join(" ")
+    );
+    out.push_str(&format!(
+        "complete -c {} -n \"__fish_seen_subcommand_from completions\" -f -a \"{}\"\n",
+        program,
+        SHELLS.join(" ")
+    ));
+    for (flag, value) in OPTIONS {
+        let long = flag.trim_start_matches("--");
+        let line = match *value {
+            "" => format!("complete -c {} -l {}", program, long),
+            "file" => format!("complete -c {} -l {} -r -F", program, long),
+            "value" => format!("complete -c {} -l {} -x", program, long),
+            choices => format!("complete -c {} -l {} -x -a \"{}\"", program, long, choices),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+fn powershell(program: &str) -> String {
+    let mut choices = String::new();
+    for (flag, value) in OPTIONS {
+        if ["", "file", "value"].contains(value) {
+            continue;
+        }
+        let quoted: Vec<String> = value.split(' ').map(|c| format!("'{}'", c)).collect();
+        choices.push_str(&format!("        '{}' = @({})\n", flag, quoted.join(", ")));
+    }
+    let quoted: Vec<String> = SHELLS.iter().map(|c| format!("'{}'", c)).collect();
+    choices.push_str(&format!(
+        "        'completions' = @({})\n",
+        quoted.join(", ")
+    ));
+    let words: Vec<String> = words().split(' ').map(|w| format!("'{}'", w)).collect();
+
+    format!(
+        "Register-ArgumentCompleter -Native -CommandName '{program}' -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $elements = @($commandAst.CommandElements | ForEach-Object {{ $_.ToString() }})
+    $previous = if ($wordToComplete) {{ $elements[-2] }} else {{ $elements[-1] }}
+    $choices = @{{
+{choices}    }}
+    $candidates = if ($choices.ContainsKey($previous)) {{ $choices[$previous] }} else {{ @({words}) }}
+    $candidates | Where-Object {{ $_ -like \"$wordToComplete*\" }} | ForEach-Object {{
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+    }}
+}}
+",
+        words = words.join(", "),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::completions::{generate, OPTIONS, SUBCOMMANDS};
+    use crate::usage;
+
+    #[test]
+    fn test_options_match_usage() {
+        let usage = usage("ts-to-avro");
+        let mentioned: Vec<&str> = usage
+            .split(|c: char| !(c.is_alphanumeric() || c == '-'))
+            .filter(|word| word.starts_with("--"))
+            .collect();
+
+        for flag in &mentioned {
+            assert!(
+                OPTIONS.iter().any(|(option, _)| option == flag),
+                "{} is missing from the completions",
+                flag
+            );
+        }
+        for (option, _) in OPTIONS {
+            assert!(mentioned.contains(option), "{} is not in the usage", option);
+        }
+        for subcommand in SUBCOMMANDS {
+            assert!(usage.contains(&format!("{} ", subcommand)));
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let bash = generate("bash", "ts-to-avro").unwrap();
+        assert!(bash.contains("complete -F _ts_to_avro ts-to-avro\n"));
+        assert!(bash.contains(
+            "--unions) COMPREPLY=($(compgen -W \"union string strict\" -- \"$cur\")); return ;;"
+        ));
+
+        let zsh = generate("zsh", "ts-to-avro").unwrap();
+        assert!(zsh.starts_with("#compdef ts-to-avro\n"));
+        assert!(zsh.contains("'--config:file:_files' \\\n"));
+
+        let fish = generate("fish", "ts-to-avro").unwrap();
+        assert!(fish.contains("complete -c ts-to-avro -l dialect -x -a \"postgres snowflake\"\n"));
+
+        let powershell = generate("powershell", "ts-to-avro").unwrap();
+        assert!(powershell.contains("'--envelope' = @('cloudevents')"));
+
+        assert!(generate("tcsh", "ts-to-avro").is_none());
+    }
+}
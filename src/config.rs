@@ -0,0 +1,637 @@
+use crate::interactive;
+use serde_json::{Map, Value};
+use std::{env, fs};
+
+pub const DEFAULT_PATH: &str = "interface2avro.toml";
+
+// An explicit path must exist; otherwise `interface2avro.toml` in the working directory is
+// used when present, and an empty config when not. `${VAR}` in a value is replaced by the
+// environment variable, and the tables of the selected profile override the rest, so that
+// one config serves every environment.
+pub fn load(path: Option<&str>, profile: Option<&str>) -> Result<Value, String> {
+    let mut config = select_profile(load_raw(path)?, profile)?;
+    interpolate(&mut config, "", &|name| env::var(name).ok())?;
+    Ok(config)
+}
+
+// `--profile prod` merges `[profile.prod]` over the config, table by table:
+//
+//   namespace = "com.acme.dev"
+//
+//   [profile.prod]
+//   namespace = "com.acme"
+//   registry.url = "https://registry.acme.com"
+//
+// The other profiles are dropped.
+pub fn select_profile(mut config: Value, profile: Option<&str>) -> Result<Value, String> {
+    let profiles = config
+        .as_object_mut()
+        .and_then(|config| config.remove("profile"))
+        .unwrap_or_default();
+    if let Some(name) = profile {
+        let overrides = profiles
+            .get(name)
+            .filter(|overrides| overrides.is_object())
+            .ok_or(format!("The config has no [profile.{}]", name))?;
+        interactive::merge(&mut config, overrides);
+    }
+    Ok(config)
+}
+
+// the config as written, with its placeholders, for saving it back
+pub fn load_raw(path: Option<&str>) -> Result<Value, String> {
+    let (path, required) = match path {
+        Some(path) => (path, true),
+        None => (DEFAULT_PATH, false),
+    };
+
+    match fs::read_to_string(path) {
+        Ok(contents) => parse(&contents).map_err(|err| format!("{}: {}", path, err)),
+        Err(_) if !required => Ok(Value::Object(Map::new())),
+        Err(err) => Err(format!("Error opening the config file {}: {}", path, err)),
+    }
+}
+
+// Parses the subset of TOML the config uses: tables, arrays of tables, dotted keys,
+// strings, numbers, booleans, arrays and inline tables.
+pub fn parse(contents: &str) -> Result<Value, String> {
+    let mut root = Value::Object(Map::new());
+    let mut table: Vec<String> = Vec::new();
+    let mut parser = Parser {
+        chars: contents.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+
+    loop {
+        parser.skip_blank();
+        match parser.peek() {
+            None => break,
+            Some('[') => {
+                let array = parser.starts_with("[[");
+                parser.pos += if array { 2 } else { 1 };
+                let path = parser.key()?;
+                parser.skip_spaces();
+                let close = if array { "]]" } else { "]" };
+                if !parser.starts_with(close) {
+                    return Err(parser.error("expected `]`"));
+                }
+                parser.pos += close.len();
+
+                let target = walk(&mut root, &path[..path.len() - 1], &parser)?;
+                let last = path.last().unwrap().to_owned();
+                if array {
+                    let entries = target
+                        .entry(last)
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    match entries {
+                        Value::Array(entries) => entries.push(Value::Object(Map::new())),
+                        _ => return Err(parser.error("key is not an array of tables")),
+                    }
+                } else {
+                    target
+                        .entry(last)
+                        .or_insert_with(|| Value::Object(Map::new()));
+                }
+                table = path;
+            }
+            Some(_) => {
+                let path = parser.key()?;
+                parser.skip_spaces();
+                if parser.peek() != Some('=') {
+                    return Err(parser.error("expected `=`"));
+                }
+                parser.pos += 1;
+                let value = parser.value()?;
+
+                let mut full: Vec<String> = table.clone();
+                full.extend(path);
+                let target = walk(&mut root, &full[..full.len() - 1], &parser)?;
+                target.insert(full.last().unwrap().to_owned(), value);
+            }
+        }
+        parser.end_of_line()?;
+    }
+
+    Ok(root)
+}
+
+// `${VAR}` is the variable's value and `${VAR:-fallback}` falls back when it's unset or
+// empty; a variable that's unset without a fallback is an error naming the key
+pub fn interpolate(
+    value: &mut Value,
+    key: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    match value {
+        Value::String(text) => {
+            let mut out = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("${") {
+                out.push_str(&rest[..start]);
+                let end = rest[start..]
+                    .find('}')
+                    .ok_or(format!("{}: unclosed `${{` in {:?}", key, text))?;
+                let placeholder = &rest[start + 2..start + end];
+                let (name, fallback) = match placeholder.split_once(":-") {
+                    Some((name, fallback)) => (name, Some(fallback)),
+                    None => (placeholder, None),
+                };
+                let found = lookup(name).filter(|found| !found.is_empty());
+                match found.as_deref().or(fallback) {
+                    Some(found) => out.push_str(found),
+                    None => {
+                        return Err(format!(
+                            "{}: the environment variable {} is not set",
+                            key, name
+                        ))
+                    }
+                }
+                rest = &rest[start + end + 1..];
+            }
+            out.push_str(rest);
+            *text = out;
+        }
+        Value::Array(items) => {
+            for item in items {
+                interpolate(item, key, lookup)?;
+            }
+        }
+        Value::Object(table) => {
+            for (name, inner) in table {
+                let key = match key.is_empty() {
+                    true => name.to_owned(),
+                    false => format!("{}.{}", key, name),
+                };
+                interpolate(inner, &key, lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Writes the config back as TOML, so interactive answers can be saved. Comments and the
+// original layout are not preserved.
+pub fn save(path: &str, config: &Value) -> Result<(), String> {
+    fs::write(path, to_toml(config)).map_err(|err| format!("Failed to write {}: {}", path, err))
+}
+
+pub fn to_toml(config: &Value) -> String {
+    let mut out = String::new();
+    if let Value::Object(table) = config {
+        write_table(&mut out, &[], table, false);
+    }
+    out.trim_start().to_owned()
+}
+
+fn write_table(out: &mut String, path: &[String], table: &Map<String, Value>, array: bool) {
+    let is_tables = |value: &Value| match value {
+        Value::Object(_) => true,
+        Value::Array(entries) => !entries.is_empty() && entries.iter().all(Value::is_object),
+        _ => false,
+    };
+
+    let scalars: Vec<(&String, &Value)> = table
+        .iter()
+        .filter(|(_, value)| !value.is_null() && !is_tables(value))
+        .collect();
+    if array || (!path.is_empty() && !scalars.is_empty()) {
+        let path: Vec<String> = path.iter().map(|key| toml_key(key)).collect();
+        match array {
+            true => out.push_str(&format!("\n[[{}]]\n", path.join("."))),
+            false => out.push_str(&format!("\n[{}]\n", path.join("."))),
+        }
+    }
+    for (key, value) in scalars {
+        out.push_str(&format!("{} = {}\n", toml_key(key), toml_value(value)));
+    }
+
+    for (key, value) in table.iter().filter(|(_, value)| is_tables(value)) {
+        let mut nested = path.to_vec();
+        nested.push(key.to_owned());
+        match value {
+            Value::Object(table) => write_table(out, &nested, table, false),
+            Value::Array(entries) => {
+                for entry in entries.iter().filter_map(Value::as_object) {
+                    write_table(out, &nested, entry, true);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    match bare {
+        true => key.to_owned(),
+        false => Value::String(key.to_owned()).to_string(),
+    }
+}
+
+fn toml_value(value: &Value) -> String {
+    match value {
+        // JSON string escapes are valid TOML basic string escapes
+        Value::String(_) | Value::Number(_) | Value::Bool(_) => value.to_string(),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(toml_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(table) => {
+            let entries: Vec<String> = table
+                .iter()
+                .map(|(key, value)| format!("{} = {}", toml_key(key), toml_value(value)))
+                .collect();
+            format!("{{ {} }}", entries.join(", "))
+        }
+        Value::Null => "\"\"".to_owned(),
+    }
+}
+
+// descends into nested tables, following the last entry of arrays of tables
+fn walk<'a>(
+    root: &'a mut Value,
+    path: &[String],
+    parser: &Parser,
+) -> Result<&'a mut Map<String, Value>, String> {
+    let mut current = root;
+    for key in path {
+        let map = match current {
+            Value::Object(map) => map,
+            _ => return Err(parser.error(&format!("`{}` is not a table", key))),
+        };
+        current = map
+            .entry(key.to_owned())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Array(entries) = current {
+            current = match entries.last_mut() {
+                Some(entry) => entry,
+                None => return Err(parser.error(&format!("`{}` is empty", key))),
+            };
+        }
+    }
+    match current {
+        Value::Object(map) => Ok(map),
+        _ => Err(parser.error("key is not a table")),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    // whitespace, newlines and comments
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') => {
+                    self.line += 1;
+                    self.pos += 1;
+                }
+                Some('\r') => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_spaces();
+        self.skip_comment();
+        match self.peek() {
+            None | Some('\n') | Some('\r') => Ok(()),
+            Some(c) => Err(self.error(&format!("unexpected `{}`", c))),
+        }
+    }
+
+    fn key(&mut self) -> Result<Vec<String>, String> {
+        let mut path = Vec::new();
+        loop {
+            self.skip_spaces();
+            let part = match self.peek() {
+                Some('"') | Some('\'') => self.string()?,
+                _ => {
+                    let start = self.pos;
+                    while self
+                        .peek()
+                        .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-')
+                    {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err(self.error("expected a key"));
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            path.push(part);
+            self.skip_spaces();
+            if self.peek() == Some('.') {
+                self.pos += 1;
+            } else {
+                return Ok(path);
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_spaces();
+        match self.peek() {
+            Some('"') | Some('\'') => Ok(Value::String(self.string()?)),
+            Some('[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                loop {
+                    self.skip_blank();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        return Ok(Value::Array(values));
+                    }
+                    values.push(self.value()?);
+                    self.skip_blank();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {}
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut table = Value::Object(Map::new());
+                loop {
+                    self.skip_spaces();
+                    if self.peek() == Some('}') {
+                        self.pos += 1;
+                        return Ok(table);
+                    }
+                    let path = self.key()?;
+                    self.skip_spaces();
+                    if self.peek() != Some('=') {
+                        return Err(self.error("expected `=`"));
+                    }
+                    self.pos += 1;
+                    let value = self.value()?;
+                    let target = walk(&mut table, &path[..path.len() - 1], self)?;
+                    target.insert(path.last().unwrap().to_owned(), value);
+                    self.skip_spaces();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some('}') => {}
+                        _ => return Err(self.error("expected `,` or `}`")),
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|c| !matches!(c, ',' | ']' | '}' | '#' | '\n' | '\r' | ' '))
+                {
+                    self.pos += 1;
+                }
+                let word: String = self.chars[start..self.pos].iter().collect();
+                let number = word.replace('_', "");
+                match word.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ => {
+                        if let Ok(int) = number.parse::<i64>() {
+                            Ok(Value::from(int))
+                        } else if let Ok(float) = number.parse::<f64>() {
+                            Ok(Value::from(float))
+                        } else {
+                            Err(self.error(&format!("invalid value `{}`", word)))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let quote = self.peek().unwrap();
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let c = match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(c) => c,
+            };
+            self.pos += 1;
+            if c == quote {
+                return Ok(out);
+            }
+            if c == '\\' && quote == '"' {
+                let escaped = self.peek().ok_or(self.error("unterminated string"))?;
+                self.pos += 1;
+                match escaped {
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    'r' => out.push('\r'),
+                    '"' => out.push('"'),
+                    '\\' => out.push('\\'),
+                    'u' => {
+                        let hex: String = self.chars
+                            [self.pos..(self.pos + 4).min(self.chars.len())]
+                            .iter()
+                            .collect();
+                        self.pos += 4;
+                        let code = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or(self.error("invalid unicode escape"))?;
+                        out.push(code);
+                    }
+                    other => return Err(self.error(&format!("invalid escape `\\{}`", other))),
+                }
+            } else {
+                out.push(c);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{interpolate, parse, select_profile, to_toml};
+
+    #[test]
+    fn test_parse() {
+        let config = parse(
+            r#"
+            # generated schemas
+            namespace = "com.acme"
+
+            [metadata]
+            version = '1.4.0'
+            from_git = false
+
+            [[envelope.fields]]
+            name = "producer"
+            type = "string"
+
+            [[envelope.fields]]
+            name = "eventTimestamp"
+            type = { type = "long", logicalType = "timestamp-millis" }
+
+            [limits]
+            branches = 4
+            ratio = 0.5
+            names = ["a", "b",
+                     "c"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config["namespace"], "com.acme");
+        assert_eq!(config["metadata"]["version"], "1.4.0");
+        assert_eq!(config["metadata"]["from_git"], false);
+        assert_eq!(config["envelope"]["fields"][0]["name"], "producer");
+        assert_eq!(
+            config["envelope"]["fields"][1]["type"]["logicalType"],
+            "timestamp-millis"
+        );
+        assert_eq!(config["limits"]["branches"], 4);
+        assert_eq!(config["limits"]["ratio"], 0.5);
+        assert_eq!(config["limits"]["names"][2], "c");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse("[metadata]\nversion = \n").unwrap_err();
+
+        assert_eq!(err, "line 2: invalid value ``");
+    }
+
+    #[test]
+    fn test_to_toml_round_trip() {
+        let config = parse(
+            r#"
+            root = "Order"
+
+            [mapping]
+            ids_as_long = true
+            types = { ObjectId = "string", "my type" = { type = "long" } }
+
+            [[mapping.decimals]]
+            pattern = "*amount*"
+            precision = 18
+
+            [[mapping.decimals]]
+            pattern = "price"
+            precision = 10
+
+            [unions]
+            strategy = "string"
+            "#,
+        )
+        .unwrap();
+
+        let written = to_toml(&config);
+
+        assert_eq!(parse(&written).unwrap(), config);
+        assert!(written.starts_with("root = \"Order\"\n\n[mapping]\nids_as_long = true\n"));
+        assert!(written.contains("\n[[mapping.decimals]]\npattern = \"price\"\n"));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let mut config = parse(
+            r#"
+            namespace = "com.acme.${ENV}"
+            subjects = ["${ENV}-{name}-value"]
+
+            [registry]
+            url = "https://${REGISTRY_HOST:-localhost:8081}/"
+            "#,
+        )
+        .unwrap();
+        let lookup = |name: &str| match name {
+            "ENV" => Some("staging".to_owned()),
+            _ => None,
+        };
+        interpolate(&mut config, "", &lookup).unwrap();
+        assert_eq!(config["namespace"], "com.acme.staging");
+        assert_eq!(config["subjects"][0], "staging-{name}-value");
+        assert_eq!(config["registry"]["url"], "https://localhost:8081/");
+
+        let mut config = parse("[registry]\nurl = \"${REGISTRY_URL}\"\n").unwrap();
+        assert_eq!(
+            interpolate(&mut config, "", &lookup),
+            Err("registry.url: the environment variable REGISTRY_URL is not set".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_select_profile() {
+        let config = parse(
+            r#"
+            namespace = "com.acme.dev"
+            [registry]
+            url = "http://localhost:8081"
+            retries = 3
+
+            [profile.prod]
+            namespace = "com.acme"
+            registry.url = "https://registry.acme.com"
+
+            [profile.staging]
+            namespace = "com.acme.staging"
+            "#,
+        )
+        .unwrap();
+
+        let prod = select_profile(config.clone(), Some("prod")).unwrap();
+        assert_eq!(
+            prod,
+            serde_json::json!({
+                "namespace": "com.acme",
+                "registry": { "url": "https://registry.acme.com", "retries": 3 }
+            })
+        );
+        let base = select_profile(config.clone(), None).unwrap();
+        assert_eq!(base["namespace"], "com.acme.dev");
+        assert!(base.get("profile").is_none());
+        assert_eq!(
+            select_profile(config, Some("qa")),
+            Err("The config has no [profile.qa]".to_owned())
+        );
+    }
+}
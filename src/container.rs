@@ -0,0 +1,110 @@
+use crate::verify;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// records per data block, as the Java writer's default sync interval roughly holds
+const BLOCK_RECORDS: usize = 1000;
+
+// An Avro object container file of `count` generated records, uncompressed, that Spark, Flink
+// or `avro-tools tojson` can read with the schema in its header. The sync marker is derived
+// from the schema so that the same schema gives the same file.
+pub fn sample(schema: &Value, count: usize) -> Result<Vec<u8>, Vec<String>> {
+    let records = verify::encoded(schema, count)?;
+    let mut sync = [0u8; 16];
+    for (i, half) in sync.chunks_mut(8).enumerate() {
+        let mut hasher = DefaultHasher::new();
+        (i, schema.to_string()).hash(&mut hasher);
+        half.copy_from_slice(&hasher.finish().to_be_bytes());
+    }
+
+    let mut out = b"Obj\x01".to_vec();
+    let metadata = [
+        ("avro.schema", schema.to_string().into_bytes()),
+        ("avro.codec", b"null".to_vec()),
+    ];
+    verify::encode_long(metadata.len() as i64, &mut out);
+    for (key, value) in metadata {
+        verify::encode_long(key.len() as i64, &mut out);
+        out.extend_from_slice(key.as_bytes());
+        verify::encode_long(value.len() as i64, &mut out);
+        out.extend_from_slice(&value);
+    }
+    verify::encode_long(0, &mut out);
+    out.extend_from_slice(&sync);
+
+    for block in records.chunks(BLOCK_RECORDS) {
+        let data = block.concat();
+        verify::encode_long(block.len() as i64, &mut out);
+        verify::encode_long(data.len() as i64, &mut out);
+        out.extend_from_slice(&data);
+        out.extend_from_slice(&sync);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::container::sample;
+    use serde_json::{json, Value};
+
+    fn read_long(input: &mut &[u8]) -> i64 {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = input[0];
+            *input = &input[1..];
+            value |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return (value >> 1) as i64 ^ -((value & 1) as i64);
+            }
+        }
+    }
+
+    fn read_bytes<'a>(input: &mut &'a [u8]) -> &'a [u8] {
+        let len = read_long(input) as usize;
+        let (bytes, rest) = input.split_at(len);
+        *input = rest;
+        bytes
+    }
+
+    #[test]
+    fn test_sample() {
+        let schema = json!({
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                { "name": "id", "type": "string" },
+                { "name": "lines", "type": { "type": "array", "items": {
+                    "type": "record", "name": "Line", "fields": [{ "name": "quantity", "type": "int" }]
+                } } }
+            ]
+        });
+
+        let file = sample(&schema, 1500).unwrap();
+
+        assert_eq!(&file[..4], b"Obj\x01");
+        let mut input = &file[4..];
+        assert_eq!(read_long(&mut input), 2);
+        assert_eq!(read_bytes(&mut input), b"avro.schema");
+        let header: Value = serde_json::from_slice(read_bytes(&mut input)).unwrap();
+        assert_eq!(header["type"], "record");
+        assert_eq!(header["fields"][1]["type"]["items"]["type"], "record");
+        assert_eq!(read_bytes(&mut input), b"avro.codec");
+        assert_eq!(read_bytes(&mut input), b"null");
+        assert_eq!(read_long(&mut input), 0);
+        let sync = &input[..16];
+        input = &input[16..];
+
+        let mut counts = Vec::new();
+        while !input.is_empty() {
+            counts.push(read_long(&mut input));
+            read_bytes(&mut input);
+            assert_eq!(&input[..16], sync);
+            input = &input[16..];
+        }
+        assert_eq!(counts, vec![1000, 500]);
+        assert_eq!(file, sample(&schema, 1500).unwrap());
+    }
+}
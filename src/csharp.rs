@@ -0,0 +1,245 @@
+use crate::symbols;
+use serde_json::Value;
+use std::collections::HashSet;
+
+// C# records of resolved schemas, with nullable reference types enabled so that only unions
+// with null are nullable, `System.DateTime` for the timestamp logical types and
+// `System.Text.Json` attributes keeping the Avro field names. The types go in the root's
+// namespace, its segments in PascalCase.
+pub fn module(schemas: &[Value]) -> String {
+    let mut models = Models::default();
+    for schema in schemas {
+        models.csharp_type(schema);
+    }
+
+    let mut out = String::from("// Generated by ts-to-avro, do not edit.\n#nullable enable\n\n");
+    out.push_str("using System;\nusing System.Collections.Generic;\n");
+    out.push_str("using System.Text.Json.Serialization;\n");
+    let namespace = schemas.first().map(symbols::full_name).and_then(|full| {
+        full.rsplit_once('.')
+            .map(|(namespace, _)| namespace.to_owned())
+    });
+    if let Some(namespace) = namespace {
+        let segments: Vec<String> = namespace.split('.').map(pascal_case).collect();
+        out.push_str(&format!("\nnamespace {};\n", segments.join(".")));
+    }
+    for item in &models.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+    out
+}
+
+#[derive(Default)]
+struct Models {
+    items: Vec<String>,
+    defined: HashSet<String>,
+}
+
+impl Models {
+    fn csharp_type(&mut self, ptype: &Value) -> String {
+        match ptype {
+            Value::String(name) => match name.as_str() {
+                "null" => "object?".to_owned(),
+                "boolean" => "bool".to_owned(),
+                "int" => "int".to_owned(),
+                "long" => "long".to_owned(),
+                "float" => "float".to_owned(),
+                "double" => "double".to_owned(),
+                "bytes" => "byte[]".to_owned(),
+                "string" => "string".to_owned(),
+                name => pascal_case(name.rsplit('.').next().unwrap_or(name)),
+            },
+            Value::Array(members) => {
+                let nullable = members.iter().any(|member| member == "null");
+                let others: Vec<&Value> = members.iter().filter(|x| *x != "null").collect();
+                let inner = match others.as_slice() {
+                    [only] => self.csharp_type(only),
+                    // C# has no union type, the branches meet at object
+                    _ => {
+                        others.iter().for_each(|member| {
+                            self.csharp_type(member);
+                        });
+                        "object".to_owned()
+                    }
+                };
+                match nullable {
+                    true => format!("{}?", inner),
+                    false => inner,
+                }
+            }
+            Value::Object(object) => {
+                let kind = object
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                match (
+                    kind.to_ascii_lowercase().as_str(),
+                    ptype["logicalType"].as_str(),
+                ) {
+                    ("record", _) => self.record(ptype),
+                    ("enum", _) => self.enumeration(ptype),
+                    (_, Some(logical)) if logical.contains("timestamp") => "DateTime".to_owned(),
+                    (_, Some("date")) => "DateOnly".to_owned(),
+                    (_, Some("uuid")) => "Guid".to_owned(),
+                    (_, Some("decimal")) => "decimal".to_owned(),
+                    ("fixed", _) => "byte[]".to_owned(),
+                    ("array", _) => format!("List<{}>", self.csharp_type(&ptype["items"])),
+                    ("map", _) => {
+                        format!("Dictionary<string, {}>", self.csharp_type(&ptype["values"]))
+                    }
+                    _ => self.csharp_type(&ptype["type"]),
+                }
+            }
+            _ => "object".to_owned(),
+        }
+    }
+
+    fn record(&mut self, schema: &Value) -> String {
+        let name = pascal_case(schema["name"].as_str().unwrap_or("Anonymous"));
+        if !self.defined.insert(name.clone()) {
+            return name;
+        }
+        let mut members = String::new();
+        for field in schema["fields"].as_array().into_iter().flatten() {
+            let field_name = field["name"].as_str().unwrap_or_default();
+            let csharp_type = self.csharp_type(&field["type"]);
+            let mut property = pascal_case(field_name);
+            if property == name {
+                // a member can't be named after the type declaring it
+                property.push_str("Value");
+            }
+            let required = match csharp_type.ends_with('?') {
+                true => "",
+                false => "required ",
+            };
+            if let Some(doc) = field["doc"].as_str() {
+                members.push_str(&format!("    /// <summary>{}</summary>\n", escape(doc)));
+            }
+            members.push_str(&format!(
+                "    [JsonPropertyName(\"{}\")]\n    public {}{} {} {{ get; init; }}\n",
+                field_name, required, csharp_type, property
+            ));
+        }
+
+        let mut item = String::new();
+        if let Some(doc) = schema["doc"].as_str() {
+            item.push_str(&format!("/// <summary>{}</summary>\n", escape(doc)));
+        }
+        item.push_str(&format!(
+            "public sealed record {}\n{{\n{}}}\n",
+            name, members
+        ));
+        self.items.push(item);
+        name
+    }
+
+    fn enumeration(&mut self, schema: &Value) -> String {
+        let name = pascal_case(schema["name"].as_str().unwrap_or("Anonymous"));
+        if !self.defined.insert(name.clone()) {
+            return name;
+        }
+        let mut item = String::from("[JsonConverter(typeof(JsonStringEnumConverter))]\n");
+        item.push_str(&format!("public enum {}\n{{\n", name));
+        for symbol in schema["symbols"].as_array().into_iter().flatten() {
+            item.push_str(&format!("    {},\n", symbol.as_str().unwrap_or_default()));
+        }
+        item.push_str("}\n");
+        self.items.push(item);
+        name
+    }
+}
+
+fn escape(doc: &str) -> String {
+    doc.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', " ")
+}
+
+// `order_line` and `orderLine` become `OrderLine`
+fn pascal_case(name: &str) -> String {
+    let pascal: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect();
+    match pascal.starts_with(|c: char| c.is_ascii_digit()) || pascal.is_empty() {
+        true => format!("_{}", pascal),
+        false => pascal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csharp::module;
+    use serde_json::json;
+
+    #[test]
+    fn test_module() {
+        let schema = json!({
+            "type": "record",
+            "name": "Order",
+            "namespace": "com.shop",
+            "doc": "A placed order",
+            "fields": [
+                { "name": "id", "type": "string" },
+                { "name": "placed_at", "type": { "type": "long", "logicalType": "timestamp-millis" } },
+                { "name": "note", "type": ["null", "string"] },
+                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["Open", "Closed"] } },
+                { "name": "lines", "type": { "type": "array", "items": {
+                    "type": "record", "name": "Line", "fields": [{ "name": "quantity", "type": "int" }]
+                } } },
+                { "name": "parent", "type": ["null", "Order"] }
+            ]
+        });
+
+        assert_eq!(
+            module(&[schema]),
+            r#"// Generated by ts-to-avro, do not edit.
+#nullable enable
+
+using System;
+using System.Collections.Generic;
+using System.Text.Json.Serialization;
+
+namespace Com.Shop;
+
+[JsonConverter(typeof(JsonStringEnumConverter))]
+public enum Status
+{
+    Open,
+    Closed,
+}
+
+public sealed record Line
+{
+    [JsonPropertyName("quantity")]
+    public required int Quantity { get; init; }
+}
+
+/// <summary>A placed order</summary>
+public sealed record Order
+{
+    [JsonPropertyName("id")]
+    public required string Id { get; init; }
+    [JsonPropertyName("placed_at")]
+    public required DateTime PlacedAt { get; init; }
+    [JsonPropertyName("note")]
+    public string? Note { get; init; }
+    [JsonPropertyName("status")]
+    public required Status Status { get; init; }
+    [JsonPropertyName("lines")]
+    public required List<Line> Lines { get; init; }
+    [JsonPropertyName("parent")]
+    public Order? Parent { get; init; }
+}
+"#
+        );
+    }
+}
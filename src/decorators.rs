@@ -0,0 +1,354 @@
+use crate::{avro, mapping};
+use serde_json::{json, Value};
+use tree_sitter::Node;
+
+// A decorator on a class property, `@Max(10)` as `Max` with the text of its arguments. The
+// constant properties of an options object, `@Column({ nullable: true })`, are in `options`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Decorator {
+    pub name: String,
+    pub arguments: Vec<String>,
+    pub options: Vec<(String, Value)>,
+}
+
+pub fn parse(node: &Node, code: &str) -> Option<Decorator> {
+    let expression = node.named_child(0)?;
+    let (function, arguments) = match expression.kind() {
+        "call_expression" => (
+            expression.child_by_field_name("function")?,
+            expression.child_by_field_name("arguments"),
+        ),
+        _ => (expression, None),
+    };
+    let mut arguments_text = Vec::new();
+    let mut options = Vec::new();
+    if let Some(arguments) = arguments {
+        let mut cursor = arguments.walk();
+        for argument in arguments.named_children(&mut cursor) {
+            arguments_text.push(argument.utf8_text(code.as_bytes()).ok()?.to_owned());
+            if argument.kind() != "object" {
+                continue;
+            }
+            let mut pairs = argument.walk();
+            for pair in argument.named_children(&mut pairs) {
+                let (key, value) = match (
+                    pair.child_by_field_name("key"),
+                    pair.child_by_field_name("value"),
+                ) {
+                    (Some(key), Some(value)) => (key, value),
+                    _ => continue,
+                };
+                if let Ok(Some(value)) = crate::initial_value(&value, code) {
+                    let key = key.utf8_text(code.as_bytes()).ok()?;
+                    options.push((
+                        key.trim_matches(|c| c == '"' || c == '\'').to_owned(),
+                        value,
+                    ));
+                }
+            }
+        }
+    }
+    Some(Decorator {
+        name: function.utf8_text(code.as_bytes()).ok()?.to_owned(),
+        arguments: arguments_text,
+        options,
+    })
+}
+
+// The class-validator decorators of a NestJS style DTO as details of the field: `@IsUUID()`
+// is the uuid logical type, `@IsInt()` an int, `@IsOptional()` a nullable union, and the
+// bounds of `@Min`, `@Max`, `@MinLength`, `@MaxLength`, `@Length` and `@Matches` are kept as
+// custom properties of the field. TypeORM's `@Column` family sets the type from the column's.
+// Other decorators are left alone. Returns the annotations they stand for, to apply with the
+// JSDoc ones.
+pub fn apply(field: &mut Value, decorators: &[Decorator]) -> Vec<(String, String)> {
+    let mut annotations = Vec::new();
+    for decorator in decorators {
+        let number = |i: usize| {
+            decorator
+                .arguments
+                .get(i)
+                .and_then(|argument| serde_json::from_str::<Value>(argument).ok())
+                .filter(Value::is_number)
+        };
+        let bound = match decorator.name.as_str() {
+            "IsUUID" => {
+                annotations.push(("avro.logicalType".to_owned(), "uuid".to_owned()));
+                None
+            }
+            "IsInt" => {
+                mapping::replace(&mut field["type"], &["double"], json!("int"));
+                None
+            }
+            "IsOptional" => {
+                nullable(field);
+                None
+            }
+            "Min" => Some(("minimum", number(0))),
+            "Max" => Some(("maximum", number(0))),
+            "MinLength" => Some(("minLength", number(0))),
+            "MaxLength" => Some(("maxLength", number(0))),
+            "Length" => {
+                if let Some(max) = number(1) {
+                    field["maxLength"] = max;
+                }
+                Some(("minLength", number(0)))
+            }
+            "Column"
+            | "PrimaryColumn"
+            | "PrimaryGeneratedColumn"
+            | "CreateDateColumn"
+            | "UpdateDateColumn"
+            | "DeleteDateColumn" => {
+                column(field, decorator);
+                None
+            }
+            "Matches" => Some((
+                "pattern",
+                decorator.arguments.first().and_then(|x| pattern(x)),
+            )),
+            _ => None,
+        };
+        if let Some((property, Some(value))) = bound {
+            field[property] = value;
+        }
+    }
+    annotations
+}
+
+// TypeORM column metadata: the column type given first or as `type`, with the `precision`
+// and `scale` of a decimal, and the `nullable` and `default` options
+fn column(field: &mut Value, decorator: &Decorator) {
+    let option = |name: &str| {
+        decorator
+            .options
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    };
+    let quoted = decorator
+        .arguments
+        .first()
+        .filter(|argument| argument.starts_with(['"', '\'']))
+        .map(|argument| argument[1..argument.len() - 1].to_owned());
+    let kind = match decorator.name.as_str() {
+        "CreateDateColumn" | "UpdateDateColumn" | "DeleteDateColumn" => {
+            Some("timestamp".to_owned())
+        }
+        // increment, the default, generates integers
+        "PrimaryGeneratedColumn" => Some(quoted.unwrap_or("int".to_owned())),
+        _ => option("type")
+            .and_then(Value::as_str)
+            .map(str::to_owned)
+            .or(quoted),
+    };
+    let precision = option("precision").and_then(Value::as_u64);
+    let ptype = match kind.as_deref().map(str::to_ascii_lowercase).as_deref() {
+        Some("int" | "integer" | "int2" | "int4" | "smallint" | "tinyint" | "mediumint") => {
+            Some(json!("int"))
+        }
+        Some("bigint" | "int8") => Some(json!("long")),
+        Some("float" | "float4" | "real") => Some(json!("float")),
+        Some("double" | "double precision" | "float8") => Some(json!("double")),
+        Some("decimal" | "numeric") => precision.map(|precision| {
+            json!({
+                "type": "bytes",
+                "logicalType": "decimal",
+                "precision": precision,
+                "scale": option("scale").and_then(Value::as_u64).unwrap_or(0)
+            })
+        }),
+        Some("boolean" | "bool") => Some(json!("boolean")),
+        Some("uuid") => Some(json!({ "type": "string", "logicalType": "uuid" })),
+        Some("date") => Some(json!({ "type": "int", "logicalType": "date" })),
+        Some("time") => Some(json!({ "type": "int", "logicalType": "time-millis" })),
+        Some("timestamp" | "timestamptz" | "datetime" | "timestamp with time zone") => {
+            Some(avro::timestamp_millis())
+        }
+        Some("bytea" | "blob" | "binary" | "varbinary") => Some(json!("bytes")),
+        _ => None,
+    };
+    if let Some(ptype) = ptype {
+        field["type"] = match field["type"].take() {
+            Value::Array(members) => avro::union(
+                members
+                    .into_iter()
+                    .map(|member| match member == "null" {
+                        true => member,
+                        false => ptype.clone(),
+                    })
+                    .collect(),
+            ),
+            _ => ptype,
+        };
+    }
+    if let Some(default) = option("default").filter(|default| !default.is_null()) {
+        field["default"] = default.clone();
+        // a union's default is of its first member
+        if let Value::Array(members) = &mut field["type"] {
+            if members[0] == "null" {
+                members.rotate_left(1);
+            }
+        }
+    }
+    if option("nullable") == Some(&json!(true)) || decorator.name == "DeleteDateColumn" {
+        nullable(field);
+    }
+}
+
+fn nullable(field: &mut Value) {
+    if avro::is_nullable(&field["type"]) {
+        return;
+    }
+    field["type"] = avro::union(vec![json!("null"), field["type"].take()]);
+    match field.get("default") {
+        // a union's default is of its first member
+        Some(default) if !default.is_null() => {
+            if let Value::Array(members) = &mut field["type"] {
+                members.rotate_left(1);
+            }
+        }
+        _ => field["default"] = Value::Null,
+    }
+}
+
+// the source of a `/^[a-z]+$/i` literal, or a pattern given as a string
+fn pattern(argument: &str) -> Option<Value> {
+    match argument.strip_prefix('/') {
+        Some(rest) => Some(json!(rest[..rest.rfind('/')?])),
+        None => serde_json::from_str::<Value>(argument)
+            .ok()
+            .filter(Value::is_string)
+            .or_else(|| {
+                let quoted = argument.strip_prefix('\'')?.strip_suffix('\'')?;
+                Some(json!(quoted))
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decorators::{apply, Decorator};
+    use serde_json::json;
+
+    fn decorator(name: &str, arguments: &[&str]) -> Decorator {
+        Decorator {
+            name: name.to_owned(),
+            arguments: arguments.iter().map(|x| x.to_string()).collect(),
+            options: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut field = json!({ "name": "count", "type": "double", "default": 1 });
+        let annotations = apply(
+            &mut field,
+            &[
+                decorator("IsInt", &[]),
+                decorator("IsOptional", &[]),
+                decorator("Min", &["-1"]),
+                decorator("Max", &["10"]),
+                decorator("ApiProperty", &["{ example: 3 }"]),
+            ],
+        );
+        assert!(annotations.is_empty());
+        assert_eq!(
+            field,
+            json!({
+                "name": "count",
+                "type": ["int", "null"],
+                "default": 1,
+                "minimum": -1,
+                "maximum": 10
+            })
+        );
+
+        let mut field = json!({ "name": "code", "type": "string" });
+        let annotations = apply(
+            &mut field,
+            &[
+                decorator("IsUUID", &["'4'"]),
+                decorator("Length", &["2", "20"]),
+                decorator("Matches", &["/^[a-z]+$/i"]),
+            ],
+        );
+        assert_eq!(
+            annotations,
+            vec![("avro.logicalType".to_owned(), "uuid".to_owned())]
+        );
+        assert_eq!(
+            field,
+            json!({
+                "name": "code",
+                "type": "string",
+                "minLength": 2,
+                "maxLength": 20,
+                "pattern": "^[a-z]+$"
+            })
+        );
+    }
+
+    #[test]
+    fn test_column() {
+        let column = |name: &str, arguments: &[&str], options: serde_json::Value| Decorator {
+            name: name.to_owned(),
+            arguments: arguments.iter().map(|x| x.to_string()).collect(),
+            options: options
+                .as_object()
+                .unwrap()
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        };
+
+        let mut field = json!({ "name": "price", "type": "double" });
+        apply(
+            &mut field,
+            &[column(
+                "Column",
+                &[],
+                json!({ "type": "decimal", "precision": 10, "scale": 2, "nullable": true }),
+            )],
+        );
+        assert_eq!(
+            field,
+            json!({
+                "name": "price",
+                "type": ["null", { "type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2 }],
+                "default": null
+            })
+        );
+
+        let mut field = json!({ "name": "stock", "type": "double" });
+        apply(
+            &mut field,
+            &[column(
+                "Column",
+                &["'int'"],
+                json!({ "default": 0, "nullable": true }),
+            )],
+        );
+        assert_eq!(
+            field,
+            json!({ "name": "stock", "type": ["int", "null"], "default": 0 })
+        );
+
+        let mut field = json!({ "name": "id", "type": "string" });
+        apply(
+            &mut field,
+            &[column("PrimaryGeneratedColumn", &["\"uuid\""], json!({}))],
+        );
+        assert_eq!(
+            field["type"],
+            json!({ "type": "string", "logicalType": "uuid" })
+        );
+
+        let mut field = json!({ "name": "deletedAt", "type": { "type": "long", "logicalType": "timestamp-millis" } });
+        apply(&mut field, &[column("DeleteDateColumn", &[], json!({}))]);
+        assert_eq!(
+            field["type"],
+            json!(["null", { "type": "long", "logicalType": "timestamp-millis" }])
+        );
+    }
+}
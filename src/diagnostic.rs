@@ -0,0 +1,308 @@
+use crate::{jsdoc, member_name, unwrap_flow_object, Grammar};
+use serde_json::{json, Value};
+use std::fmt;
+use std::ops::Range;
+use tree_sitter::{Node, Parser};
+
+// A problem with one field, rendered against its declaration in the source. Problems with
+// the record itself have no field and point at the record's name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub record: String,
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.field.is_empty() {
+            true => write!(f, "{}: {}", self.record, self.message),
+            false => write!(f, "{}.{}: {}", self.record, self.field, self.message),
+        }
+    }
+}
+
+pub struct Source<'a> {
+    pub path: &'a str,
+    pub code: &'a str,
+    pub grammar: Grammar,
+}
+
+impl Source<'_> {
+    pub fn render(&self, severity: &str, diagnostic: &Diagnostic) -> String {
+        let span = self.field_span(&diagnostic.record, &diagnostic.field);
+        self.frame(severity, &diagnostic.to_string(), span)
+    }
+
+    // byte ranges the parser could not make sense of
+    pub fn syntax_errors(&self) -> Vec<Range<usize>> {
+        let mut errors = Vec::new();
+        if let Some(tree) = self.parse() {
+            collect_errors(&tree.root_node(), &mut errors);
+        }
+        errors
+    }
+
+    //   error: Setting.value: union of boolean | double | string is not allowed in strict mode
+    //    --> src/setting.ts:3:12
+    //     |
+    //   3 |     value: string | number | boolean;
+    //     |            ^^^^^^^^^^^^^^^^^^^^^^^^^
+    pub fn frame(&self, severity: &str, message: &str, span: Option<Range<usize>>) -> String {
+        let span = match span {
+            Some(span) => span,
+            None => return format!("{}: {}\n --> {}\n", severity, message, self.path),
+        };
+
+        let line_start = self.code[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.code[span.start..]
+            .find('\n')
+            .map_or(self.code.len(), |i| span.start + i);
+        let (line, column) = self.location(span.start);
+        let width = self.code[span.start..span.end.min(line_end)]
+            .chars()
+            .count()
+            .max(1);
+
+        let number = line.to_string();
+        let gutter = " ".repeat(number.len());
+        format!(
+            "{severity}: {message}\n{gutter}--> {path}:{line}:{column}\n{gutter} |\n{number} | {text}\n{gutter} | {pad}{marks}\n",
+            path = self.path,
+            text = &self.code[line_start..line_end],
+            pad = " ".repeat(column - 1),
+            marks = "^".repeat(width),
+        )
+    }
+
+    // the machine-readable form of `render`, for `--diagnostics json`
+    pub fn json(&self, severity: &str, diagnostic: &Diagnostic) -> Value {
+        let span = self.field_span(&diagnostic.record, &diagnostic.field);
+        let mut json = self.json_frame(severity, &diagnostic.message, span);
+        json["record"] = json!(diagnostic.record);
+        json["field"] = json!(diagnostic.field);
+        json
+    }
+
+    pub fn json_frame(&self, severity: &str, message: &str, span: Option<Range<usize>>) -> Value {
+        let mut json = json!({ "severity": severity, "message": message, "file": self.path });
+        if let Some(span) = span {
+            let (line, column) = self.location(span.start);
+            json["line"] = json!(line);
+            json["column"] = json!(column);
+        }
+        json
+    }
+
+    // 1-based line and column of a byte offset
+    fn location(&self, offset: usize) -> (usize, usize) {
+        let line_start = self.code[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line = self.code[..offset].matches('\n').count() + 1;
+        let column = self.code[line_start..offset].chars().count() + 1;
+        (line, column)
+    }
+
+    // the span of the field's type, or of the whole member for anything but a property
+    pub fn field_span(&self, record: &str, field: &str) -> Option<Range<usize>> {
+        let tree = self.parse()?;
+        let member = find_member(&tree.root_node(), self.code, record, field)?;
+        let span = member
+            .child_by_field_name("type")
+            .filter(|_| {
+                member.kind() == "property_signature" || member.kind() == "public_field_definition"
+            })
+            .and_then(|annotation| annotation.named_child(0))
+            .unwrap_or(member);
+        Some(span.byte_range())
+    }
+
+    // whether a JSDoc comment with a description sits directly above the field
+    pub fn documented(&self, record: &str, field: &str) -> bool {
+        let tree = match self.parse() {
+            Some(tree) => tree,
+            None => return false,
+        };
+        let comment = find_member(&tree.root_node(), self.code, record, field)
+            .and_then(|member| member.prev_named_sibling())
+            .filter(|comment| comment.kind() == "comment")
+            .and_then(|comment| comment.utf8_text(self.code.as_bytes()).ok());
+        comment.is_some_and(|comment| {
+            comment.starts_with("/**") && !jsdoc::description(comment).is_empty()
+        })
+    }
+
+    fn parse(&self) -> Option<tree_sitter::Tree> {
+        let mut parser = Parser::new();
+        parser.set_language(self.grammar.language()).ok()?;
+        parser.parse(self.code, None)
+    }
+}
+
+// the property declaring the field, or the name of the record when the field is empty
+fn find_member<'tree>(
+    node: &Node<'tree>,
+    code: &str,
+    record: &str,
+    field: &str,
+) -> Option<Node<'tree>> {
+    let body = match node.kind() {
+        "interface_declaration"
+        | "class_declaration"
+        | "abstract_class_declaration"
+        | "enum_declaration" => node.child_by_field_name("body"),
+        "type_alias_declaration" => node
+            .child_by_field_name("value")
+            .and_then(|value| unwrap_flow_object(&value)),
+        _ => None,
+    };
+    let name_node = node.child_by_field_name("name");
+    let name = name_node.and_then(|name| name.utf8_text(code.as_bytes()).ok());
+    if let (Some(_), Some(name_node), true) = (body, name_node, field.is_empty()) {
+        if name == Some(record) {
+            return Some(name_node);
+        }
+    }
+
+    if let (Some(body), Some(record_name)) = (body, name) {
+        if record_name == record {
+            let mut cursor = body.walk();
+            for property in body.named_children(&mut cursor) {
+                if member_name(&property, code).as_deref() == Some(field) {
+                    return Some(property);
+                }
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children
+        .iter()
+        .find_map(|child| find_member(child, code, record, field))
+}
+
+fn collect_errors(node: &Node, errors: &mut Vec<Range<usize>>) {
+    if node.is_error() || node.is_missing() {
+        errors.push(node.byte_range());
+        return;
+    }
+    if !node.has_error() {
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_errors(&child, errors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostic::{Diagnostic, Source};
+    use crate::Grammar;
+
+    const CODE: &str = "interface Setting {\n    name: string;\n    value: string | number;\n    reset(): void;\n}\n";
+
+    #[test]
+    fn test_render_record() {
+        let source = Source {
+            path: "setting.ts",
+            code: CODE,
+            grammar: Grammar::TypeScript,
+        };
+        let diagnostic = Diagnostic {
+            record: "Setting".to_owned(),
+            field: String::new(),
+            message: "has no namespace".to_owned(),
+        };
+        assert_eq!(
+            source.render("error", &diagnostic),
+            "error: Setting: has no namespace\n --> setting.ts:1:11\n  |\n1 | interface Setting {\n  |           ^^^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn test_render_field() {
+        let source = Source {
+            path: "setting.ts",
+            code: CODE,
+            grammar: Grammar::TypeScript,
+        };
+        let diagnostic = Diagnostic {
+            record: "Setting".to_owned(),
+            field: "value".to_owned(),
+            message: "union of double | string is not allowed in strict mode".to_owned(),
+        };
+
+        assert_eq!(
+            source.render("error", &diagnostic),
+            "error: Setting.value: union of double | string is not allowed in strict mode
+ --> setting.ts:3:12
+  |
+3 |     value: string | number;
+  |            ^^^^^^^^^^^^^^^
+"
+        );
+    }
+
+    #[test]
+    fn test_render_without_span() {
+        let source = Source {
+            path: "setting.ts",
+            code: CODE,
+            grammar: Grammar::TypeScript,
+        };
+        let diagnostic = Diagnostic {
+            record: "Pet".to_owned(),
+            field: "name".to_owned(),
+            message: "unsupported".to_owned(),
+        };
+
+        assert_eq!(
+            source.render("warning", &diagnostic),
+            "warning: Pet.name: unsupported\n --> setting.ts\n"
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let source = Source {
+            path: "broken.ts",
+            code: "interface Broken {\n    name: ;\n}\n",
+            grammar: Grammar::TypeScript,
+        };
+
+        let errors = source.syntax_errors();
+
+        assert_eq!(errors.len(), 1);
+        assert!(source
+            .frame("warning", "syntax error", Some(errors[0].clone()))
+            .contains("--> broken.ts:2:"));
+    }
+
+    #[test]
+    fn test_json() {
+        let source = Source {
+            path: "setting.ts",
+            code: CODE,
+            grammar: Grammar::TypeScript,
+        };
+        let diagnostic = Diagnostic {
+            record: "Setting".to_owned(),
+            field: "reset".to_owned(),
+            message: "dropped method, it has no Avro equivalent".to_owned(),
+        };
+
+        assert_eq!(
+            source.json("warning", &diagnostic),
+            serde_json::json!({
+                "severity": "warning",
+                "message": "dropped method, it has no Avro equivalent",
+                "file": "setting.ts",
+                "line": 4,
+                "column": 5,
+                "record": "Setting",
+                "field": "reset"
+            })
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use serde_json::{json, Value};
+use std::collections::HashSet;
+
+// A data dictionary of resolved schemas: every record with its fields, their type, doc,
+// default and whether they take null, and every enum with its symbols. Types are written the
+// way a reader of the schemas would say them, `array<Line>` or `long (timestamp-millis)`, with
+// the null of a nullable union left to `nullable`.
+pub fn dictionary(schemas: &[Value]) -> Value {
+    let mut entries = Entries::default();
+    for schema in schemas {
+        entries.describe(schema, "");
+    }
+    json!({ "records": entries.records, "enums": entries.enums })
+}
+
+// the dictionary as a standalone HTML page, a table per record
+pub fn html(dictionary: &Value) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Data dictionary</title>\n</head>\n<body>\n<h1>Data dictionary</h1>\n",
+    );
+    for record in dictionary["records"].as_array().into_iter().flatten() {
+        out.push_str(&format!(
+            "<h2 id=\"{0}\">{0}</h2>\n",
+            escape(record["name"].as_str().unwrap_or_default())
+        ));
+        if let Some(doc) = record["doc"].as_str() {
+            out.push_str(&format!("<p>{}</p>\n", escape(doc)));
+        }
+        out.push_str("<table>\n<tr><th>Field</th><th>Type</th><th>Nullable</th><th>Default</th><th>Description</th></tr>\n");
+        for field in record["fields"].as_array().into_iter().flatten() {
+            let default = match field.get("default") {
+                Some(default) => escape(&default.to_string()),
+                None => String::new(),
+            };
+            out.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(field["name"].as_str().unwrap_or_default()),
+                escape(field["type"].as_str().unwrap_or_default()),
+                match field["nullable"].as_bool() {
+                    Some(true) => "yes",
+                    _ => "no",
+                },
+                default,
+                escape(field["doc"].as_str().unwrap_or_default())
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+    for enumeration in dictionary["enums"].as_array().into_iter().flatten() {
+        out.push_str(&format!(
+            "<h2 id=\"{0}\">{0}</h2>\n",
+            escape(enumeration["name"].as_str().unwrap_or_default())
+        ));
+        if let Some(doc) = enumeration["doc"].as_str() {
+            out.push_str(&format!("<p>{}</p>\n", escape(doc)));
+        }
+        out.push_str("<ul>\n");
+        for symbol in enumeration["symbols"].as_array().into_iter().flatten() {
+            out.push_str(&format!(
+                "<li>{}</li>\n",
+                escape(symbol.as_str().unwrap_or_default())
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[derive(Default)]
+struct Entries {
+    records: Vec<Value>,
+    enums: Vec<Value>,
+    described: HashSet<String>,
+}
+
+impl Entries {
+    // describes the named types in `ptype` and returns the way it is written
+    fn describe(&mut self, ptype: &Value, namespace: &str) -> String {
+        match ptype {
+            Value::String(name) => name.rsplit('.').next().unwrap_or(name).to_owned(),
+            Value::Array(members) => {
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|member| self.describe(member, namespace))
+                    .collect();
+                members.join(" | ")
+            }
+            Value::Object(object) => {
+                let namespace = object
+                    .get("namespace")
+                    .and_then(Value::as_str)
+                    .unwrap_or(namespace);
+                let kind = object
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let name = object.get("name").and_then(Value::as_str);
+                match (kind.to_ascii_lowercase().as_str(), name) {
+                    ("record", Some(name)) => {
+                        self.record(ptype, name, namespace);
+                        name.to_owned()
+                    }
+                    ("enum", Some(name)) => {
+                        if self.described.insert(full_name(name, namespace)) {
+                            self.enums.push(entry(ptype, name, namespace, "symbols"));
+                        }
+                        name.to_owned()
+                    }
+                    ("fixed", Some(name)) => {
+                        format!(
+                            "{} (fixed {})",
+                            name,
+                            ptype["size"].as_u64().unwrap_or_default()
+                        )
+                    }
+                    ("array", _) => format!("array<{}>", self.describe(&ptype["items"], namespace)),
+                    ("map", _) => format!("map<{}>", self.describe(&ptype["values"], namespace)),
+                    (_, _) => match ptype["logicalType"].as_str() {
+                        Some(logical) => format!("{} ({})", kind, logical),
+                        None => self.describe(&ptype["type"], namespace),
+                    },
+                }
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn record(&mut self, schema: &Value, name: &str, namespace: &str) {
+        if !self.described.insert(full_name(name, namespace)) {
+            return;
+        }
+        // the record comes before the records declared in its fields
+        let position = self.records.len();
+        self.records.push(Value::Null);
+        let mut fields = Vec::new();
+        for field in schema["fields"].as_array().into_iter().flatten() {
+            let (ptype, nullable) = match &field["type"] {
+                Value::Array(members) if members.iter().any(|member| member == "null") => {
+                    let others: Vec<Value> = members
+                        .iter()
+                        .filter(|member| *member != "null")
+                        .cloned()
+                        .collect();
+                    match others.len() {
+                        1 => (others[0].clone(), true),
+                        _ => (Value::Array(others), true),
+                    }
+                }
+                ptype => (ptype.clone(), false),
+            };
+            let mut described = entry(
+                field,
+                field["name"].as_str().unwrap_or_default(),
+                "",
+                "default",
+            );
+            described["type"] = json!(self.describe(&ptype, namespace));
+            described["nullable"] = json!(nullable);
+            fields.push(described);
+        }
+        let mut described = entry(schema, name, namespace, "");
+        described["fields"] = json!(fields);
+        self.records[position] = described;
+    }
+}
+
+// the name, namespace and doc of a declaration, with `extra` copied over when it has one
+fn entry(declaration: &Value, name: &str, namespace: &str, extra: &str) -> Value {
+    let mut entry = json!({ "name": name });
+    if !namespace.is_empty() {
+        entry["namespace"] = json!(namespace);
+    }
+    for key in ["doc", extra] {
+        if let Some(value) = declaration.get(key) {
+            entry[key] = value.clone();
+        }
+    }
+    entry
+}
+
+fn full_name(name: &str, namespace: &str) -> String {
+    match namespace.is_empty() || name.contains('.') {
+        true => name.to_owned(),
+        false => format!("{}.{}", namespace, name),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dictionary::{dictionary, html};
+    use serde_json::json;
+
+    #[test]
+    fn test_dictionary() {
+        let schema = json!({
+            "type": "record",
+            "name": "Order",
+            "namespace": "com.shop",
+            "doc": "A placed order",
+            "fields": [
+                { "name": "placedAt", "type": { "type": "long", "logicalType": "timestamp-millis" } },
+                { "name": "note", "type": ["null", "string"], "default": null, "doc": "Left by the customer" },
+                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["Open", "Closed"] } },
+                { "name": "lines", "type": { "type": "array", "items": {
+                    "type": "record", "name": "Line", "fields": [{ "name": "status", "type": "Status" }]
+                } } },
+                { "name": "payment", "type": ["Line", "string"] }
+            ]
+        });
+
+        let dictionary = dictionary(&[schema]);
+
+        assert_eq!(
+            dictionary,
+            json!({
+                "records": [
+                    {
+                        "name": "Order",
+                        "namespace": "com.shop",
+                        "doc": "A placed order",
+                        "fields": [
+                            { "name": "placedAt", "type": "long (timestamp-millis)", "nullable": false },
+                            { "name": "note", "type": "string", "nullable": true, "default": null, "doc": "Left by the customer" },
+                            { "name": "status", "type": "Status", "nullable": false },
+                            { "name": "lines", "type": "array<Line>", "nullable": false },
+                            { "name": "payment", "type": "Line | string", "nullable": false }
+                        ]
+                    },
+                    {
+                        "name": "Line",
+                        "namespace": "com.shop",
+                        "fields": [{ "name": "status", "type": "Status", "nullable": false }]
+                    }
+                ],
+                "enums": [{ "name": "Status", "namespace": "com.shop", "symbols": ["Open", "Closed"] }]
+            })
+        );
+        let html = html(&dictionary);
+        assert!(html.contains("<h2 id=\"Order\">Order</h2>\n<p>A placed order</p>\n"));
+        assert!(html.contains(
+            "<tr><td>note</td><td>string</td><td>yes</td><td>null</td><td>Left by the customer</td></tr>\n"
+        ));
+        assert!(html.contains("<td>array&lt;Line&gt;</td>"));
+        assert!(html.contains("<li>Closed</li>\n"));
+    }
+}
@@ -0,0 +1,208 @@
+use serde_json::Value;
+use std::fmt;
+
+// A field, attribute or enum symbol only one of the schemas has, or that differs between them.
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    Added(String, Value),
+    Removed(String, Value),
+    Changed(String, Value, Value),
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Added(path, new) => write!(f, "+ {}: {}", path, new),
+            Change::Removed(path, old) => write!(f, "- {}: {}", path, old),
+            Change::Changed(path, old, new) => write!(f, "~ {}: {} -> {}", path, old, new),
+        }
+    }
+}
+
+// A structural diff of two resolved schemas, one change per field or attribute that differs,
+// by its path from the root record. Records nested in both are compared field by field.
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut lines = Vec::new();
+    let path = new["name"].as_str().unwrap_or_default();
+    compare(path, old, new, &mut lines);
+    lines
+}
+
+// The changes of `diff(registered, generated)` told by the side that is ahead.
+pub fn drift(changes: &[Change]) -> Vec<String> {
+    changes
+        .iter()
+        .map(|change| match change {
+            Change::Added(path, new) => format!("not published: {}: {}", path, new),
+            Change::Removed(path, old) => format!("not in source: {}: {}", path, old),
+            Change::Changed(path, old, new) => {
+                format!(
+                    "changed: {} is {} in the registry, {} in the source",
+                    path, old, new
+                )
+            }
+        })
+        .collect()
+}
+
+fn compare(path: &str, old: &Value, new: &Value, lines: &mut Vec<Change>) {
+    if is_record(old) && is_record(new) {
+        for key in attributes(old, new) {
+            changed(
+                &format!("{} ({})", path, key),
+                &old[&key],
+                &new[&key],
+                lines,
+            );
+        }
+        let old_fields = fields(old);
+        let new_fields = fields(new);
+        for (name, ptype) in &old_fields {
+            if !new_fields.iter().any(|(x, _)| x == name) {
+                lines.push(Change::Removed(format!("{}.{}", path, name), ptype.clone()));
+            }
+        }
+        for (name, ptype) in &new_fields {
+            let field_path = format!("{}.{}", path, name);
+            match old_fields.iter().find(|(x, _)| x == name) {
+                Some((_, old_type)) => compare(&field_path, old_type, ptype, lines),
+                None => lines.push(Change::Added(field_path, ptype.clone())),
+            }
+        }
+        return;
+    }
+    if old["type"] == "enum" && new["type"] == "enum" {
+        let symbols = |schema: &Value| schema["symbols"].as_array().cloned().unwrap_or_default();
+        let (old_symbols, new_symbols) = (symbols(old), symbols(new));
+        let path = format!("{} (symbols)", path);
+        for symbol in old_symbols.iter().filter(|x| !new_symbols.contains(x)) {
+            lines.push(Change::Removed(path.clone(), symbol.clone()));
+        }
+        for symbol in new_symbols.iter().filter(|x| !old_symbols.contains(x)) {
+            lines.push(Change::Added(path.clone(), symbol.clone()));
+        }
+        return;
+    }
+    changed(path, old, new, lines);
+}
+
+fn changed(path: &str, old: &Value, new: &Value, lines: &mut Vec<Change>) {
+    let path = path.to_owned();
+    match (old, new) {
+        (old, new) if old == new => {}
+        (Value::Null, new) => lines.push(Change::Added(path, new.clone())),
+        (old, Value::Null) => lines.push(Change::Removed(path, old.clone())),
+        (old, new) => lines.push(Change::Changed(path, old.clone(), new.clone())),
+    }
+}
+
+fn is_record(schema: &Value) -> bool {
+    schema["type"] == "record"
+}
+
+// the record's own attributes, such as doc, namespace and aliases
+fn attributes(old: &Value, new: &Value) -> Vec<String> {
+    let mut keys: Vec<String> = [old, new]
+        .iter()
+        .filter_map(|schema| schema.as_object())
+        .flat_map(|object| object.keys().cloned())
+        .filter(|key| key != "fields" && key != "type")
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+fn fields(record: &Value) -> Vec<(String, Value)> {
+    record["fields"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|field| {
+            let name = field["name"].as_str().unwrap_or_default().to_owned();
+            (name, field["type"].clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::{diff, drift};
+    use serde_json::json;
+
+    #[test]
+    fn test_diff() {
+        let old = json!({
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                { "name": "total", "type": "double" },
+                { "name": "legacy", "type": "string" },
+                { "name": "line", "type": { "type": "record", "name": "Line", "fields": [
+                    { "name": "sku", "type": "string" }
+                ] } },
+                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["Open", "Closed"] } }
+            ]
+        });
+        let new = json!({
+            "type": "record",
+            "name": "Order",
+            "doc": "A placed order",
+            "fields": [
+                { "name": "total", "type": "long" },
+                { "name": "line", "type": { "type": "record", "name": "Line", "fields": [
+                    { "name": "sku", "type": "string" },
+                    { "name": "quantity", "type": "double" }
+                ] } },
+                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["Open", "Shipped"] } },
+                { "name": "note", "type": ["null", "string"], "default": null }
+            ]
+        });
+
+        let lines: Vec<String> = diff(&old, &new).iter().map(|x| x.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "+ Order (doc): \"A placed order\"",
+                "- Order.legacy: \"string\"",
+                "~ Order.total: \"double\" -> \"long\"",
+                "+ Order.line.quantity: \"double\"",
+                "- Order.status (symbols): \"Closed\"",
+                "+ Order.status (symbols): \"Shipped\"",
+                "+ Order.note: [\"null\",\"string\"]",
+            ]
+        );
+        assert!(diff(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_drift() {
+        let registered = json!({
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                { "name": "id", "type": "string" },
+                { "name": "channel", "type": "string" },
+                { "name": "total", "type": "double" }
+            ]
+        });
+        let generated = json!({
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                { "name": "id", "type": "string" },
+                { "name": "total", "type": "long" },
+                { "name": "note", "type": "string" }
+            ]
+        });
+
+        assert_eq!(
+            drift(&diff(&registered, &generated)),
+            vec![
+                "not in source: Order.channel: \"string\"",
+                "changed: Order.total is \"double\" in the registry, \"long\" in the source",
+                "not published: Order.note: \"string\"",
+            ]
+        );
+    }
+}
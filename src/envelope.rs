@@ -0,0 +1,248 @@
+use crate::mapping;
+use serde_json::{json, Value};
+
+// Appends the `[[envelope.fields]]` declared in the config to every record of the schema
+// (or only the top-level one with `envelope.root_only = true`), skipping records that
+// already declare a field of the same name. A type can be a logical type's name, as
+// `type = "timestamp-millis"`, which becomes the logical type on its underlying one.
+pub fn append_fields(schema: &mut Value, envelope: &Value) -> Result<(), String> {
+    let fields = match envelope["fields"].as_array() {
+        Some(fields) if !fields.is_empty() => fields,
+        _ => return Ok(()),
+    };
+    let fields = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            envelope_field(field).map_err(|err| format!("envelope.fields[{}]: {}", i, err))
+        })
+        .collect::<Result<Vec<Value>, String>>()?;
+    let root_only = envelope["root_only"] == true;
+    append_to_record(schema, &fields, root_only);
+    Ok(())
+}
+
+fn envelope_field(field: &Value) -> Result<Value, String> {
+    let name = field["name"].as_str().ok_or("expected a name")?;
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("{:?} isn't a valid field name", name));
+    }
+    let mut field = field.clone();
+    field["type"] = field_type(&field["type"]).map_err(|err| format!("{}: {}", name, err))?;
+    Ok(field)
+}
+
+fn field_type(ptype: &Value) -> Result<Value, String> {
+    const PRIMITIVES: [&str; 8] = [
+        "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+    ];
+    match ptype {
+        Value::String(name) if PRIMITIVES.contains(&name.as_str()) => Ok(ptype.clone()),
+        Value::String(name) => {
+            mapping::logical_type(name).ok_or(format!("unknown type {:?}", name))
+        }
+        Value::Array(members) => members.iter().map(field_type).collect(),
+        Value::Object(object) if object.get("type").is_some_and(Value::is_string) => {
+            Ok(ptype.clone())
+        }
+        Value::Null => Err("expected a type".to_owned()),
+        _ => Err(format!("{} isn't a type", ptype)),
+    }
+}
+
+fn append_to_record(record: &mut Value, envelope_fields: &[Value], root_only: bool) {
+    let fields = match record["fields"].as_array_mut() {
+        Some(fields) => fields,
+        None => return,
+    };
+
+    if !root_only {
+        for field in fields.iter_mut() {
+            append_to_nested(field, envelope_fields);
+        }
+    }
+
+    for envelope_field in envelope_fields {
+        if !fields.iter().any(|x| x["name"] == envelope_field["name"]) {
+            fields.push(envelope_field.clone());
+        }
+    }
+}
+
+// nested records are inlined in a field's type, possibly inside a union or collection
+fn append_to_nested(value: &mut Value, envelope_fields: &[Value]) {
+    match value {
+        Value::Object(object) if object.contains_key("fields") => {
+            append_to_record(value, envelope_fields, false)
+        }
+        Value::Object(object) => {
+            for key in ["type", "items", "values"] {
+                if let Some(inner) = object.get_mut(key) {
+                    append_to_nested(inner, envelope_fields);
+                }
+            }
+        }
+        Value::Array(members) => {
+            for member in members {
+                append_to_nested(member, envelope_fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Wraps the record as the `data` attribute of a CloudEvents structured-mode event, with
+// the required context attributes as strings and the optional ones nullable.
+pub fn wrap_cloudevents(schema: Value, envelope: &Value) -> Value {
+    let name = schema["name"].as_str().unwrap_or_default().to_owned();
+    let event_name = envelope["cloudevents"]["name"]
+        .as_str()
+        .map(|name| name.to_owned())
+        .unwrap_or(format!("{}CloudEvent", name));
+
+    let mut fields = Vec::new();
+    for attribute in ["id", "source", "specversion", "type"] {
+        fields.push(json!({ "name": attribute, "type": "string" }));
+    }
+    for attribute in ["datacontenttype", "dataschema", "subject"] {
+        fields.push(json!({ "name": attribute, "type": ["null", "string"], "default": null }));
+    }
+    fields.push(json!({
+        "name": "time",
+        "type": ["null", { "type": "long", "logicalType": "timestamp-millis" }],
+        "default": null
+    }));
+    fields.push(json!({ "name": "data", "type": schema }));
+
+    json!({
+        "type": "record",
+        "name": event_name,
+        "fields": fields
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::envelope::{append_fields, wrap_cloudevents};
+    use crate::{config, get_schema, merger, Grammar};
+    use serde_json::json;
+
+    const ENVELOPE: &str = r#"
+        [[envelope.fields]]
+        name = "eventTimestamp"
+        type = { type = "long", logicalType = "timestamp-millis" }
+
+        [[envelope.fields]]
+        name = "producer"
+        type = "string"
+        "#;
+
+    const CODE: &str = r#"
+        interface Person {
+            producer: string;
+            location: Location;
+        }
+
+        interface Location {
+            city: string;
+        }
+        "#;
+
+    #[test]
+    fn test_append_fields() {
+        let config = config::parse(ENVELOPE).unwrap();
+        let mut schema = merger(get_schema(CODE, Grammar::TypeScript).unwrap()).unwrap();
+
+        append_fields(&mut schema, &config["envelope"]).unwrap();
+
+        let fields = schema["fields"].as_array().unwrap();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[2]["name"], "eventTimestamp");
+        assert_eq!(fields[2]["type"]["logicalType"], "timestamp-millis");
+        assert_eq!(fields[1]["type"]["fields"][1]["name"], "eventTimestamp");
+        assert_eq!(fields[1]["type"]["fields"][2]["name"], "producer");
+    }
+
+    #[test]
+    fn test_append_fields_root_only() {
+        let mut config = config::parse(ENVELOPE).unwrap();
+        config["envelope"]["root_only"] = serde_json::json!(true);
+        let mut schema = merger(get_schema(CODE, Grammar::TypeScript).unwrap()).unwrap();
+
+        append_fields(&mut schema, &config["envelope"]).unwrap();
+
+        assert_eq!(schema["fields"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            schema["fields"][1]["type"]["fields"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_append_fields_shorthand() {
+        let config = config::parse(
+            r#"
+            [[envelope.fields]]
+            name = "eventTimestamp"
+            type = "timestamp-millis"
+
+            [[envelope.fields]]
+            name = "traceId"
+            type = ["null", "uuid"]
+            "#,
+        )
+        .unwrap();
+        let mut schema = merger(get_schema(CODE, Grammar::TypeScript).unwrap()).unwrap();
+
+        append_fields(&mut schema, &config["envelope"]).unwrap();
+
+        assert_eq!(
+            schema["fields"][2]["type"],
+            json!({ "type": "long", "logicalType": "timestamp-millis" })
+        );
+        assert_eq!(
+            schema["fields"][3]["type"],
+            json!(["null", { "type": "string", "logicalType": "uuid" }])
+        );
+
+        let invalid = [
+            (
+                "name = \"at\"\ntype = \"timestamp\"",
+                "envelope.fields[0]: at: unknown type \"timestamp\"",
+            ),
+            ("name = \"at\"", "envelope.fields[0]: at: expected a type"),
+            (
+                "name = \"event-time\"\ntype = \"long\"",
+                "envelope.fields[0]: \"event-time\" isn't a valid field name",
+            ),
+            ("type = \"long\"", "envelope.fields[0]: expected a name"),
+        ];
+        for (field, expected) in invalid {
+            let config = config::parse(&format!("[[envelope.fields]]\n{}\n", field)).unwrap();
+            assert_eq!(
+                append_fields(&mut schema, &config["envelope"]),
+                Err(expected.to_owned())
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrap_cloudevents() {
+        let schema = merger(get_schema(CODE, Grammar::TypeScript).unwrap()).unwrap();
+
+        let event = wrap_cloudevents(schema, &serde_json::json!({}));
+
+        assert_eq!(event["name"], "PersonCloudEvent");
+        assert_eq!(event["fields"][0]["name"], "id");
+        assert_eq!(event["fields"][2]["name"], "specversion");
+        assert_eq!(event["fields"][4]["type"][0], "null");
+        assert_eq!(event["fields"][7]["name"], "time");
+        assert_eq!(event["fields"][8]["name"], "data");
+        assert_eq!(event["fields"][8]["type"]["name"], "Person");
+    }
+}
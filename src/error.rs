@@ -0,0 +1,144 @@
+use std::fmt;
+
+// Why a source could not be converted. Problems in the model itself, such as unknown types,
+// are diagnostics instead; these stop the conversion of the file.
+#[derive(Debug, PartialEq)]
+pub enum ConversionError {
+    Grammar(String),
+    Parse,
+    NoDeclarations,
+    NotARecord(String),
+    Malformed {
+        kind: String,
+        line: usize,
+        column: usize,
+    },
+    Conditional {
+        text: String,
+        line: usize,
+        column: usize,
+    },
+    Symbol {
+        symbol: String,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl ConversionError {
+    pub fn malformed(node: &tree_sitter::Node) -> ConversionError {
+        ConversionError::Malformed {
+            kind: node.kind().to_owned(),
+            line: node.start_position().row + 1,
+            column: node.start_position().column + 1,
+        }
+    }
+
+    // an enum symbol Avro doesn't accept, at the member or element declaring it
+    pub fn symbol(symbol: &str, node: &tree_sitter::Node) -> ConversionError {
+        ConversionError::Symbol {
+            symbol: symbol.to_owned(),
+            line: node.start_position().row + 1,
+            column: node.start_position().column + 1,
+        }
+    }
+
+    // from a conditional type `avro::undecided` found
+    pub fn conditional(conditional: &serde_json::Value) -> ConversionError {
+        ConversionError::Conditional {
+            text: conditional["conditional"]
+                .as_str()
+                .unwrap_or_default()
+                .to_owned(),
+            line: conditional["line"].as_u64().unwrap_or_default() as usize,
+            column: conditional["column"].as_u64().unwrap_or_default() as usize,
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionError::Grammar(err) => write!(f, "Error loading the grammar: {}", err),
+            ConversionError::Parse => write!(f, "The parser gave up on the source"),
+            ConversionError::NoDeclarations => write!(f, "No interface, type or enum to convert"),
+            ConversionError::NotARecord(name) => {
+                write!(f, "{} has no fields, so it can't be the root record", name)
+            }
+            ConversionError::Malformed { kind, line, column } => {
+                write!(f, "Malformed {} at {}:{}", kind, line, column)
+            }
+            ConversionError::Conditional { text, line, column } => write!(
+                f,
+                "The conditional type `{}` at {}:{} can't be decided: only the defaults of type parameters are bound, not the type arguments of a use such as `Box<string>`",
+                text, line, column
+            ),
+            ConversionError::Symbol {
+                symbol,
+                line,
+                column,
+            } => write!(
+                f,
+                "The enum symbol {:?} at {}:{} isn't a valid Avro name, which matches [A-Za-z_][A-Za-z0-9_]*",
+                symbol, line, column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ConversionError;
+    use crate::{get_schema, merger, Grammar};
+
+    #[test]
+    fn test_conversion_errors() {
+        let empty = get_schema("", Grammar::TypeScript).unwrap();
+        assert_eq!(merger(empty), Err(ConversionError::NoDeclarations));
+
+        let schemas = get_schema("enum Role { Admin }", Grammar::TypeScript).unwrap();
+        assert_eq!(
+            merger(schemas).unwrap_err().to_string(),
+            "Role has no fields, so it can't be the root record"
+        );
+
+        let schemas = get_schema("interface A { a\n", Grammar::TypeScript).unwrap();
+        assert_eq!(merger(schemas).unwrap()["name"], "A");
+
+        let code = "interface Box<T> {\n  value: T extends string ? string : number;\n}\n";
+        assert_eq!(
+            get_schema(code, Grammar::TypeScript)
+                .unwrap_err()
+                .to_string(),
+            "The conditional type `T extends string ? string : number` at 2:10 can't be decided: only the defaults of type parameters are bound, not the type arguments of a use such as `Box<string>`"
+        );
+        let code = "interface Holder {\n  w: Wrap<string>;\n}\ninterface Wrap<T> {\n  v: T extends string ? number : boolean;\n}\n";
+        assert!(matches!(
+            get_schema(code, Grammar::TypeScript),
+            Err(ConversionError::Conditional {
+                line: 5,
+                column: 6,
+                ..
+            })
+        ));
+
+        let code = "enum Grade {\n  A = \"top\",\n  B = \"has space\",\n}\n";
+        assert_eq!(
+            get_schema(code, Grammar::TypeScript)
+                .unwrap_err()
+                .to_string(),
+            "The enum symbol \"has space\" at 3:3 isn't a valid Avro name, which matches [A-Za-z_][A-Za-z0-9_]*"
+        );
+        let code = "const GRADES = [\"1st\", \"2nd\"] as const;\n";
+        assert_eq!(
+            get_schema(code, Grammar::TypeScript).unwrap_err(),
+            ConversionError::Symbol {
+                symbol: "1st".to_owned(),
+                line: 1,
+                column: 17
+            }
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{faker, symbols};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+// One representative document per record, for documentation and contract tests: fields take
+// their `example` or the first value of their `faker` method, else their default when they have one, enums their default or first symbol, logical types a
+// plausible value of the type underneath and unions their first branch that isn't null. The
+// documents are plain JSON, as the modelled types serialize, so union values aren't wrapped
+// in their branch as the Avro JSON encoding would.
+pub fn documents(schemas: &[Value]) -> Vec<(String, Value)> {
+    schemas
+        .iter()
+        .filter(|schema| schema["type"] == "record")
+        .map(|schema| {
+            let mut names = BTreeMap::new();
+            collect(schema, "", &mut names);
+            let document = example(schema, "", &names, "", 0);
+            (symbols::full_name(schema), document)
+        })
+        .collect()
+}
+
+// past this depth a recursive type takes its null branch and collections stay empty
+const MAX_DEPTH: usize = 4;
+
+fn collect(ptype: &Value, namespace: &str, names: &mut BTreeMap<String, Value>) {
+    match ptype {
+        Value::Array(members) => members
+            .iter()
+            .for_each(|member| collect(member, namespace, names)),
+        Value::Object(object) => {
+            let namespace = object
+                .get("namespace")
+                .and_then(Value::as_str)
+                .unwrap_or(namespace);
+            if let Some(name) = object.get("name").and_then(Value::as_str) {
+                names
+                    .entry(qualified(name, namespace))
+                    .or_insert_with(|| ptype.clone());
+            }
+            for field in object
+                .get("fields")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                collect(&field["type"], namespace, names);
+            }
+            for key in ["items", "values"] {
+                if let Some(inner) = object.get(key) {
+                    collect(inner, namespace, names);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn qualified(name: &str, namespace: &str) -> String {
+    match namespace.is_empty() || name.contains('.') {
+        true => name.to_owned(),
+        false => format!("{}.{}", namespace, name),
+    }
+}
+
+// `hint` is the name of the field the value is for, which a string takes as its value
+fn example(
+    ptype: &Value,
+    namespace: &str,
+    names: &BTreeMap<String, Value>,
+    hint: &str,
+    depth: usize,
+) -> Value {
+    if depth > MAX_DEPTH * 4 {
+        // a record that contains itself with no way to end
+        return Value::Null;
+    }
+    match ptype {
+        Value::String(name) => match name.as_str() {
+            "null" => Value::Null,
+            "boolean" => json!(true),
+            "int" | "long" => json!(1),
+            "float" | "double" => json!(1.5),
+            "bytes" => json!(""),
+            "string" => json!(hint),
+            name => {
+                let definition = names.get(&qualified(name, namespace)).or_else(|| {
+                    names
+                        .iter()
+                        .find(|(full, _)| *full == name || full.rsplit('.').next() == Some(name))
+                        .map(|(_, definition)| definition)
+                });
+                match definition {
+                    Some(definition) => example(definition, namespace, names, hint, depth),
+                    None => Value::Null,
+                }
+            }
+        },
+        Value::Array(members) => {
+            let nullable = members.iter().any(|member| member == "null");
+            match members.iter().find(|member| *member != "null") {
+                Some(_) if nullable && depth > MAX_DEPTH => Value::Null,
+                Some(member) => example(member, namespace, names, hint, depth),
+                None => Value::Null,
+            }
+        }
+        Value::Object(object) => {
+            let namespace = object
+                .get("namespace")
+                .and_then(Value::as_str)
+                .unwrap_or(namespace);
+            if let Some(value) = logical(ptype) {
+                return value;
+            }
+            let kind = object
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            match kind.to_ascii_lowercase().as_str() {
+                "record" => {
+                    let mut document = Map::new();
+                    for field in object
+                        .get("fields")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                    {
+                        let name = field["name"].as_str().unwrap_or_default();
+                        let fake = field["faker"]
+                            .as_str()
+                            .and_then(|method| faker::fake(method, 0));
+                        let value = match (field.get("example"), fake, field.get("default")) {
+                            (Some(example), _, _) => example.clone(),
+                            (None, Some(fake), _) => json!(fake),
+                            (None, None, Some(default)) => default.clone(),
+                            (None, None, None) => {
+                                example(&field["type"], namespace, names, name, depth + 1)
+                            }
+                        };
+                        document.insert(name.to_owned(), value);
+                    }
+                    Value::Object(document)
+                }
+                "enum" => match ptype.get("default") {
+                    Some(default) => default.clone(),
+                    None => ptype["symbols"].get(0).cloned().unwrap_or(Value::Null),
+                },
+                "fixed" => {
+                    let size = ptype["size"].as_u64().unwrap_or_default() as usize;
+                    json!("\u{0}".repeat(size))
+                }
+                "array" => match depth > MAX_DEPTH {
+                    true => json!([]),
+                    false => json!([example(&ptype["items"], namespace, names, hint, depth + 1)]),
+                },
+                "map" => match depth > MAX_DEPTH {
+                    true => json!({}),
+                    false => json!({
+                        "key": example(&ptype["values"], namespace, names, hint, depth + 1)
+                    }),
+                },
+                _ => example(&ptype["type"], namespace, names, hint, depth),
+            }
+        }
+        _ => Value::Null,
+    }
+}
+
+// a plausible value for a logical type, around the start of 2024 for the ones in time
+fn logical(ptype: &Value) -> Option<Value> {
+    let value = match ptype["logicalType"].as_str()? {
+        "uuid" => json!("3fa85f64-5717-4562-b3fc-2c963f66afa6"),
+        "date" => json!(19723),
+        "time-millis" => json!(43_200_000),
+        "time-micros" => json!(43_200_000_000u64),
+        "timestamp-millis" | "local-timestamp-millis" => json!(1_704_067_200_000u64),
+        "timestamp-micros" | "local-timestamp-micros" => json!(1_704_067_200_000_000u64),
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::examples::documents;
+    use serde_json::json;
+
+    #[test]
+    fn test_documents() {
+        let schema = json!({
+            "type": "record",
+            "name": "Order",
+            "namespace": "com.shop",
+            "fields": [
+                { "name": "id", "type": { "type": "string", "logicalType": "uuid" } },
+                { "name": "customer", "type": "string" },
+                { "name": "email", "type": "string", "faker": "internet.email" },
+                { "name": "country", "type": "string", "example": "GB" },
+                { "name": "placedAt", "type": { "type": "long", "logicalType": "timestamp-millis" } },
+                { "name": "quantity", "type": "int", "default": 3 },
+                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["Open", "Closed"], "default": "Closed" } },
+                { "name": "note", "type": ["null", "string"] },
+                { "name": "lines", "type": { "type": "array", "items": {
+                    "type": "record", "name": "Line", "fields": [
+                        { "name": "sku", "type": "string" },
+                        { "name": "status", "type": "Status" }
+                    ]
+                } } },
+                { "name": "parent", "type": ["null", "Order"], "default": null }
+            ]
+        });
+
+        assert_eq!(
+            documents(&[schema]),
+            vec![(
+                "com.shop.Order".to_owned(),
+                json!({
+                    "id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                    "customer": "customer",
+                    "email": "olivia.brown@example.com",
+                    "country": "GB",
+                    "placedAt": 1_704_067_200_000u64,
+                    "quantity": 3,
+                    "status": "Closed",
+                    "note": "note",
+                    "lines": [{ "sku": "sku", "status": "Closed" }],
+                    "parent": null
+                })
+            )]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+// Realistic looking strings for the `@faker <method>` annotation, named after the faker.js
+// methods people already know. A seed picks the value, so the same seed gives the same one.
+pub fn fake(method: &str, seed: u64) -> Option<String> {
+    let pick = |values: &[&str]| values[(seed % values.len() as u64) as usize].to_owned();
+    let second = seed / 7 + 3;
+    let first_name = pick(FIRST_NAMES);
+    let last_name = LAST_NAMES[(second % LAST_NAMES.len() as u64) as usize];
+    let value = match method {
+        "name.firstName" | "person.firstName" => first_name,
+        "name.lastName" | "person.lastName" => last_name.to_owned(),
+        "name.fullName" | "person.fullName" => format!("{} {}", first_name, last_name),
+        "internet.userName" => format!("{}.{}{}", first_name, last_name, seed % 100).to_lowercase(),
+        "internet.email" => format!(
+            "{}.{}@{}",
+            first_name.to_lowercase(),
+            last_name.to_lowercase(),
+            pick(DOMAINS)
+        ),
+        "internet.domainName" => pick(DOMAINS),
+        "internet.url" => format!("https://www.{}/", pick(DOMAINS)),
+        "address.city" | "location.city" => pick(CITIES),
+        "address.country" | "location.country" => pick(COUNTRIES),
+        "address.countryCode" | "location.countryCode" => pick(COUNTRY_CODES),
+        "address.streetAddress" | "location.streetAddress" => {
+            format!("{} {} Street", seed % 999 + 1, last_name)
+        }
+        "address.zipCode" | "location.zipCode" => format!("{:05}", seed % 100_000),
+        "phone.number" | "phone.phoneNumber" => {
+            format!("+44 20 {:04} {:04}", seed % 10_000, second % 10_000)
+        }
+        "company.name" | "company.companyName" => {
+            format!("{} {}", last_name, pick(COMPANY_SUFFIXES))
+        }
+        "commerce.productName" => format!("{} {}", pick(ADJECTIVES), pick(PRODUCTS)),
+        "lorem.word" => pick(WORDS),
+        "lorem.sentence" => {
+            let words: Vec<&str> = (0..6)
+                .map(|i| WORDS[((seed + i * second) % WORDS.len() as u64) as usize])
+                .collect();
+            let sentence = words.join(" ");
+            let mut chars = sentence.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str() + "."
+            })
+        }
+        "string.uuid" | "datatype.uuid" => format!(
+            "{:08x}-{:04x}-4{:03x}-a{:03x}-{:012x}",
+            seed as u32,
+            (seed >> 32) as u16,
+            second % 0x1000,
+            (seed >> 20) % 0x1000,
+            second.wrapping_mul(0x9e37_79b9) % 0x1_0000_0000_0000
+        ),
+        _ => return None,
+    };
+    Some(value)
+}
+
+const FIRST_NAMES: &[&str] = &[
+    "Olivia", "Liam", "Amelia", "Noah", "Isla", "Oliver", "Ava", "Elijah", "Mia", "Lucas", "Sofia",
+    "Mateo", "Chloe", "Arjun", "Yuki", "Kofi",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Smith", "Jones", "Garcia", "Brown", "Miller", "Davis", "Wilson", "Taylor", "Nakamura",
+    "Okafor", "Silva", "Patel", "Kowalski", "Murphy",
+];
+
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net", "mail.test"];
+
+const CITIES: &[&str] = &[
+    "London",
+    "Manchester",
+    "Lisbon",
+    "Berlin",
+    "Lagos",
+    "Toronto",
+    "Osaka",
+    "Melbourne",
+    "Athens",
+    "Chicago",
+];
+
+const COUNTRIES: &[&str] = &[
+    "United Kingdom",
+    "Portugal",
+    "Germany",
+    "Nigeria",
+    "Canada",
+    "Japan",
+    "Australia",
+    "Greece",
+    "United States",
+];
+
+const COUNTRY_CODES: &[&str] = &["GB", "PT", "DE", "NG", "CA", "JP", "AU", "GR", "US"];
+
+const COMPANY_SUFFIXES: &[&str] = &["Ltd", "Group", "and Sons", "Holdings", "Labs"];
+
+const ADJECTIVES: &[&str] = &[
+    "Handmade",
+    "Sleek",
+    "Rustic",
+    "Ergonomic",
+    "Small",
+    "Gorgeous",
+];
+
+const PRODUCTS: &[&str] = &["Chair", "Lamp", "Keyboard", "Table", "Shoes", "Bottle"];
+
+const WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::faker::fake;
+
+    #[test]
+    fn test_fake() {
+        assert_eq!(
+            fake("internet.email", 0),
+            Some("olivia.brown@example.com".to_owned())
+        );
+        assert_eq!(fake("address.countryCode", 2), Some("DE".to_owned()));
+        assert_eq!(fake("lorem.sentence", 5), fake("lorem.sentence", 5));
+        assert_ne!(fake("name.fullName", 1), fake("name.fullName", 2));
+        let uuid = fake("string.uuid", 123_456_789).unwrap();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.matches('-').count(), 4);
+        assert_eq!(fake("internet.nothing", 0), None);
+    }
+}
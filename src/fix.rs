@@ -0,0 +1,138 @@
+use crate::{unwrap_flow_object, Grammar};
+use tree_sitter::{Node, Parser};
+
+#[derive(Debug, PartialEq)]
+pub struct Rewrite {
+    pub record: String,
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+// Rewrites `field: T` into `field: T | null` for each (record, field) target declared in
+// the code, which the generator turns into a nullable field defaulting to null.
+pub fn make_nullable(
+    code: &str,
+    grammar: Grammar,
+    targets: &[(String, String)],
+) -> (String, Vec<Rewrite>) {
+    let mut parser = Parser::new();
+    parser
+        .set_language(grammar.language())
+        .expect("Error loading typescript grammar");
+    let parsed = parser.parse(code, None).unwrap();
+
+    let mut edits = Vec::new();
+    let mut rewrites = Vec::new();
+    find_fields(
+        &parsed.root_node(),
+        code,
+        targets,
+        &mut edits,
+        &mut rewrites,
+    );
+
+    let mut fixed = code.to_owned();
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.0));
+    for (at, text) in edits {
+        fixed.insert_str(at, &text);
+    }
+
+    (fixed, rewrites)
+}
+
+fn find_fields(
+    node: &Node,
+    code: &str,
+    targets: &[(String, String)],
+    edits: &mut Vec<(usize, String)>,
+    rewrites: &mut Vec<Rewrite>,
+) {
+    let body = match node.kind() {
+        "interface_declaration" => node.child_by_field_name("body"),
+        "type_alias_declaration" => node
+            .child_by_field_name("value")
+            .and_then(|value| unwrap_flow_object(&value)),
+        _ => None,
+    };
+    let record = node
+        .child_by_field_name("name")
+        .map(|name| text(&name, code));
+
+    if let (Some(body), Some(record)) = (body, record) {
+        let mut cursor = body.walk();
+        for property in body.named_children(&mut cursor) {
+            if property.kind() != "property_signature" {
+                continue;
+            }
+            let field = match property.child_by_field_name("name") {
+                Some(name) => text(&name, code),
+                None => continue,
+            };
+            if !targets.iter().any(|(r, f)| *r == record && *f == field) {
+                continue;
+            }
+            let ptype = match property
+                .child_by_field_name("type")
+                .and_then(|annotation| annotation.named_child(0))
+            {
+                Some(ptype) => ptype,
+                None => continue,
+            };
+
+            let before = text(&ptype, code);
+            let after = match ptype.kind() {
+                "function_type" | "constructor_type" | "conditional_type" => {
+                    edits.push((ptype.start_byte(), "(".to_owned()));
+                    edits.push((ptype.end_byte(), ") | null".to_owned()));
+                    format!("({}) | null", before)
+                }
+                _ => {
+                    edits.push((ptype.end_byte(), " | null".to_owned()));
+                    format!("{} | null", before)
+                }
+            };
+            rewrites.push(Rewrite {
+                record: record.clone(),
+                field,
+                before,
+                after,
+            });
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        find_fields(&child, code, targets, edits, rewrites);
+    }
+}
+
+fn text(node: &Node, code: &str) -> String {
+    node.utf8_text(code.as_bytes()).unwrap().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fix::make_nullable;
+    use crate::Grammar;
+
+    #[test]
+    fn test_make_nullable() {
+        let code = "export interface Person {\n    age: number;\n    greet: () => string;\n}\n";
+        let targets = vec![
+            ("Person".to_owned(), "age".to_owned()),
+            ("Person".to_owned(), "greet".to_owned()),
+            ("Other".to_owned(), "age".to_owned()),
+        ];
+
+        let (fixed, rewrites) = make_nullable(code, Grammar::TypeScript, &targets);
+
+        assert_eq!(
+            fixed,
+            "export interface Person {\n    age: number | null;\n    greet: (() => string) | null;\n}\n"
+        );
+        assert_eq!(rewrites.len(), 2);
+        assert_eq!(rewrites[0].before, "number");
+        assert_eq!(rewrites[0].after, "number | null");
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{merger, verify};
+use serde_json::{json, Value};
+
+// A record with its schema and example values, so that code serializing the modelled types
+// can be tested against the schema it will be read with. `encoded` holds the same examples
+// with the Avro binary encoding.
+#[derive(Debug, PartialEq)]
+pub struct Fixture {
+    pub name: String,
+    pub schema: Value,
+    pub examples: Vec<Value>,
+    pub encoded: Vec<Vec<u8>>,
+}
+
+impl Fixture {
+    pub fn json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "schema": self.schema,
+            "examples": self.examples
+        })
+    }
+
+    // The examples as Kafka messages in the Confluent wire format: a zero magic byte and the
+    // big-endian id of the registered schema before the Avro encoded value, as the standard
+    // deserializers expect.
+    pub fn messages(&self, schema_id: u32) -> Vec<Vec<u8>> {
+        self.encoded
+            .iter()
+            .map(|bytes| {
+                let mut message = vec![0];
+                message.extend_from_slice(&schema_id.to_be_bytes());
+                message.extend_from_slice(bytes);
+                message
+            })
+            .collect()
+    }
+}
+
+// one fixture per record, each rooted at it
+pub fn fixtures(schemas: &[Value], count: usize) -> Result<Vec<Fixture>, String> {
+    let mut fixtures = Vec::new();
+    for (i, schema) in schemas.iter().enumerate() {
+        let name = match schema["name"].as_str() {
+            Some(name) if schema["type"] == "record" => name.to_owned(),
+            _ => continue,
+        };
+        let mut reordered = schemas.to_vec();
+        let root = reordered.remove(i);
+        reordered.insert(0, root);
+        let schema = merger(reordered).map_err(|err| err.to_string())?;
+        let failed = |problems: Vec<String>| format!("{}: {}", name, problems.join(", "));
+        let examples = verify::examples(&schema, count).map_err(failed)?;
+        let encoded = verify::encoded(&schema, count).map_err(failed)?;
+        fixtures.push(Fixture {
+            name,
+            schema,
+            examples,
+            encoded,
+        });
+    }
+    Ok(fixtures)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::fixtures;
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_fixtures() {
+        let code = r#"
+        interface Payment {
+            id: number;
+            captured: boolean;
+            method: Method;
+            reference?: string;
+            tags: string[];
+        }
+
+        enum Method { Card, Transfer }
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        let fixtures = fixtures(&schemas, 20).unwrap();
+
+        assert_eq!(fixtures.len(), 1);
+        assert_eq!(fixtures[0].name, "Payment");
+        assert_eq!(fixtures[0].schema["fields"][0]["type"], "double");
+        assert_eq!(fixtures[0].examples.len(), 20);
+        for example in &fixtures[0].examples {
+            assert!(example["id"].is_f64());
+            assert!(example["captured"].is_boolean());
+            assert!(["Card", "Transfer"].contains(&example["method"].as_str().unwrap()));
+            assert!(example["reference"].is_null() || example["reference"]["string"].is_string());
+            assert!(example["tags"].is_array());
+        }
+        assert_eq!(
+            fixtures,
+            super::fixtures(&schemas, 20).unwrap(),
+            "the same schema gives the same examples"
+        );
+    }
+
+    #[test]
+    fn test_messages() {
+        let schemas = get_schema("interface Ping { seq: number; }", Grammar::TypeScript).unwrap();
+
+        let fixtures = fixtures(&schemas, 3).unwrap();
+
+        let messages = fixtures[0].messages(258);
+        assert_eq!(messages.len(), 3);
+        for (message, encoded) in messages.iter().zip(&fixtures[0].encoded) {
+            assert_eq!(message[..5], [0, 0, 0, 1, 2]);
+            assert_eq!(message[5..], encoded[..]);
+            assert_eq!(encoded.len(), 8, "a double");
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::ir::{Field, Record, Type};
+use serde_json::{json, Value};
+
+// Arrow's JSON schema representation, as used by the Arrow integration tests
+pub fn to_arrow(record: &Record) -> Value {
+    json!({
+        "fields": record.fields.iter().map(field).collect::<Vec<Value>>(),
+        "metadata": [{ "key": "name", "value": record.name }]
+    })
+}
+
+fn field(field: &Field) -> Value {
+    let (ty, nullable) = field.ty.nullable();
+    column(&field.name, &ty, nullable)
+}
+
+fn column(name: &str, ty: &Type, nullable: bool) -> Value {
+    let (arrow_type, children) = match ty {
+        Type::Null => (json!({ "name": "null" }), vec![]),
+        Type::Boolean => (json!({ "name": "bool" }), vec![]),
+        Type::Number => (
+            json!({ "name": "floatingpoint", "precision": "DOUBLE" }),
+            vec![],
+        ),
+        // tabular formats have no enum type, the symbol is stored as text
+        Type::String | Type::Enum(_) | Type::Unknown(_) => (json!({ "name": "utf8" }), vec![]),
+        Type::Date => (
+            json!({ "name": "timestamp", "unit": "MILLISECOND", "timezone": "UTC" }),
+            vec![],
+        ),
+        Type::Array(item) => {
+            let (item, nullable) = item.nullable();
+            (
+                json!({ "name": "list" }),
+                vec![column("item", &item, nullable)],
+            )
+        }
+        Type::Map(value) => {
+            let (value, nullable) = value.nullable();
+            let entries = json!({
+                "name": "entries",
+                "nullable": false,
+                "type": { "name": "struct" },
+                "children": [
+                    column("key", &Type::String, false),
+                    column("value", &value, nullable)
+                ]
+            });
+            (json!({ "name": "map", "keysSorted": false }), vec![entries])
+        }
+        Type::Record(record) => (
+            json!({ "name": "struct" }),
+            record.fields.iter().map(field).collect(),
+        ),
+        Type::Union(members) => (
+            json!({ "name": "union", "mode": "Dense", "typeIds": (0..members.len()).collect::<Vec<usize>>() }),
+            members
+                .iter()
+                .enumerate()
+                .map(|(i, member)| column(&format!("member{}", i), member, false))
+                .collect(),
+        ),
+    };
+
+    json!({
+        "name": name,
+        "nullable": nullable,
+        "type": arrow_type,
+        "children": children
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::arrow::to_arrow;
+    use crate::ir::resolve;
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_arrow_schema() {
+        let code = r#"
+        interface Person {
+            age: number;
+            location: Location | null;
+            tags: string[];
+            createdAt: Date;
+        }
+
+        interface Location {
+            city: string;
+        }
+        "#;
+
+        let record = resolve(&get_schema(code, Grammar::TypeScript).unwrap()).unwrap();
+        let schema = to_arrow(&record);
+
+        assert_eq!(schema["fields"][0]["name"], "age");
+        assert_eq!(schema["fields"][0]["type"]["precision"], "DOUBLE");
+        assert_eq!(schema["fields"][0]["nullable"], false);
+        assert_eq!(schema["fields"][1]["type"]["name"], "struct");
+        assert_eq!(schema["fields"][1]["nullable"], true);
+        assert_eq!(schema["fields"][1]["children"][0]["name"], "city");
+        assert_eq!(schema["fields"][2]["type"]["name"], "list");
+        assert_eq!(schema["fields"][2]["children"][0]["type"]["name"], "utf8");
+        assert_eq!(schema["fields"][3]["type"]["unit"], "MILLISECOND");
+    }
+}
@@ -0,0 +1,96 @@
+use crate::ir::{Field, Record, Type};
+use serde_json::{json, Value};
+
+pub fn to_bigquery(record: &Record) -> Value {
+    Value::Array(record.fields.iter().map(field).collect())
+}
+
+fn field(field: &Field) -> Value {
+    let (ty, nullable) = field.ty.nullable();
+    let mode = if nullable { "NULLABLE" } else { "REQUIRED" };
+
+    // nulls inside an array cannot be stored, so the element nullability is dropped;
+    // arrays of arrays have no BigQuery equivalent and fall back to JSON
+    match &ty {
+        Type::Array(item) => match item.nullable().0 {
+            Type::Array(_) => column(&field.name, &ty, mode),
+            item => column(&field.name, &item, "REPEATED"),
+        },
+        _ => column(&field.name, &ty, mode),
+    }
+}
+
+fn column(name: &str, ty: &Type, mode: &str) -> Value {
+    let bigquery_type = match ty {
+        Type::Null | Type::String | Type::Enum(_) | Type::Unknown(_) => "STRING",
+        Type::Boolean => "BOOLEAN",
+        Type::Number => "FLOAT",
+        Type::Date => "TIMESTAMP",
+        Type::Record(_) | Type::Map(_) => "RECORD",
+        Type::Array(_) | Type::Union(_) => "JSON",
+    };
+
+    let mut bigquery_column = json!({
+        "name": name,
+        "type": bigquery_type,
+        "mode": mode
+    });
+
+    match ty {
+        Type::Record(record) => {
+            bigquery_column["fields"] = Value::Array(record.fields.iter().map(field).collect());
+        }
+        // maps become repeated key/value records, the usual BigQuery encoding
+        Type::Map(value) => {
+            bigquery_column["mode"] = json!("REPEATED");
+            bigquery_column["fields"] = json!([
+                column("key", &Type::String, "REQUIRED"),
+                field(&Field {
+                    name: "value".to_owned(),
+                    ty: *value.clone(),
+                })
+            ]);
+        }
+        _ => {}
+    }
+
+    bigquery_column
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::bigquery::to_bigquery;
+    use crate::ir::resolve;
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_bigquery_schema() {
+        let code = r#"
+        interface Person {
+            age: number;
+            nickname: string | null;
+            tags: string[];
+            location: Location;
+            scores: Record<string, number>;
+        }
+
+        interface Location {
+            city: string;
+        }
+        "#;
+
+        let record = resolve(&get_schema(code, Grammar::TypeScript).unwrap()).unwrap();
+        let schema = to_bigquery(&record);
+
+        assert_eq!(schema[0]["type"], "FLOAT");
+        assert_eq!(schema[0]["mode"], "REQUIRED");
+        assert_eq!(schema[1]["type"], "STRING");
+        assert_eq!(schema[1]["mode"], "NULLABLE");
+        assert_eq!(schema[2]["type"], "STRING");
+        assert_eq!(schema[2]["mode"], "REPEATED");
+        assert_eq!(schema[3]["type"], "RECORD");
+        assert_eq!(schema[3]["fields"][0]["name"], "city");
+        assert_eq!(schema[4]["mode"], "REPEATED");
+        assert_eq!(schema[4]["fields"][1]["type"], "FLOAT");
+    }
+}
@@ -0,0 +1,213 @@
+use crate::ir::{Field, Record, Type};
+
+// A FlatBuffers schema with a table for the record and every record it contains, each
+// declared before the first table that uses it, and the record as the `root_type`. Maps
+// become vectors of key/value entry tables, sorted by key, and a list of lists a vector of
+// tables wrapping the inner list, as FlatBuffers has neither. Only unions of records are
+// FlatBuffers unions; other unions keep their JSON text.
+pub fn to_fbs(record: &Record) -> String {
+    let mut declarations = Vec::new();
+    write_table(&mut declarations, &record.name, &record.fields);
+    let mut out: Vec<String> = declarations
+        .into_iter()
+        .map(|(_, declaration)| declaration)
+        .collect();
+    out.push(format!("root_type {};\n", record.name));
+    out.join("\n")
+}
+
+fn write_table(declarations: &mut Vec<(String, String)>, name: &str, fields: &[Field]) {
+    if declarations.iter().any(|(declared, _)| declared == name) {
+        return;
+    }
+    let mut out = format!("table {} {{\n", name);
+    for field in fields {
+        let (ty, nullable) = field.ty.nullable();
+        let inner = format!("{}{}", name, capitalized(&field.name));
+        let fbs_type = type_name(declarations, &ty, &inner);
+        // only scalars have a default, and only references can be required
+        let attribute = match (is_scalar(&ty), nullable) {
+            (true, true) => " = null",
+            (false, false) if !matches!(ty, Type::Union(_)) || fbs_type == "string" => {
+                " (required)"
+            }
+            _ => "",
+        };
+        out.push_str(&format!("  {}:{}{};\n", field.name, fbs_type, attribute));
+    }
+    out.push_str("}\n");
+    declarations.push((name.to_owned(), out));
+}
+
+fn is_scalar(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Boolean | Type::Number | Type::Date | Type::Enum(_)
+    )
+}
+
+fn type_name(declarations: &mut Vec<(String, String)>, ty: &Type, name: &str) -> String {
+    match ty {
+        Type::Null | Type::String | Type::Unknown(_) => "string".to_owned(),
+        Type::Boolean => "bool".to_owned(),
+        Type::Number => "double".to_owned(),
+        // milliseconds since the epoch, as Avro's timestamp-millis
+        Type::Date => "long".to_owned(),
+        Type::Enum(enumeration) => {
+            if !declarations
+                .iter()
+                .any(|(declared, _)| *declared == enumeration.name)
+            {
+                let base = if enumeration.symbols.len() <= 128 {
+                    "byte"
+                } else {
+                    "short"
+                };
+                let declaration = format!(
+                    "enum {} : {} {{ {} }}\n",
+                    enumeration.name,
+                    base,
+                    enumeration.symbols.join(", ")
+                );
+                declarations.push((enumeration.name.clone(), declaration));
+            }
+            enumeration.name.clone()
+        }
+        Type::Record(record) => {
+            write_table(declarations, &record.name, &record.fields);
+            record.name.clone()
+        }
+        Type::Array(item) => match item.nullable().0 {
+            Type::Array(inner) => {
+                let wrapper = format!("{}Item", name);
+                let items = Field {
+                    name: "items".to_owned(),
+                    ty: Type::Array(inner),
+                };
+                write_table(declarations, &wrapper, &[items]);
+                format!("[{}]", wrapper)
+            }
+            item => format!("[{}]", type_name(declarations, &item, name)),
+        },
+        Type::Map(value) => {
+            let entry = format!("{}Entry", name);
+            let value = type_name(declarations, &value.nullable().0, name);
+            if !declarations.iter().any(|(declared, _)| *declared == entry) {
+                let declaration = format!(
+                    "table {} {{\n  key:string (key);\n  value:{};\n}}\n",
+                    entry, value
+                );
+                declarations.push((entry.clone(), declaration));
+            }
+            format!("[{}]", entry)
+        }
+        Type::Union(members) => {
+            let records: Vec<&Record> = members
+                .iter()
+                .filter_map(|member| match member {
+                    Type::Record(record) => Some(record),
+                    _ => None,
+                })
+                .collect();
+            if records.len() != members.len() {
+                return "string".to_owned();
+            }
+            for record in &records {
+                write_table(declarations, &record.name, &record.fields);
+            }
+            if !declarations.iter().any(|(declared, _)| declared == name) {
+                let names: Vec<&str> = records.iter().map(|record| record.name.as_str()).collect();
+                let declaration = format!("union {} {{ {} }}\n", name, names.join(", "));
+                declarations.push((name.to_owned(), declaration));
+            }
+            name.to_owned()
+        }
+    }
+}
+
+fn capitalized(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map_or(String::new(), |first| {
+        first.to_ascii_uppercase().to_string() + chars.as_str()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::fbs::to_fbs;
+    use crate::ir::resolve;
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_fbs_tables() {
+        let code = r#"
+        interface Person {
+            age: number;
+            score?: number;
+            location: Location | null;
+            tags: string[];
+            matrix: number[][];
+            scores: Map<string, number>;
+            color: Color;
+            contact: Location | Phone;
+            note: string | number;
+            createdAt: Date;
+        }
+
+        enum Color {
+            Red,
+            Green,
+        }
+
+        interface Location {
+            city: string;
+        }
+
+        interface Phone {
+            number: string;
+        }
+        "#;
+
+        let record = resolve(&get_schema(code, Grammar::TypeScript).unwrap()).unwrap();
+
+        assert_eq!(
+            to_fbs(&record),
+            "table Location {
+  city:string (required);
+}
+
+table PersonMatrixItem {
+  items:[double] (required);
+}
+
+table PersonScoresEntry {
+  key:string (key);
+  value:double;
+}
+
+enum Color : byte { Red, Green }
+
+table Phone {
+  number:string (required);
+}
+
+union PersonContact { Location, Phone }
+
+table Person {
+  age:double;
+  score:double = null;
+  location:Location;
+  tags:[string] (required);
+  matrix:[PersonMatrixItem] (required);
+  scores:[PersonScoresEntry] (required);
+  color:Color;
+  contact:PersonContact;
+  note:string (required);
+  createdAt:long;
+}
+
+root_type Person;
+"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::ir::{Record, Type};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+
+// Field ids keyed by dotted path, persisted between runs so that adding, removing or
+// reordering fields never renumbers the columns that already exist.
+#[derive(Default)]
+pub struct FieldIds {
+    ids: BTreeMap<String, u64>,
+    last_column_id: u64,
+}
+
+impl FieldIds {
+    pub fn load(path: &str) -> Result<FieldIds, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return Ok(FieldIds::default()),
+        };
+        let value: Value = serde_json::from_str(&contents)
+            .map_err(|err| format!("Invalid field id file {}: {}", path, err))?;
+
+        let ids: BTreeMap<String, u64> = value["fields"]
+            .as_object()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter_map(|(path, id)| Some((path.to_owned(), id.as_u64()?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let highest = ids.values().copied().max().unwrap_or_default();
+        let last_column_id = value["last-column-id"].as_u64().unwrap_or_default();
+
+        Ok(FieldIds {
+            ids,
+            last_column_id: last_column_id.max(highest),
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let fields: Map<String, Value> = self
+            .ids
+            .iter()
+            .map(|(path, id)| (path.to_owned(), json!(id)))
+            .collect();
+        let value = json!({
+            "last-column-id": self.last_column_id,
+            "fields": fields
+        });
+
+        fs::write(path, format!("{:#}\n", value))
+            .map_err(|err| format!("Failed to write field id file {}: {}", path, err))
+    }
+
+    fn id(&mut self, path: &str) -> u64 {
+        if let Some(id) = self.ids.get(path) {
+            return *id;
+        }
+        self.last_column_id += 1;
+        self.ids.insert(path.to_owned(), self.last_column_id);
+        self.last_column_id
+    }
+}
+
+pub fn to_iceberg(record: &Record, ids: &mut FieldIds) -> Value {
+    let mut schema = struct_type(record, "", ids);
+    schema["schema-id"] = json!(0);
+    schema
+}
+
+fn struct_type(record: &Record, prefix: &str, ids: &mut FieldIds) -> Value {
+    // like Iceberg itself, a struct's own fields are numbered before any nested ones
+    let numbered: Vec<(u64, String)> = record
+        .fields
+        .iter()
+        .map(|field| {
+            let path = format!("{}{}", prefix, field.name);
+            (ids.id(&path), path)
+        })
+        .collect();
+
+    let fields: Vec<Value> = record
+        .fields
+        .iter()
+        .zip(numbered)
+        .map(|(field, (id, path))| {
+            let (ty, nullable) = field.ty.nullable();
+            json!({
+                "id": id,
+                "name": field.name,
+                "required": !nullable,
+                "type": iceberg_type(&ty, &path, ids)
+            })
+        })
+        .collect();
+
+    json!({ "type": "struct", "fields": fields })
+}
+
+fn iceberg_type(ty: &Type, path: &str, ids: &mut FieldIds) -> Value {
+    match ty {
+        Type::Boolean => json!("boolean"),
+        Type::Number => json!("double"),
+        Type::Date => json!("timestamptz"),
+        // Iceberg has no unions, heterogeneous values are kept as their JSON text
+        Type::Null | Type::String | Type::Enum(_) | Type::Unknown(_) | Type::Union(_) => {
+            json!("string")
+        }
+        Type::Record(record) => struct_type(record, &format!("{}.", path), ids),
+        Type::Array(item) => {
+            let (item, nullable) = item.nullable();
+            let element_path = format!("{}.element", path);
+            let element_id = ids.id(&element_path);
+            json!({
+                "type": "list",
+                "element-id": element_id,
+                "element-required": !nullable,
+                "element": iceberg_type(&item, &element_path, ids)
+            })
+        }
+        Type::Map(value) => {
+            let (value, nullable) = value.nullable();
+            let key_id = ids.id(&format!("{}.key", path));
+            let value_path = format!("{}.value", path);
+            let value_id = ids.id(&value_path);
+            json!({
+                "type": "map",
+                "key-id": key_id,
+                "key": "string",
+                "value-id": value_id,
+                "value-required": !nullable,
+                "value": iceberg_type(&value, &value_path, ids)
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::iceberg::{to_iceberg, FieldIds};
+    use crate::ir::resolve;
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_iceberg_schema() {
+        let code = r#"
+        interface Person {
+            age: number;
+            location: Location | null;
+            tags: string[];
+        }
+
+        interface Location {
+            city: string;
+        }
+        "#;
+
+        let record = resolve(&get_schema(code, Grammar::TypeScript).unwrap()).unwrap();
+        let schema = to_iceberg(&record, &mut FieldIds::default());
+
+        assert_eq!(schema["type"], "struct");
+        assert_eq!(schema["fields"][0]["id"], 1);
+        assert_eq!(schema["fields"][0]["type"], "double");
+        assert_eq!(schema["fields"][0]["required"], true);
+        assert_eq!(schema["fields"][1]["id"], 2);
+        assert_eq!(schema["fields"][1]["required"], false);
+        assert_eq!(schema["fields"][1]["type"]["fields"][0]["id"], 4);
+        assert_eq!(schema["fields"][2]["id"], 3);
+        assert_eq!(schema["fields"][2]["type"]["element-id"], 5);
+    }
+
+    #[test]
+    fn test_stable_field_ids() {
+        let before = r#"
+        interface Person {
+            age: number;
+            name: string;
+        }
+        "#;
+        let after = r#"
+        interface Person {
+            nickname: string;
+            name: string;
+        }
+        "#;
+
+        let mut ids = FieldIds::default();
+        let record = resolve(&get_schema(before, Grammar::TypeScript).unwrap()).unwrap();
+        to_iceberg(&record, &mut ids);
+
+        let record = resolve(&get_schema(after, Grammar::TypeScript).unwrap()).unwrap();
+        let schema = to_iceberg(&record, &mut ids);
+
+        assert_eq!(schema["fields"][0]["name"], "nickname");
+        assert_eq!(schema["fields"][0]["id"], 3);
+        assert_eq!(schema["fields"][1]["name"], "name");
+        assert_eq!(schema["fields"][1]["id"], 2);
+    }
+}
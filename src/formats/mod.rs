@@ -0,0 +1,79 @@
+use crate::ir::Record;
+
+pub mod arrow;
+pub mod bigquery;
+pub mod fbs;
+pub mod iceberg;
+pub mod parquet;
+pub mod sql;
+pub mod thrift;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    #[default]
+    Avro,
+    Arrow,
+    Parquet,
+    BigQuery,
+    Sql,
+    Iceberg,
+    Thrift,
+    FlatBuffers,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Format> {
+        match name {
+            "avro" => Some(Format::Avro),
+            "arrow" => Some(Format::Arrow),
+            "parquet" => Some(Format::Parquet),
+            "bigquery" => Some(Format::BigQuery),
+            "sql" => Some(Format::Sql),
+            "iceberg" => Some(Format::Iceberg),
+            "thrift" => Some(Format::Thrift),
+            "fbs" => Some(Format::FlatBuffers),
+            _ => None,
+        }
+    }
+}
+
+// renders the non-avro formats, which are all built from the resolved IR
+pub struct Options {
+    pub dialect: sql::Dialect,
+    pub flatten_nested: bool,
+    pub field_ids: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            dialect: sql::Dialect::Postgres,
+            flatten_nested: false,
+            field_ids: None,
+        }
+    }
+}
+
+pub fn render(format: Format, record: &Record, options: &Options) -> Result<String, String> {
+    let rendered = match format {
+        Format::Avro => unreachable!("avro output is produced by merger"),
+        Format::Arrow => format!("{}\n", arrow::to_arrow(record)),
+        Format::Parquet => parquet::to_parquet(record),
+        Format::BigQuery => format!("{}\n", bigquery::to_bigquery(record)),
+        Format::Sql => sql::to_sql(record, options.dialect, options.flatten_nested),
+        Format::Iceberg => {
+            let mut ids = match &options.field_ids {
+                Some(path) => iceberg::FieldIds::load(path)?,
+                None => iceberg::FieldIds::default(),
+            };
+            let schema = iceberg::to_iceberg(record, &mut ids);
+            if let Some(path) = &options.field_ids {
+                ids.save(path)?;
+            }
+            format!("{}\n", schema)
+        }
+        Format::Thrift => thrift::to_thrift(record),
+        Format::FlatBuffers => fbs::to_fbs(record),
+    };
+    Ok(rendered)
+}
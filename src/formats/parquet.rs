@@ -0,0 +1,113 @@
+use crate::ir::{Field, Record, Type};
+
+pub fn to_parquet(record: &Record) -> String {
+    let mut out = format!("message {} {{\n", record.name);
+    for field in &record.fields {
+        write_field(&mut out, field, 1);
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn write_field(out: &mut String, field: &Field, depth: usize) {
+    let (ty, nullable) = field.ty.nullable();
+    let repetition = if nullable { "optional" } else { "required" };
+    write_column(out, repetition, &field.name, &ty, depth);
+}
+
+fn write_column(out: &mut String, repetition: &str, name: &str, ty: &Type, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let primitive = match ty {
+        Type::Null | Type::String | Type::Enum(_) | Type::Unknown(_) => Some("binary {} (STRING)"),
+        Type::Boolean => Some("boolean {}"),
+        Type::Number => Some("double {}"),
+        Type::Date => Some("int64 {} (TIMESTAMP(MILLIS,true))"),
+        // parquet has no unions, so heterogeneous values are stored as JSON text
+        Type::Union(_) => Some("binary {} (JSON)"),
+        _ => None,
+    };
+
+    if let Some(primitive) = primitive {
+        out.push_str(&format!(
+            "{}{} {};\n",
+            indent,
+            repetition,
+            primitive.replace("{}", name)
+        ));
+        return;
+    }
+
+    match ty {
+        Type::Array(item) => {
+            let (item, nullable) = item.nullable();
+            out.push_str(&format!(
+                "{}{} group {} (LIST) {{\n",
+                indent, repetition, name
+            ));
+            out.push_str(&format!("{}  repeated group list {{\n", indent));
+            let repetition = if nullable { "optional" } else { "required" };
+            write_column(out, repetition, "element", &item, depth + 2);
+            out.push_str(&format!("{}  }}\n{}}}\n", indent, indent));
+        }
+        Type::Map(value) => {
+            let (value, nullable) = value.nullable();
+            out.push_str(&format!(
+                "{}{} group {} (MAP) {{\n",
+                indent, repetition, name
+            ));
+            out.push_str(&format!("{}  repeated group key_value {{\n", indent));
+            write_column(out, "required", "key", &Type::String, depth + 2);
+            let repetition = if nullable { "optional" } else { "required" };
+            write_column(out, repetition, "value", &value, depth + 2);
+            out.push_str(&format!("{}  }}\n{}}}\n", indent, indent));
+        }
+        Type::Record(record) => {
+            out.push_str(&format!("{}{} group {} {{\n", indent, repetition, name));
+            for field in &record.fields {
+                write_field(out, field, depth + 1);
+            }
+            out.push_str(&format!("{}}}\n", indent));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::parquet::to_parquet;
+    use crate::ir::resolve;
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_parquet_message() {
+        let code = r#"
+        interface Person {
+            age: number;
+            location: Location | null;
+            tags: string[];
+        }
+
+        interface Location {
+            city: string;
+        }
+        "#;
+
+        let record = resolve(&get_schema(code, Grammar::TypeScript).unwrap()).unwrap();
+
+        assert_eq!(
+            to_parquet(&record),
+            "message Person {
+  required double age;
+  optional group location {
+    required binary city (STRING);
+  }
+  required group tags (LIST) {
+    repeated group list {
+      required binary element (STRING);
+    }
+  }
+}
+"
+        );
+    }
+}
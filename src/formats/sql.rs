@@ -0,0 +1,134 @@
+use crate::ir::{Record, Type};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dialect {
+    Postgres,
+    Snowflake,
+}
+
+impl Dialect {
+    pub fn parse(name: &str) -> Option<Dialect> {
+        match name {
+            "postgres" => Some(Dialect::Postgres),
+            "snowflake" => Some(Dialect::Snowflake),
+            _ => None,
+        }
+    }
+
+    fn column_type(&self, ty: &Type) -> String {
+        let name = match (self, ty) {
+            (Dialect::Postgres, Type::Boolean) => "BOOLEAN",
+            (Dialect::Postgres, Type::Number) => "DOUBLE PRECISION",
+            (Dialect::Postgres, Type::Date) => "TIMESTAMPTZ",
+            (Dialect::Postgres, Type::Array(item)) => match item.nullable().0 {
+                Type::Array(_) | Type::Map(_) | Type::Record(_) | Type::Union(_) => "JSONB",
+                item => return format!("{}[]", self.column_type(&item)),
+            },
+            (Dialect::Postgres, Type::Map(_) | Type::Record(_) | Type::Union(_)) => "JSONB",
+            (Dialect::Postgres, _) => "TEXT",
+            (Dialect::Snowflake, Type::Boolean) => "BOOLEAN",
+            (Dialect::Snowflake, Type::Number) => "FLOAT",
+            (Dialect::Snowflake, Type::Date) => "TIMESTAMP_TZ",
+            (Dialect::Snowflake, Type::Array(_)) => "ARRAY",
+            (Dialect::Snowflake, Type::Map(_) | Type::Record(_)) => "OBJECT",
+            (Dialect::Snowflake, Type::Union(_)) => "VARIANT",
+            (Dialect::Snowflake, _) => "VARCHAR",
+        };
+        name.to_owned()
+    }
+}
+
+pub fn to_sql(record: &Record, dialect: Dialect, flatten_nested: bool) -> String {
+    let mut columns = Vec::new();
+    collect_columns(record, "", false, dialect, flatten_nested, &mut columns);
+
+    format!(
+        "CREATE TABLE {} (\n{}\n);\n",
+        quote(&record.name),
+        columns.join(",\n")
+    )
+}
+
+fn collect_columns(
+    record: &Record,
+    prefix: &str,
+    parent_nullable: bool,
+    dialect: Dialect,
+    flatten_nested: bool,
+    columns: &mut Vec<String>,
+) {
+    for field in &record.fields {
+        let (ty, nullable) = field.ty.nullable();
+        let nullable = nullable || parent_nullable;
+        let name = format!("{}{}", prefix, field.name);
+
+        match ty {
+            Type::Record(nested) if flatten_nested => {
+                let prefix = format!("{}_", name);
+                collect_columns(&nested, &prefix, nullable, dialect, flatten_nested, columns);
+            }
+            _ => {
+                let constraint = if nullable { "" } else { " NOT NULL" };
+                columns.push(format!(
+                    "  {} {}{}",
+                    quote(&name),
+                    dialect.column_type(&ty),
+                    constraint
+                ));
+            }
+        }
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::sql::{to_sql, Dialect};
+    use crate::ir::resolve;
+    use crate::{get_schema, Grammar};
+
+    const CODE: &str = r#"
+        interface Person {
+            age: number;
+            tags: string[];
+            location: Location | null;
+        }
+
+        interface Location {
+            city: string;
+        }
+        "#;
+
+    #[test]
+    fn test_postgres_json_columns() {
+        let record = resolve(&get_schema(CODE, Grammar::TypeScript).unwrap()).unwrap();
+
+        assert_eq!(
+            to_sql(&record, Dialect::Postgres, false),
+            "CREATE TABLE \"Person\" (
+  \"age\" DOUBLE PRECISION NOT NULL,
+  \"tags\" TEXT[] NOT NULL,
+  \"location\" JSONB
+);
+"
+        );
+    }
+
+    #[test]
+    fn test_snowflake_flattened() {
+        let record = resolve(&get_schema(CODE, Grammar::TypeScript).unwrap()).unwrap();
+
+        assert_eq!(
+            to_sql(&record, Dialect::Snowflake, true),
+            "CREATE TABLE \"Person\" (
+  \"age\" FLOAT NOT NULL,
+  \"tags\" ARRAY NOT NULL,
+  \"location_city\" VARCHAR
+);
+"
+        );
+    }
+}
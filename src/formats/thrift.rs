@@ -0,0 +1,155 @@
+use crate::ir::{Record, Type};
+
+// Thrift IDL for the record and every record it contains, each declared before the first
+// struct that uses it. Field ids follow the declaration order, so adding a field at the end
+// keeps the existing ids. Unions of several types become a Thrift `union` named after the
+// field, with a member per type.
+pub fn to_thrift(record: &Record) -> String {
+    let mut declarations = Vec::new();
+    write_struct(&mut declarations, record);
+    declarations
+        .into_iter()
+        .map(|(_, declaration)| declaration)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn write_struct(declarations: &mut Vec<(String, String)>, record: &Record) {
+    if declarations.iter().any(|(name, _)| *name == record.name) {
+        return;
+    }
+    let mut out = format!("struct {} {{\n", record.name);
+    for (i, field) in record.fields.iter().enumerate() {
+        let (ty, nullable) = field.ty.nullable();
+        let name = format!("{}{}", record.name, capitalized(&field.name));
+        let ty = type_name(declarations, &ty, &name);
+        let requiredness = if nullable { "optional" } else { "required" };
+        out.push_str(&format!(
+            "  {}: {} {} {}\n",
+            i + 1,
+            requiredness,
+            ty,
+            field.name
+        ));
+    }
+    out.push_str("}\n");
+    declarations.push((record.name.clone(), out));
+}
+
+fn type_name(declarations: &mut Vec<(String, String)>, ty: &Type, name: &str) -> String {
+    match ty {
+        // the symbol, as Avro writes it in JSON
+        Type::Null | Type::String | Type::Enum(_) | Type::Unknown(_) => "string".to_owned(),
+        Type::Boolean => "bool".to_owned(),
+        Type::Number => "double".to_owned(),
+        // milliseconds since the epoch, as Avro's timestamp-millis
+        Type::Date => "i64".to_owned(),
+        Type::Array(item) => format!(
+            "list<{}>",
+            type_name(declarations, &item.nullable().0, name)
+        ),
+        Type::Map(value) => format!(
+            "map<string, {}>",
+            type_name(declarations, &value.nullable().0, name)
+        ),
+        Type::Record(record) => {
+            write_struct(declarations, record);
+            record.name.clone()
+        }
+        Type::Union(members) => {
+            let mut out = format!("union {} {{\n", name);
+            let mut ids = 0;
+            for member in members {
+                let ty = type_name(declarations, member, name);
+                let field = format!("{}Value", uncapitalized(&identifier(&ty)));
+                if out.contains(&format!(" {}\n", field)) {
+                    continue;
+                }
+                ids += 1;
+                out.push_str(&format!("  {}: {} {}\n", ids, ty, field));
+            }
+            out.push_str("}\n");
+            if !declarations.iter().any(|(declared, _)| declared == name) {
+                declarations.push((name.to_owned(), out));
+            }
+            name.to_owned()
+        }
+    }
+}
+
+// `list<string>` as `listString`, for naming a union member
+fn identifier(ty: &str) -> String {
+    ty.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .enumerate()
+        .map(|(i, word)| match i {
+            0 => word.to_owned(),
+            _ => capitalized(word),
+        })
+        .collect()
+}
+
+fn capitalized(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map_or(String::new(), |first| {
+        first.to_ascii_uppercase().to_string() + chars.as_str()
+    })
+}
+
+fn uncapitalized(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map_or(String::new(), |first| {
+        first.to_ascii_lowercase().to_string() + chars.as_str()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::thrift::to_thrift;
+    use crate::ir::resolve;
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_thrift_structs() {
+        let code = r#"
+        interface Person {
+            age: number;
+            location: Location | null;
+            tags: string[];
+            scores: Map<string, number>;
+            contact: string | Location | null;
+            createdAt: Date;
+        }
+
+        interface Location {
+            city: string;
+            active?: boolean;
+        }
+        "#;
+
+        let record = resolve(&get_schema(code, Grammar::TypeScript).unwrap()).unwrap();
+
+        assert_eq!(
+            to_thrift(&record),
+            "struct Location {
+  1: required string city
+  2: optional bool active
+}
+
+union PersonContact {
+  1: string stringValue
+  2: Location locationValue
+}
+
+struct Person {
+  1: required double age
+  2: optional Location location
+  3: required list<string> tags
+  4: required map<string, double> scores
+  5: optional PersonContact contact
+  6: required i64 createdAt
+}
+"
+        );
+    }
+}
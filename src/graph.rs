@@ -0,0 +1,156 @@
+use crate::list::Declaration;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+// Which declarations refer to which, across every file given or imported. References to
+// names declared nowhere, such as `Date` or a package type, have no edge.
+pub fn edges(declarations: &[Declaration]) -> Vec<(&str, &str)> {
+    declarations
+        .iter()
+        .flat_map(|declaration| {
+            declaration
+                .references
+                .iter()
+                .filter(|reference| declarations.iter().any(|x| x.name == **reference))
+                .map(|reference| (declaration.name.as_str(), reference.as_str()))
+        })
+        .collect()
+}
+
+// An interface declared more than once is merged by TypeScript into one, so it is one node,
+// at its first declaration, with the references of every declaration.
+fn merge(declarations: &[Declaration]) -> Vec<Declaration> {
+    let mut merged: Vec<Declaration> = Vec::new();
+    for declaration in declarations {
+        match merged.iter_mut().find(|x| x.name == declaration.name) {
+            Some(first) => first
+                .references
+                .extend(declaration.references.iter().cloned()),
+            None => merged.push(declaration.clone()),
+        }
+    }
+    merged
+}
+
+pub fn json(declarations: &[Declaration]) -> Value {
+    let declarations = &merge(declarations);
+    let nodes: Vec<Value> = declarations
+        .iter()
+        .map(|declaration| {
+            json!({
+                "name": declaration.name,
+                "kind": declaration.kind,
+                "file": declaration.file,
+                "line": declaration.line
+            })
+        })
+        .collect();
+    let edges: Vec<Value> = edges(declarations)
+        .iter()
+        .map(|(from, to)| json!({ "from": from, "to": to }))
+        .collect();
+    json!({ "nodes": nodes, "edges": edges })
+}
+
+// declarations are grouped in one cluster per file
+pub fn dot(declarations: &[Declaration]) -> String {
+    let declarations = &merge(declarations);
+    let mut files: BTreeMap<&str, Vec<&Declaration>> = BTreeMap::new();
+    for declaration in declarations {
+        files
+            .entry(declaration.file.as_str())
+            .or_default()
+            .push(declaration);
+    }
+
+    let mut out = String::from("digraph types {\n    node [shape=box];\n");
+    for (i, (file, declared)) in files.iter().enumerate() {
+        out.push_str(&format!(
+            "    subgraph cluster_{} {{\n        label={};\n",
+            i,
+            quote(file)
+        ));
+        for declaration in declared {
+            let shape = match declaration.kind {
+                "enum" => " [shape=ellipse]",
+                _ => "",
+            };
+            out.push_str(&format!("        {}{};\n", quote(&declaration.name), shape));
+        }
+        out.push_str("    }\n");
+    }
+    for (from, to) in edges(declarations) {
+        out.push_str(&format!("    {} -> {};\n", quote(from), quote(to)));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{dot, json};
+    use crate::list::declarations;
+    use crate::Grammar;
+
+    #[test]
+    fn test_graph() {
+        let mut found = declarations(
+            "interface User {\n    address: Address;\n    role: Role;\n    created: Date;\n}\n",
+            Grammar::TypeScript,
+            "src/user.ts",
+        );
+        found.extend(declarations(
+            "interface Address {\n    city: string;\n}\nenum Role { Admin }\n",
+            Grammar::TypeScript,
+            "src/address.ts",
+        ));
+
+        assert_eq!(
+            json(&found)["edges"],
+            serde_json::json!([
+                { "from": "User", "to": "Address" },
+                { "from": "User", "to": "Role" }
+            ])
+        );
+        assert_eq!(json(&found)["nodes"][2]["kind"], "enum");
+        assert_eq!(
+            dot(&found),
+            r#"digraph types {
+    node [shape=box];
+    subgraph cluster_0 {
+        label="src/address.ts";
+        "Address";
+        "Role" [shape=ellipse];
+    }
+    subgraph cluster_1 {
+        label="src/user.ts";
+        "User";
+    }
+    "User" -> "Address";
+    "User" -> "Role";
+}
+"#
+        );
+
+        found.extend(declarations(
+            "interface User {\n    billing: Address;\n}\n",
+            Grammar::TypeScript,
+            "src/user.extra.ts",
+        ));
+        let graph = json(&found);
+        assert_eq!(graph["nodes"].as_array().unwrap().len(), 3);
+        assert_eq!(graph["nodes"][0]["file"], "src/user.ts");
+        assert_eq!(
+            graph["edges"],
+            serde_json::json!([
+                { "from": "User", "to": "Address" },
+                { "from": "User", "to": "Role" }
+            ])
+        );
+        assert_eq!(dot(&found).matches("\"User\";").count(), 1);
+    }
+}
@@ -0,0 +1,576 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+// Just enough HTTP/1.1 to talk to a schema registry: one request per connection, with
+// `Content-Length` or chunked responses. Anything needing TLS (an `https://` registry or
+// proxy, or a client certificate) is handed to `curl` instead, which also tunnels through
+// `HTTPS_PROXY` with CONNECT.
+
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+// How requests are sent: the time allowed for connecting and for each read and write, how
+// many times a request failing to connect or answered with a 5xx is sent again, waiting
+// `backoff` and then twice as long each time, and the proxies `http://` and `https://`
+// registries go through.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Client {
+    pub timeout: Duration,
+    pub retries: u32,
+    pub backoff: Duration,
+    pub proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    // hosts reached directly, as `registry.internal`, `.internal` for its subdomains or `*`
+    pub no_proxy: Vec<String>,
+    pub tls: Tls,
+}
+
+// The client certificate and key for mutual TLS, and the CA bundle the registry's
+// certificate is checked against instead of the system one, as files curl reads.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tls {
+    pub cert: Option<String>,
+    pub key: Option<String>,
+    pub ca: Option<String>,
+}
+
+impl Default for Client {
+    fn default() -> Client {
+        Client {
+            timeout: Duration::from_secs(30),
+            retries: 3,
+            backoff: Duration::from_millis(250),
+            proxy: None,
+            https_proxy: None,
+            no_proxy: Vec::new(),
+            tls: Tls::default(),
+        }
+    }
+}
+
+impl Client {
+    // `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`, or their lowercase spellings
+    pub fn proxy_from_env(mut self, env: impl Fn(&str) -> Option<String>) -> Client {
+        let variable = |name: &str| env(name).or_else(|| env(&name.to_lowercase()));
+        self.proxy = variable("HTTP_PROXY").filter(|proxy| !proxy.is_empty());
+        self.https_proxy = variable("HTTPS_PROXY").filter(|proxy| !proxy.is_empty());
+        self.no_proxy = variable("NO_PROXY")
+            .unwrap_or_default()
+            .split(',')
+            .map(|host| host.trim().to_owned())
+            .filter(|host| !host.is_empty())
+            .collect();
+        self
+    }
+
+    pub fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: Option<&str>,
+    ) -> Result<Response, String> {
+        let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+        if scheme != "http" && scheme != "https" {
+            return Err(unsupported("registry URL", url));
+        }
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let proxy = self.proxy_for(scheme, authority);
+        let tls = scheme == "https" || proxy.is_some_and(|proxy| proxy.starts_with("https://"));
+        if !tls && self.tls != Tls::default() {
+            return Err(format!(
+                "Request to {} failed: a client certificate or CA needs an https:// URL",
+                url
+            ));
+        }
+        if let Some(proxy) = proxy.filter(|proxy| !tls && proxy.contains("://")) {
+            if !proxy.starts_with("http://") {
+                return Err(unsupported("proxy", proxy));
+            }
+        }
+
+        let message = match tls {
+            true => self.curl_config(method, url, headers, body, proxy),
+            false => {
+                // through a proxy the request line carries the whole URL
+                let target = if proxy.is_some() { url } else { path };
+                let mut message = format!(
+                    "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nAccept: application/json\r\n",
+                    method, target, authority
+                );
+                for (name, value) in headers {
+                    message.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                if let Some(body) = body {
+                    message.push_str(&format!(
+                        "Content-Type: {}\r\nContent-Length: {}\r\n",
+                        CONTENT_TYPE,
+                        body.len()
+                    ));
+                }
+                message.push_str("\r\n");
+                message.push_str(body.unwrap_or_default());
+                message
+            }
+        };
+        let address = proxy.map_or(authority, |proxy| {
+            proxy.trim_start_matches("http://").trim_end_matches('/')
+        });
+
+        let mut attempt = 0;
+        loop {
+            let result = match tls {
+                true => self.curl(&message, url),
+                false => self.send(address, &message, url),
+            };
+            let retry = match &result {
+                Ok(response) => response.status >= 500,
+                Err(_) => true,
+            };
+            if !retry || attempt >= self.retries {
+                return result;
+            }
+            thread::sleep(self.backoff * 2u32.saturating_pow(attempt));
+            attempt += 1;
+        }
+    }
+
+    // the proxy for the URL's scheme, unless `NO_PROXY` lists its host
+    fn proxy_for(&self, scheme: &str, authority: &str) -> Option<&str> {
+        let host = authority.split(':').next().unwrap_or_default();
+        let bypass = self.no_proxy.iter().any(|entry| {
+            entry == "*"
+                || entry == host
+                || host.ends_with(&format!(".{}", entry.trim_start_matches('.')))
+        });
+        let proxy = match scheme {
+            "https" => &self.https_proxy,
+            _ => &self.proxy,
+        };
+        proxy.as_deref().filter(|_| !bypass)
+    }
+
+    // A curl config file for the request, read from stdin so that credentials stay out of
+    // the command line. `NO_PROXY` has already been applied, so curl's own proxy variables
+    // are overridden either way.
+    fn curl_config(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: Option<&str>,
+        proxy: Option<&str>,
+    ) -> String {
+        let quote = |value: &str| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r");
+            format!("\"{}\"", value)
+        };
+        let seconds = self.timeout.as_secs_f64();
+        let mut lines = vec![
+            format!("url = {}", quote(url)),
+            format!("request = {}", quote(method)),
+            "globoff".to_owned(),
+            "silent".to_owned(),
+            "show-error".to_owned(),
+            format!("connect-timeout = {}", seconds),
+            format!("max-time = {}", seconds),
+            "write-out = \"\\n%{http_code}\"".to_owned(),
+            format!("header = {}", quote("Accept: application/json")),
+        ];
+        lines.extend(
+            headers.iter().map(|(name, value)| {
+                format!("header = {}", quote(&format!("{}: {}", name, value)))
+            }),
+        );
+        if let Some(body) = body {
+            lines.push(format!(
+                "header = {}",
+                quote(&format!("Content-Type: {}", CONTENT_TYPE))
+            ));
+            lines.push(format!("data-binary = {}", quote(body)));
+        }
+        lines.push(match proxy {
+            Some(proxy) => format!("proxy = {}", quote(proxy)),
+            None => "noproxy = \"*\"".to_owned(),
+        });
+        let files = [
+            ("cert", &self.tls.cert),
+            ("key", &self.tls.key),
+            ("cacert", &self.tls.ca),
+        ];
+        for (option, file) in files {
+            if let Some(file) = file {
+                lines.push(format!("{} = {}", option, quote(file)));
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+
+    // The body comes back followed by a line holding the status, from `write-out`.
+    fn curl(&self, config: &str, url: &str) -> Result<Response, String> {
+        let failed =
+            |err: std::io::Error| format!("Request to {} failed: running curl: {}", url, err);
+        let mut child = Command::new("curl")
+            .args(["--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(failed)?;
+        let mut stdin = child.stdin.take().expect("piped stdin");
+        let config = config.to_owned();
+        let writer = thread::spawn(move || stdin.write_all(config.as_bytes()));
+        let output = child.wait_with_output().map_err(failed)?;
+        writer
+            .join()
+            .expect("writing the curl config")
+            .map_err(failed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "Request to {} failed: {}",
+                url,
+                stderr.trim().trim_start_matches("curl: ")
+            ));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .rsplit_once('\n')
+            .and_then(|(body, status)| {
+                Some(Response {
+                    status: status.trim().parse().ok()?,
+                    body: body.to_owned(),
+                })
+            })
+            .ok_or(format!("Malformed response from {}", url))
+    }
+
+    fn send(&self, authority: &str, message: &str, url: &str) -> Result<Response, String> {
+        let address = match authority.contains(':') {
+            true => authority.to_owned(),
+            false => format!("{}:80", authority),
+        };
+        let failed = |err: std::io::Error| format!("Request to {} failed: {}", url, err);
+        let addresses = address.to_socket_addrs().map_err(failed)?;
+        let mut stream = Err(format!(
+            "Request to {} failed: no address for {}",
+            url, address
+        ));
+        for address in addresses {
+            stream = TcpStream::connect_timeout(&address, self.timeout).map_err(failed);
+            if stream.is_ok() {
+                break;
+            }
+        }
+        let mut stream = stream?;
+        stream
+            .set_read_timeout(Some(self.timeout))
+            .map_err(failed)?;
+        stream
+            .set_write_timeout(Some(self.timeout))
+            .map_err(failed)?;
+        stream.write_all(message.as_bytes()).map_err(failed)?;
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).map_err(failed)?;
+        parse(&received).ok_or(format!("Malformed response from {}", url))
+    }
+}
+
+const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+// names the scheme that can't be used, as a mistyped one is easily missed
+fn unsupported(what: &str, url: &str) -> String {
+    match url.split_once("://") {
+        Some((scheme, _)) => format!(
+            "Unsupported {} {}: the {} scheme isn't supported, only http:// and https://",
+            what, url, scheme
+        ),
+        None => format!(
+            "Unsupported {} {}, only http:// and https:// are supported",
+            what, url
+        ),
+    }
+}
+
+fn parse(received: &[u8]) -> Option<Response> {
+    let text = String::from_utf8_lossy(received);
+    let (head, body) = text.split_once("\r\n\r\n")?;
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let chunked = lines.any(|line| {
+        let line = line.to_ascii_lowercase();
+        line.starts_with("transfer-encoding:") && line.contains("chunked")
+    });
+    let body = match chunked {
+        true => dechunk(body)?,
+        false => body.to_owned(),
+    };
+    Some(Response { status, body })
+}
+
+fn dechunk(mut body: &str) -> Option<String> {
+    let mut out = String::new();
+    loop {
+        let (size, rest) = body.split_once("\r\n")?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        if size == 0 {
+            return Some(out);
+        }
+        out.push_str(rest.get(..size)?);
+        body = rest.get(size..)?.strip_prefix("\r\n")?;
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::http::{parse, Client, Response, Tls};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    // Answers each connection with the next canned `(status, body)`, keeping the request
+    // lines and bodies it received.
+    pub fn serve(responses: Vec<(u16, &str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        let responses: Vec<(u16, String)> = responses
+            .into_iter()
+            .map(|(status, body)| (status, body.to_owned()))
+            .collect();
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 4096];
+                loop {
+                    let n = stream.read(&mut buffer).unwrap();
+                    request.extend_from_slice(&buffer[..n]);
+                    let text = String::from_utf8_lossy(&request).into_owned();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.strip_prefix("Content-Length: "))
+                            .map_or(0, |length| length.parse().unwrap());
+                        if body.len() >= length || n == 0 {
+                            log.lock().unwrap().push(text);
+                            break;
+                        }
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {} OK\r\nContent-Length: {}\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, received)
+    }
+
+    #[test]
+    fn test_request() {
+        let (url, received) = serve(vec![(200, "{\"id\":1}")]);
+
+        let response = Client::default()
+            .request(
+                "POST",
+                &format!("{}/subjects/a/versions", url),
+                &[("X-Test".to_owned(), "yes".to_owned())],
+                Some("{}"),
+            )
+            .unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, "{\"id\":1}");
+        let received = received.lock().unwrap();
+        assert!(received[0].starts_with("POST /subjects/a/versions HTTP/1.1\r\n"));
+        assert!(received[0].contains("X-Test: yes\r\n"));
+        assert!(received[0].ends_with("\r\n\r\n{}"));
+    }
+
+    #[test]
+    fn test_parse_chunked() {
+        let received = b"HTTP/1.1 404 Not Found\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n3\r\n:1}\r\n0\r\n\r\n";
+        assert_eq!(
+            parse(received),
+            Some(Response {
+                status: 404,
+                body: "{\"a\":1}".to_owned()
+            })
+        );
+        assert_eq!(
+            Client::default().request("GET", "ftp://registry", &[], None),
+            Err("Unsupported registry URL ftp://registry: the ftp scheme isn't supported, only http:// and https://".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_retries() {
+        let (url, received) = serve(vec![(503, "busy"), (502, "busy"), (200, "{}")]);
+        let client = Client {
+            backoff: Duration::from_millis(1),
+            ..Client::default()
+        };
+
+        let response = client.request("GET", &format!("{}/subjects", url), &[], None);
+
+        assert_eq!(response.unwrap().status, 200);
+        assert_eq!(received.lock().unwrap().len(), 3);
+
+        let (url, _) = serve(vec![(500, "down"), (500, "down")]);
+        let client = Client {
+            retries: 1,
+            ..client
+        };
+        let response = client.request("GET", &format!("{}/subjects", url), &[], None);
+        assert_eq!(response.unwrap().status, 500, "given up after one retry");
+    }
+
+    #[test]
+    fn test_timeout() {
+        // connecting succeeds into the backlog, but nothing ever answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = Client {
+            timeout: Duration::from_millis(50),
+            retries: 0,
+            ..Client::default()
+        };
+
+        let url = format!("http://{}/subjects", listener.local_addr().unwrap());
+        assert!(client.request("GET", &url, &[], None).is_err());
+    }
+
+    #[test]
+    fn test_proxy() {
+        let (proxy, received) = serve(vec![(200, "[]"), (200, "[]")]);
+        let env = |name: &str| match name {
+            "http_proxy" => Some(proxy.clone()),
+            "NO_PROXY" => Some("localhost, .internal".to_owned()),
+            _ => None,
+        };
+        let client = Client::default().proxy_from_env(env);
+
+        let response = client.request("GET", "http://registry.example:8081/subjects", &[], None);
+
+        assert_eq!(response.unwrap().body, "[]");
+        let received = received.lock().unwrap();
+        assert!(received[0].starts_with("GET http://registry.example:8081/subjects HTTP/1.1\r\n"));
+        assert!(received[0].contains("Host: registry.example:8081\r\n"));
+        assert_eq!(client.no_proxy, vec!["localhost", ".internal"]);
+        assert_eq!(client.proxy_for("http", "registry.internal:8081"), None);
+        let err = Client {
+            proxy: Some("socks5://proxy".to_owned()),
+            ..Client::default()
+        }
+        .request("GET", "http://registry", &[], None);
+        assert!(err
+            .unwrap_err()
+            .starts_with("Unsupported proxy socks5://proxy"));
+    }
+
+    #[test]
+    fn test_curl_config() {
+        let client = Client {
+            timeout: Duration::from_millis(2500),
+            tls: Tls {
+                cert: Some("client.pem".to_owned()),
+                key: Some("client.key".to_owned()),
+                ca: Some("ca.pem".to_owned()),
+            },
+            ..Client::default()
+        };
+        let headers = [("Authorization".to_owned(), "Bearer t0k".to_owned())];
+
+        let config = client.curl_config(
+            "POST",
+            "https://registry/subjects/a/versions",
+            &headers,
+            Some("{\"schema\":\"\\\"x\\\"\"}"),
+            None,
+        );
+
+        assert!(config
+            .starts_with("url = \"https://registry/subjects/a/versions\"\nrequest = \"POST\"\n"));
+        assert!(config.contains("connect-timeout = 2.5\nmax-time = 2.5\n"));
+        assert!(config.contains("header = \"Authorization: Bearer t0k\"\n"));
+        assert!(config.contains("data-binary = \"{\\\"schema\\\":\\\"\\\\\\\"x\\\\\\\"\\\"}\"\n"));
+        assert!(config.contains("noproxy = \"*\"\n"));
+        assert!(
+            config.ends_with("cert = \"client.pem\"\nkey = \"client.key\"\ncacert = \"ca.pem\"\n")
+        );
+        assert!(Client::default()
+            .curl_config(
+                "GET",
+                "https://registry",
+                &[],
+                None,
+                Some("http://proxy:3128")
+            )
+            .contains("proxy = \"http://proxy:3128\"\n"));
+        let err = client.request("GET", "http://registry", &[], None);
+        assert!(err
+            .unwrap_err()
+            .ends_with("a client certificate or CA needs an https:// URL"));
+    }
+
+    #[test]
+    fn test_curl() {
+        let (url, received) = serve(vec![(201, "{\"id\":1}\n")]);
+        let headers = [("X-Test".to_owned(), "yes".to_owned())];
+        let body = "{\"schema\":\"\\\"a\\\"\\n\"}";
+        let client = Client::default();
+
+        let config = client.curl_config(
+            "POST",
+            &format!("{}/subjects", url),
+            &headers,
+            Some(body),
+            None,
+        );
+        let response = client.curl(&config, &url).unwrap();
+
+        assert_eq!(response.status, 201);
+        assert_eq!(response.body, "{\"id\":1}\n");
+        let received = received.lock().unwrap();
+        assert!(received[0].starts_with("POST /subjects HTTP/1.1\r\n"));
+        assert!(received[0].contains("X-Test: yes\r\n"));
+        assert!(received[0].ends_with(&format!("\r\n\r\n{}", body)));
+    }
+
+    #[test]
+    fn test_https_proxy() {
+        let (proxy, received) = serve(vec![(403, "")]);
+        let client = Client {
+            retries: 0,
+            ..Client::default()
+        }
+        .proxy_from_env(|name| (name == "HTTPS_PROXY").then(|| proxy.clone()));
+
+        let err = client.request("GET", "https://registry.example:8081/subjects", &[], None);
+
+        assert!(err
+            .unwrap_err()
+            .starts_with("Request to https://registry.example:8081/subjects failed: "));
+        let received = received.lock().unwrap();
+        assert!(received[0].starts_with("CONNECT registry.example:8081 HTTP/1.1\r\n"));
+    }
+}
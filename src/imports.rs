@@ -0,0 +1,239 @@
+use crate::Grammar;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use tree_sitter::{Node, Parser};
+
+// Relative imports and re-exports of a file, such as `./address` in
+// `import { Address } from "./address"`. Package imports are left out.
+pub fn relative(code: &str, grammar: Grammar) -> Vec<String> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(grammar.language())
+        .expect("Error loading typescript grammar");
+    let parsed = parser.parse(code, None).unwrap();
+    let root = parsed.root_node();
+
+    let mut specifiers = Vec::new();
+    let mut cursor = root.walk();
+    for node in root.named_children(&mut cursor) {
+        if node.kind() != "import_statement" && node.kind() != "export_statement" {
+            continue;
+        }
+        let specifier = node
+            .child_by_field_name("source")
+            .and_then(|source| source.utf8_text(code.as_bytes()).ok())
+            .map(|source| source.trim_matches(['"', '\'', '`']));
+        if let Some(specifier) = specifier.filter(|x| x.starts_with("./") || x.starts_with("../")) {
+            specifiers.push(specifier.to_owned());
+        }
+    }
+    specifiers
+}
+
+// The `(local, imported)` names of the renamed imports of a file, value or type-only, such
+// as `("Nation", "Country")` for `import type { Country as Nation } from "./country"`.
+pub fn renamed(root: &Node, code: &str) -> Vec<(String, String)> {
+    let mut renamed = Vec::new();
+    let mut cursor = root.walk();
+    for node in root.named_children(&mut cursor) {
+        if node.kind() != "import_statement" {
+            continue;
+        }
+        let mut clauses = node.walk();
+        for clause in node.named_children(&mut clauses) {
+            let mut named = clause.walk();
+            for imports in clause.named_children(&mut named) {
+                if imports.kind() != "named_imports" {
+                    continue;
+                }
+                let mut specifiers = imports.walk();
+                for specifier in imports.named_children(&mut specifiers) {
+                    let name = |field: &str| {
+                        specifier
+                            .child_by_field_name(field)
+                            .and_then(|name| name.utf8_text(code.as_bytes()).ok())
+                    };
+                    if let (Some(imported), Some(local)) = (name("name"), name("alias")) {
+                        renamed.push((local.to_owned(), imported.to_owned()));
+                    }
+                }
+            }
+        }
+    }
+    renamed
+}
+
+// points references to a renamed import back at the name it is declared under
+pub fn unalias(ptype: &mut Value, renamed: &[(String, String)]) {
+    match ptype {
+        Value::String(name) => {
+            if let Some((_, imported)) = renamed.iter().find(|(local, _)| local == name) {
+                *name = imported.clone();
+            }
+        }
+        Value::Array(members) => members
+            .iter_mut()
+            .for_each(|member| unalias(member, renamed)),
+        Value::Object(object) => {
+            if let Some(Value::Array(fields)) = object.get_mut("fields") {
+                for field in fields {
+                    unalias(&mut field["type"], renamed);
+                }
+            }
+            for key in ["items", "values"] {
+                if let Some(inner) = object.get_mut(key) {
+                    unalias(inner, renamed);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// the file an import refers to, trying the extensions the converter reads and `index` files
+pub fn resolve(from: &Path, specifier: &str) -> Option<PathBuf> {
+    let base = normalize(&from.parent().unwrap_or(Path::new("")).join(specifier));
+    if base.is_file() {
+        return Some(base);
+    }
+    let extensions = [
+        "ts", "tsx", "d.ts", "mts", "cts", "js", "jsx", "mjs", "flow",
+    ];
+    let stripped = base.with_extension("");
+    for candidate in [&base, &stripped] {
+        for extension in extensions {
+            let file = PathBuf::from(format!("{}.{}", candidate.display(), extension));
+            if file.is_file() {
+                return Some(file);
+            }
+        }
+    }
+    extensions
+        .iter()
+        .map(|extension| base.join(format!("index.{}", extension)))
+        .find(|index| index.is_file())
+}
+
+// drops the `.` and `..` an import adds, so each file keeps a single spelling
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+// the files together with everything they import, transitively, each listed once
+pub fn follow(files: Vec<PathBuf>, jsx: bool) -> Vec<PathBuf> {
+    let mut seen: BTreeSet<PathBuf> = BTreeSet::new();
+    let mut ordered = Vec::new();
+    let mut pending = files;
+    pending.reverse();
+    while let Some(file) = pending.pop() {
+        let key = file.canonicalize().unwrap_or(file.clone());
+        if !seen.insert(key) {
+            continue;
+        }
+        let path = file.to_string_lossy().into_owned();
+        if let Ok(code) = std::fs::read_to_string(&file) {
+            let grammar = Grammar::from_path(&path, jsx);
+            let mut imported: Vec<PathBuf> = relative(&code, grammar)
+                .iter()
+                .filter_map(|specifier| resolve(&file, specifier))
+                .collect();
+            imported.reverse();
+            pending.extend(imported);
+        }
+        ordered.push(file);
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::imports::{follow, normalize, relative, renamed};
+    use crate::Grammar;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_relative() {
+        let code = r#"
+        import { Address, Country as Nation } from "./address";
+        import type { Order } from '../orders/order';
+        import * as lodash from "lodash";
+        export { Role } from "./role";
+        export interface User {}
+        "#;
+
+        assert_eq!(
+            relative(code, Grammar::TypeScript),
+            vec!["./address", "../orders/order", "./role"]
+        );
+    }
+
+    #[test]
+    fn test_renamed() {
+        let code = r#"
+        import { Address, Country as Nation } from "./address";
+        import type { Order as PlacedOrder, Line } from '../orders/order';
+        import { type Refund as Return } from "./refund";
+        import * as geo from "./geo";
+        "#;
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(Grammar::TypeScript.language()).unwrap();
+        let parsed = parser.parse(code, None).unwrap();
+
+        assert_eq!(
+            renamed(&parsed.root_node(), code),
+            vec![
+                ("Nation".to_owned(), "Country".to_owned()),
+                ("PlacedOrder".to_owned(), "Order".to_owned()),
+                ("Return".to_owned(), "Refund".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize(Path::new("src/models/./../orders/order")),
+            Path::new("src/orders/order")
+        );
+        assert_eq!(
+            normalize(Path::new("../shared/id")),
+            Path::new("../shared/id")
+        );
+    }
+
+    #[test]
+    fn test_follow_circular_imports() {
+        let dir = std::env::temp_dir().join(format!("ts-to-avro-imports-{}", std::process::id()));
+        fs::create_dir_all(dir.join("billing")).unwrap();
+        fs::write(
+            dir.join("order.ts"),
+            "import { Invoice } from './billing/invoice';\ninterface Order { invoice: Invoice }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("billing/invoice.ts"),
+            "import { Order } from '../order';\ninterface Invoice { order: Order }\n",
+        )
+        .unwrap();
+
+        let files = follow(vec![dir.join("order.ts")], false);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            files,
+            vec![dir.join("order.ts"), dir.join("billing/invoice.ts")]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::avro::{self, UnionStrategy};
+use serde_json::{json, Map, Value};
+use std::io::{BufRead, Write};
+
+// Asks about the choices a run would otherwise make silently, collecting the answers in
+// the shape of the config file so they can be saved for the next run.
+pub struct Session<'a> {
+    input: &'a mut dyn BufRead,
+    output: &'a mut dyn Write,
+    pub answers: Value,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(input: &'a mut dyn BufRead, output: &'a mut dyn Write) -> Session<'a> {
+        Session {
+            input,
+            output,
+            answers: Value::Object(Map::new()),
+        }
+    }
+
+    // an empty answer, or the end of the input, picks the default
+    fn ask(&mut self, question: &str, default: &str) -> String {
+        let _ = write!(self.output, "{} [{}]: ", question, default);
+        let _ = self.output.flush();
+        let mut line = String::new();
+        match self.input.read_line(&mut line) {
+            Ok(0) | Err(_) => default.to_owned(),
+            Ok(_) if line.trim().is_empty() => default.to_owned(),
+            Ok(_) => line.trim().to_owned(),
+        }
+    }
+
+    pub fn choose_root(&mut self, candidates: &[String]) -> String {
+        let _ = writeln!(self.output, "Several records could be the root:");
+        for (i, candidate) in candidates.iter().enumerate() {
+            let _ = writeln!(self.output, "  {}) {}", i + 1, candidate);
+        }
+        loop {
+            let answer = self.ask("Root record", &candidates[0]);
+            let chosen = match answer.parse::<usize>() {
+                Ok(i) if i >= 1 && i <= candidates.len() => Some(&candidates[i - 1]),
+                _ => candidates.iter().find(|candidate| **candidate == answer),
+            };
+            if let Some(chosen) = chosen {
+                self.answers["root"] = json!(chosen);
+                return chosen.to_owned();
+            }
+            let _ = writeln!(self.output, "{} is not one of the records", answer);
+        }
+    }
+
+    // the answer is an Avro type name or a JSON schema; `keep` leaves the reference as is
+    pub fn map_type(&mut self, name: &str) -> Option<Value> {
+        loop {
+            let answer = self.ask(
+                &format!("Avro type for the unknown type {} (a name or JSON)", name),
+                "keep",
+            );
+            let mapped = match answer.as_str() {
+                "keep" => return None,
+                _ if answer.starts_with(['{', '[', '"']) => match serde_json::from_str(&answer) {
+                    Ok(mapped) => mapped,
+                    Err(err) => {
+                        let _ = writeln!(self.output, "Invalid JSON: {}", err);
+                        continue;
+                    }
+                },
+                _ => json!(answer),
+            };
+            if !self.answers["mapping"]["types"].is_object() {
+                self.answers["mapping"]["types"] = json!({});
+            }
+            self.answers["mapping"]["types"][name] = mapped.clone();
+            return Some(mapped);
+        }
+    }
+
+    pub fn union_strategy(&mut self, fields: &[String]) -> UnionStrategy {
+        let _ = writeln!(
+            self.output,
+            "These fields are unions of several primitives: {}",
+            fields.join(", ")
+        );
+        loop {
+            let answer = self.ask(
+                "Keep them as unions, collapse them to string, or fail (union/string/strict)",
+                "union",
+            );
+            if let Some(strategy) = UnionStrategy::parse(&answer) {
+                self.answers["unions"] = json!({ "strategy": answer });
+                return strategy;
+            }
+        }
+    }
+
+    pub fn confirm(&mut self, question: &str) -> bool {
+        matches!(self.ask(question, "y").as_str(), "y" | "Y" | "yes")
+    }
+}
+
+// records no other declaration refers to, in declaration order
+pub fn root_candidates(schemas: &[Value]) -> Vec<String> {
+    let referenced: Vec<String> = schemas
+        .iter()
+        .flat_map(|schema| schema["fields"].as_array().into_iter().flatten())
+        .flat_map(|field| avro::references(&field["type"]))
+        .collect();
+    schemas
+        .iter()
+        .filter(|schema| schema["type"] == "record")
+        .filter_map(|schema| schema["name"].as_str())
+        .filter(|name| !referenced.iter().any(|x| x == name))
+        .map(|name| name.to_owned())
+        .collect()
+}
+
+// merges the answers into the loaded config, table by table
+pub fn merge(config: &mut Value, answers: &Value) {
+    match (config, answers) {
+        (Value::Object(config), Value::Object(answers)) => {
+            for (key, answer) in answers {
+                match config.get_mut(key) {
+                    Some(existing) if existing.is_object() && answer.is_object() => {
+                        merge(existing, answer)
+                    }
+                    _ => {
+                        config.insert(key.to_owned(), answer.clone());
+                    }
+                }
+            }
+        }
+        (config, answers) => *config = answers.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::avro::UnionStrategy;
+    use crate::interactive::{merge, root_candidates, Session};
+    use crate::{get_schema, Grammar};
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_session() {
+        let mut input = Cursor::new("7\n2\n{\"type\": \"string\"}\nkeep\nbogus\nstring\n");
+        let mut output = Vec::new();
+        let mut session = Session::new(&mut input, &mut output);
+        let candidates = vec!["Order".to_owned(), "Invoice".to_owned()];
+
+        assert_eq!(session.choose_root(&candidates), "Invoice");
+        assert_eq!(
+            session.map_type("ObjectId"),
+            Some(json!({ "type": "string" }))
+        );
+        assert_eq!(session.map_type("Dayjs"), None);
+        assert_eq!(
+            session.union_strategy(&["Order.value".to_owned()]),
+            UnionStrategy::String
+        );
+        assert!(session.confirm("Save"));
+
+        assert_eq!(
+            session.answers,
+            json!({
+                "root": "Invoice",
+                "mapping": { "types": { "ObjectId": { "type": "string" } } },
+                "unions": { "strategy": "string" }
+            })
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  2) Invoice\n"));
+        assert!(output.contains("7 is not one of the records\n"));
+    }
+
+    #[test]
+    fn test_root_candidates() {
+        let code = r#"
+        interface Order {
+            lines: Line[];
+        }
+
+        interface Line {
+            sku: string;
+        }
+
+        interface Invoice {
+            order: Order;
+        }
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        assert_eq!(root_candidates(&schemas), vec!["Invoice"]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut config = json!({ "mapping": { "ids_as_long": true }, "root": "Order" });
+
+        merge(
+            &mut config,
+            &json!({ "mapping": { "types": { "Id": "long" } }, "root": "Invoice" }),
+        );
+
+        assert_eq!(
+            config,
+            json!({
+                "mapping": { "ids_as_long": true, "types": { "Id": "long" } },
+                "root": "Invoice"
+            })
+        );
+    }
+}
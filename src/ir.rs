@@ -0,0 +1,247 @@
+use crate::symbols::Symbols;
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Date,
+    Array(Box<Type>),
+    Map(Box<Type>),
+    Record(Record),
+    Enum(Enum),
+    Union(Vec<Type>),
+    Unknown(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub name: String,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Enum {
+    pub name: String,
+    pub symbols: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+}
+
+impl Type {
+    // splits `T | null` into `(T, true)`; other unions keep their non-null members
+    pub fn nullable(&self) -> (Type, bool) {
+        match self {
+            Type::Union(members) => {
+                let rest: Vec<Type> = members
+                    .iter()
+                    .filter(|member| **member != Type::Null)
+                    .cloned()
+                    .collect();
+                let nullable = rest.len() != members.len();
+                match rest.len() {
+                    0 => (Type::Null, nullable),
+                    1 => (rest[0].clone(), nullable),
+                    _ => (Type::Union(rest), nullable),
+                }
+            }
+            Type::Null => (Type::Null, true),
+            _ => (self.clone(), false),
+        }
+    }
+}
+
+pub fn resolve(schemas: &[Value]) -> Option<Record> {
+    let root = schemas.first()?;
+    Some(resolve_record(
+        root,
+        &Symbols::new(schemas),
+        &mut Vec::new(),
+    ))
+}
+
+fn resolve_record(schema: &Value, symbols: &Symbols, visiting: &mut Vec<String>) -> Record {
+    let name = schema["name"].as_str().unwrap_or_default().to_owned();
+    visiting.push(name.clone());
+
+    let fields = schema["fields"]
+        .as_array()
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|field| Field {
+                    name: field["name"].as_str().unwrap_or_default().to_owned(),
+                    ty: resolve_type(&field["type"], symbols, visiting),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    visiting.pop();
+    Record { name, fields }
+}
+
+fn resolve_type(value: &Value, symbols: &Symbols, visiting: &mut Vec<String>) -> Type {
+    match value {
+        Value::Array(members) => Type::Union(
+            members
+                .iter()
+                .map(|member| resolve_type(member, symbols, visiting))
+                .collect(),
+        ),
+        Value::String(text) => resolve_text(text.trim(), symbols, visiting),
+        Value::Object(object) if object.contains_key("fields") => {
+            resolve_record(value, symbols, visiting).into()
+        }
+        Value::Object(object) => match object.get("logicalType").and_then(Value::as_str) {
+            Some("timestamp-millis") | Some("timestamp-micros") => Type::Date,
+            Some("decimal") => Type::Number,
+            _ => match object.get("type").and_then(Value::as_str) {
+                Some("enum") => enum_type(value),
+                Some("array") => {
+                    Type::Array(Box::new(resolve_type(&value["items"], symbols, visiting)))
+                }
+                Some("map") => {
+                    Type::Map(Box::new(resolve_type(&value["values"], symbols, visiting)))
+                }
+                _ => resolve_type(&value["type"], symbols, visiting),
+            },
+        },
+        _ => Type::Unknown(value.to_string()),
+    }
+}
+
+fn resolve_text(text: &str, symbols: &Symbols, visiting: &mut Vec<String>) -> Type {
+    if let Some(inner) = text.strip_suffix("[]") {
+        return Type::Array(Box::new(resolve_text(inner.trim(), symbols, visiting)));
+    }
+
+    if let Some((name, arguments)) = generic(text) {
+        let last = arguments.last().copied().unwrap_or_default();
+        return match name {
+            "Array" | "ReadonlyArray" | "Set" => {
+                Type::Array(Box::new(resolve_text(last, symbols, visiting)))
+            }
+            "Map" | "Record" => Type::Map(Box::new(resolve_text(last, symbols, visiting))),
+            _ => Type::Unknown(text.to_owned()),
+        };
+    }
+
+    match text {
+        "null" | "undefined" | "void" => Type::Null,
+        "boolean" => Type::Boolean,
+        "number" | "bigint" | "int" | "long" | "float" | "double" => Type::Number,
+        "string" => Type::String,
+        "Date" => Type::Date,
+        _ => {
+            if visiting.iter().any(|name| name == text) {
+                return Type::Unknown(text.to_owned());
+            }
+            match symbols.lookup(text, "").map(|(_, schema)| schema) {
+                Some(schema) if schema["type"] == "enum" => enum_type(schema),
+                Some(schema) => resolve_record(schema, symbols, visiting).into(),
+                None => Type::Unknown(text.to_owned()),
+            }
+        }
+    }
+}
+
+fn enum_type(schema: &Value) -> Type {
+    Type::Enum(Enum {
+        name: schema["name"].as_str().unwrap_or_default().to_owned(),
+        symbols: schema["symbols"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_owned)
+            .collect(),
+    })
+}
+
+// `Map<string, Foo>` -> ("Map", ["string", "Foo"]), splitting only on top-level commas
+fn generic(text: &str) -> Option<(&str, Vec<&str>)> {
+    let open = text.find('<')?;
+    let inner = text[open + 1..].strip_suffix('>')?;
+
+    let mut arguments = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '<' | '{' | '[' | '(' => depth += 1,
+            '>' | '}' | ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    arguments.push(inner[start..].trim());
+
+    Some((text[..open].trim(), arguments))
+}
+
+impl From<Record> for Type {
+    fn from(record: Record) -> Type {
+        Type::Record(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ir::{resolve, Enum, Type};
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_resolve() {
+        let code = r#"
+        interface Person {
+            age: number;
+            tags: string[];
+            scores: Map<string, number>;
+            location: Location | null;
+            friend: Person;
+            color: Color;
+        }
+
+        enum Color {
+            Red,
+        }
+
+        interface Location {
+            city: string;
+        }
+        "#;
+
+        let record = resolve(&get_schema(code, Grammar::TypeScript).unwrap()).unwrap();
+
+        assert_eq!(record.name, "Person");
+        assert_eq!(record.fields[0].ty, Type::Number);
+        assert_eq!(record.fields[1].ty, Type::Array(Box::new(Type::String)));
+        assert_eq!(record.fields[2].ty, Type::Map(Box::new(Type::Number)));
+
+        let (location, nullable) = record.fields[3].ty.nullable();
+        assert!(nullable);
+        match location {
+            Type::Record(location) => assert_eq!(location.fields[0].name, "city"),
+            other => panic!("expected a record, got {:?}", other),
+        }
+
+        assert_eq!(record.fields[4].ty, Type::Unknown("Person".to_owned()));
+        assert_eq!(
+            record.fields[5].ty,
+            Type::Enum(Enum {
+                name: "Color".to_owned(),
+                symbols: vec!["Red".to_owned()]
+            })
+        );
+    }
+}
@@ -0,0 +1,545 @@
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+// Java sources for resolved schemas laid out the way avro-tools generates them: a class per
+// record extending `SpecificRecordBase`, an enum per enum and a `SpecificFixed` per fixed,
+// each in the package of its namespace and holding its own `SCHEMA$`. Fields keep their Avro
+// names and the types avro-tools picks, `CharSequence` for strings unless `avro.java.string`
+// asks for `String`, boxed primitives in unions with null and `java.time` for the date and
+// time logical types.
+pub fn classes(schemas: &[Value]) -> Vec<(PathBuf, String)> {
+    let mut types = Types::default();
+    for schema in schemas {
+        types.collect(schema, "");
+    }
+    types
+        .order
+        .iter()
+        .map(|full| {
+            let definition = &types.definitions[full];
+            let (namespace, name) = match full.rsplit_once('.') {
+                Some((namespace, name)) => (namespace, name),
+                None => ("", full.as_str()),
+            };
+            let standalone = types.standalone(definition, namespace, &mut HashSet::new());
+            let source = match definition["type"].as_str().map(str::to_ascii_lowercase) {
+                Some(kind) if kind == "enum" => enumeration(definition, name),
+                Some(kind) if kind == "fixed" => fixed(definition, name),
+                _ => types.record(definition, name, namespace),
+            };
+            let mut path: PathBuf = namespace.split('.').filter(|x| !x.is_empty()).collect();
+            path.push(format!("{}.java", name));
+            let mut out = String::from("/**\n * Autogenerated by ts-to-avro, do not edit.\n */\n");
+            if !namespace.is_empty() {
+                out.push_str(&format!("package {};\n\n", namespace));
+            }
+            out.push_str(&source.replace("SCHEMA_JSON", &java_string(&standalone.to_string())));
+            (path, out)
+        })
+        .collect()
+}
+
+fn qualified(name: &str, namespace: &str) -> String {
+    match namespace.is_empty() || name.contains('.') {
+        true => name.to_owned(),
+        false => format!("{}.{}", namespace, name),
+    }
+}
+
+#[derive(Default)]
+struct Types {
+    // every named type by full name, with the namespace it inherits written out
+    definitions: HashMap<String, Value>,
+    order: Vec<String>,
+}
+
+impl Types {
+    fn collect(&mut self, ptype: &Value, namespace: &str) {
+        match ptype {
+            Value::Array(members) => members
+                .iter()
+                .for_each(|member| self.collect(member, namespace)),
+            Value::Object(object) => {
+                let mut namespace = namespace.to_owned();
+                if let Some(name) = object.get("name").and_then(Value::as_str) {
+                    if let Some(declared) = object.get("namespace").and_then(Value::as_str) {
+                        namespace = declared.to_owned();
+                    }
+                    let full = qualified(name, &namespace);
+                    if let Some((declared, _)) = full.rsplit_once('.') {
+                        namespace = declared.to_owned();
+                    }
+                    if !self.definitions.contains_key(&full) {
+                        let mut definition = ptype.clone();
+                        if !namespace.is_empty() && !name.contains('.') {
+                            definition["namespace"] = json!(namespace);
+                        }
+                        self.definitions.insert(full.clone(), definition);
+                        self.order.push(full);
+                    }
+                }
+                for field in object
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    self.collect(&field["type"], &namespace);
+                }
+                for key in ["items", "values"] {
+                    if let Some(inner) = object.get(key) {
+                        self.collect(inner, &namespace);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // the full name a reference made from `namespace` stands for
+    fn resolve(&self, name: &str, namespace: &str) -> Option<String> {
+        let full = qualified(name, namespace);
+        if self.definitions.contains_key(&full) {
+            return Some(full);
+        }
+        match self.definitions.contains_key(name) {
+            true => Some(name.to_owned()),
+            false => self
+                .order
+                .iter()
+                .find(|full| full.rsplit('.').next() == Some(name))
+                .cloned(),
+        }
+    }
+
+    // A type's schema on its own for its `SCHEMA$`: the types it refers to by name are written
+    // in full where first used.
+    fn standalone(&self, ptype: &Value, namespace: &str, seen: &mut HashSet<String>) -> Value {
+        match ptype {
+            Value::String(name) => match self.resolve(name, namespace) {
+                Some(full) if seen.insert(full.clone()) => {
+                    let definition = &self.definitions[&full];
+                    let inner = full.rsplit_once('.').map_or("", |(namespace, _)| namespace);
+                    self.standalone(definition, inner, seen)
+                }
+                Some(full) => json!(full),
+                None => ptype.clone(),
+            },
+            Value::Array(members) => members
+                .iter()
+                .map(|member| self.standalone(member, namespace, seen))
+                .collect(),
+            Value::Object(object) => {
+                let mut resolved = object.clone();
+                let mut namespace = namespace.to_owned();
+                if let Some(name) = object.get("name").and_then(Value::as_str) {
+                    if let Some(declared) = object.get("namespace").and_then(Value::as_str) {
+                        namespace = declared.to_owned();
+                    }
+                    let full = qualified(name, &namespace);
+                    if let Some((declared, _)) = full.rsplit_once('.') {
+                        namespace = declared.to_owned();
+                    }
+                    seen.insert(full);
+                }
+                if let Some(Value::Array(fields)) = resolved.get_mut("fields") {
+                    for field in fields {
+                        field["type"] = self.standalone(&field["type"], &namespace, seen);
+                    }
+                }
+                for key in ["items", "values"] {
+                    if let Some(inner) = resolved.get_mut(key) {
+                        *inner = self.standalone(inner, &namespace, seen);
+                    }
+                }
+                Value::Object(resolved)
+            }
+            _ => ptype.clone(),
+        }
+    }
+
+    fn java_type(&self, ptype: &Value, namespace: &str, boxed: bool) -> String {
+        let primitive = |unboxed: &str, boxed_type: &str| match boxed {
+            true => boxed_type.to_owned(),
+            false => unboxed.to_owned(),
+        };
+        match ptype {
+            Value::String(name) => match name.as_str() {
+                "null" => "java.lang.Void".to_owned(),
+                "boolean" => primitive("boolean", "java.lang.Boolean"),
+                "int" => primitive("int", "java.lang.Integer"),
+                "long" => primitive("long", "java.lang.Long"),
+                "float" => primitive("float", "java.lang.Float"),
+                "double" => primitive("double", "java.lang.Double"),
+                "bytes" => "java.nio.ByteBuffer".to_owned(),
+                "string" => "java.lang.CharSequence".to_owned(),
+                name => self
+                    .resolve(name, namespace)
+                    .unwrap_or_else(|| name.to_owned()),
+            },
+            Value::Array(members) => {
+                let others: Vec<&Value> = members.iter().filter(|x| *x != "null").collect();
+                match others.as_slice() {
+                    [only] => {
+                        self.java_type(only, namespace, boxed || others.len() < members.len())
+                    }
+                    // as avro-tools does, any other union is an Object
+                    _ => "java.lang.Object".to_owned(),
+                }
+            }
+            Value::Object(object) => {
+                if let Some(name) = object.get("name").and_then(Value::as_str) {
+                    let namespace = object
+                        .get("namespace")
+                        .and_then(Value::as_str)
+                        .unwrap_or(namespace);
+                    return qualified(name, namespace);
+                }
+                match (ptype["type"].as_str(), ptype["logicalType"].as_str()) {
+                    (_, Some("date")) => "java.time.LocalDate".to_owned(),
+                    (_, Some("time-millis" | "time-micros")) => "java.time.LocalTime".to_owned(),
+                    (_, Some("timestamp-millis" | "timestamp-micros")) => {
+                        "java.time.Instant".to_owned()
+                    }
+                    (_, Some("local-timestamp-millis" | "local-timestamp-micros")) => {
+                        "java.time.LocalDateTime".to_owned()
+                    }
+                    (Some("string"), _) if ptype["avro.java.string"] == "String" => {
+                        "java.lang.String".to_owned()
+                    }
+                    (Some("array"), _) => format!(
+                        "java.util.List<{}>",
+                        self.java_type(&ptype["items"], namespace, true)
+                    ),
+                    (Some("map"), _) => format!(
+                        "java.util.Map<java.lang.CharSequence,{}>",
+                        self.java_type(&ptype["values"], namespace, true)
+                    ),
+                    (Some(_), _) => self.java_type(&ptype["type"], namespace, boxed),
+                    (None, _) => "java.lang.Object".to_owned(),
+                }
+            }
+            _ => "java.lang.Object".to_owned(),
+        }
+    }
+
+    fn record(&self, schema: &Value, name: &str, namespace: &str) -> String {
+        let fields: Vec<(String, String, String, &Value)> = schema["fields"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|field| {
+                let field_name = mangle(field["name"].as_str().unwrap_or_default());
+                let java = self.java_type(&field["type"], namespace, false);
+                let boxed = self.java_type(&field["type"], namespace, true);
+                (field_name, java, boxed, field)
+            })
+            .collect();
+
+        let mut out = javadoc(&schema["doc"], "");
+        out.push_str("@org.apache.avro.specific.AvroGenerated\n");
+        out.push_str(&format!(
+            "public class {} extends org.apache.avro.specific.SpecificRecordBase implements org.apache.avro.specific.SpecificRecord {{\n",
+            name
+        ));
+        out.push_str("  public static final org.apache.avro.Schema SCHEMA$ = new org.apache.avro.Schema.Parser().parse(SCHEMA_JSON);\n");
+        out.push_str(
+            "  public static org.apache.avro.Schema getClassSchema() { return SCHEMA$; }\n\n",
+        );
+        for (field_name, java, _, field) in &fields {
+            out.push_str(&javadoc(&field["doc"], "  "));
+            out.push_str(&format!("  private {} {};\n", java, field_name));
+        }
+
+        out.push_str(&format!("\n  public {}() {{}}\n", name));
+        if !fields.is_empty() {
+            let parameters: Vec<String> = fields
+                .iter()
+                .map(|(field_name, _, boxed, _)| format!("{} {}", boxed, field_name))
+                .collect();
+            out.push_str(&format!(
+                "\n  public {}({}) {{\n",
+                name,
+                parameters.join(", ")
+            ));
+            for (field_name, _, _, _) in &fields {
+                out.push_str(&format!("    this.{0} = {0};\n", field_name));
+            }
+            out.push_str("  }\n");
+        }
+
+        out.push_str(
+            "\n  @Override\n  public org.apache.avro.Schema getSchema() { return SCHEMA$; }\n",
+        );
+        // the logical types are read and written through the conversions of avro-tools
+        let conversions: Vec<Option<&str>> = fields
+            .iter()
+            .map(|(_, _, _, field)| conversion(&field["type"]))
+            .collect();
+        if conversions.iter().any(Option::is_some) {
+            let conversions: Vec<String> = conversions
+                .iter()
+                .map(|conversion| match conversion {
+                    Some(conversion) => {
+                        format!("new org.apache.avro.data.TimeConversions.{}()", conversion)
+                    }
+                    None => "null".to_owned(),
+                })
+                .collect();
+            out.push_str(&format!(
+                "\n  private static final org.apache.avro.Conversion<?>[] conversions =\n      new org.apache.avro.Conversion<?>[] {{ {} }};\n",
+                conversions.join(", ")
+            ));
+            out.push_str("\n  @Override\n  public org.apache.avro.Conversion<?> getConversion(int field) {\n    return conversions[field];\n  }\n");
+        }
+        out.push_str(
+            "\n  @Override\n  public java.lang.Object get(int field$) {\n    switch (field$) {\n",
+        );
+        for (i, (field_name, _, _, _)) in fields.iter().enumerate() {
+            out.push_str(&format!("    case {}: return {};\n", i, field_name));
+        }
+        out.push_str("    default: throw new IndexOutOfBoundsException(\"Invalid index: \" + field$);\n    }\n  }\n");
+        out.push_str("\n  @Override\n  @SuppressWarnings(value=\"unchecked\")\n  public void put(int field$, java.lang.Object value$) {\n    switch (field$) {\n");
+        for (i, (field_name, _, boxed, _)) in fields.iter().enumerate() {
+            out.push_str(&format!(
+                "    case {}: {} = ({})value$; break;\n",
+                i, field_name, boxed
+            ));
+        }
+        out.push_str("    default: throw new IndexOutOfBoundsException(\"Invalid index: \" + field$);\n    }\n  }\n");
+
+        for (field_name, java, _, _) in &fields {
+            // accessors that would clash with those of `SpecificRecordBase` get a `$` too
+            let mut accessor = capitalize(field_name);
+            if ["Class", "Schema"].contains(&accessor.as_str()) {
+                accessor.push('$');
+            }
+            out.push_str(&format!(
+                "\n  public {0} get{1}() {{\n    return {2};\n  }}\n\n  public void set{1}({0} value) {{\n    this.{2} = value;\n  }}\n",
+                java, accessor, field_name
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+const KEYWORDS: [&str; 53] = [
+    "abstract",
+    "assert",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "implements",
+    "import",
+    "instanceof",
+    "int",
+    "interface",
+    "long",
+    "native",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "static",
+    "strictfp",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "transient",
+    "true",
+    "try",
+    "void",
+    "volatile",
+    "while",
+];
+
+// reserved words get a `$` appended, as avro-tools does
+fn mangle(name: &str) -> String {
+    match KEYWORDS.contains(&name) {
+        true => format!("{}$", name),
+        false => name.to_owned(),
+    }
+}
+
+fn capitalize(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map_or(String::new(), |first| {
+        first.to_ascii_uppercase().to_string() + chars.as_str()
+    })
+}
+
+fn javadoc(doc: &Value, indent: &str) -> String {
+    match doc.as_str() {
+        Some(doc) => format!("{}/** {} */\n", indent, doc.replace("*/", "*&#47;")),
+        None => String::new(),
+    }
+}
+
+fn enumeration(schema: &Value, name: &str) -> String {
+    let symbols: Vec<&str> = schema["symbols"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let mut out = javadoc(&schema["doc"], "");
+    out.push_str("@org.apache.avro.specific.AvroGenerated\n");
+    out.push_str(&format!(
+        "public enum {0} implements org.apache.avro.generic.GenericEnumSymbol<{0}> {{\n  {1};\n",
+        name,
+        symbols.join(", ")
+    ));
+    out.push_str("  public static final org.apache.avro.Schema SCHEMA$ = new org.apache.avro.Schema.Parser().parse(SCHEMA_JSON);\n");
+    out.push_str("  public static org.apache.avro.Schema getClassSchema() { return SCHEMA$; }\n");
+    out.push_str(
+        "\n  @Override\n  public org.apache.avro.Schema getSchema() { return SCHEMA$; }\n}\n",
+    );
+    out
+}
+
+fn fixed(schema: &Value, name: &str) -> String {
+    let mut out = javadoc(&schema["doc"], "");
+    out.push_str(&format!(
+        "@org.apache.avro.specific.FixedSize({})\n@org.apache.avro.specific.AvroGenerated\n",
+        schema["size"].as_u64().unwrap_or_default()
+    ));
+    out.push_str(&format!(
+        "public class {} extends org.apache.avro.specific.SpecificFixed {{\n",
+        name
+    ));
+    out.push_str("  public static final org.apache.avro.Schema SCHEMA$ = new org.apache.avro.Schema.Parser().parse(SCHEMA_JSON);\n");
+    out.push_str("  public static org.apache.avro.Schema getClassSchema() { return SCHEMA$; }\n");
+    out.push_str(
+        "\n  @Override\n  public org.apache.avro.Schema getSchema() { return SCHEMA$; }\n}\n",
+    );
+    out
+}
+
+fn conversion(ptype: &Value) -> Option<&'static str> {
+    let ptype = match ptype {
+        Value::Array(members) => {
+            match members.iter().filter(|x| *x != "null").collect::<Vec<_>>()[..] {
+                [only] => only,
+                _ => return None,
+            }
+        }
+        _ => ptype,
+    };
+    match ptype["logicalType"].as_str()? {
+        "date" => Some("DateConversion"),
+        "time-millis" => Some("TimeMillisConversion"),
+        "time-micros" => Some("TimeMicrosConversion"),
+        "timestamp-millis" => Some("TimestampMillisConversion"),
+        "timestamp-micros" => Some("TimestampMicrosConversion"),
+        "local-timestamp-millis" => Some("LocalTimestampMillisConversion"),
+        "local-timestamp-micros" => Some("LocalTimestampMicrosConversion"),
+        _ => None,
+    }
+}
+
+// the schema as a Java string literal
+fn java_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::java::classes;
+    use serde_json::json;
+    use std::path::Path;
+
+    #[test]
+    fn test_classes() {
+        let schema = json!({
+            "type": "record",
+            "name": "Order",
+            "namespace": "com.shop",
+            "fields": [
+                { "name": "id", "type": { "type": "string", "avro.java.string": "String" } },
+                { "name": "total", "type": "double" },
+                { "name": "discount", "type": ["null", "double"] },
+                { "name": "placedAt", "type": { "type": "long", "logicalType": "timestamp-millis" } },
+                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["Open", "Closed"] } },
+                { "name": "lines", "type": { "type": "array", "items": {
+                    "type": "record", "name": "Line", "fields": [
+                        { "name": "sku", "type": "string" },
+                        { "name": "status", "type": "Status" }
+                    ]
+                } } },
+                { "name": "class", "type": "string" }
+            ]
+        });
+
+        let classes = classes(&[schema]);
+
+        let paths: Vec<&Path> = classes.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("com/shop/Order.java"),
+                Path::new("com/shop/Status.java"),
+                Path::new("com/shop/Line.java"),
+            ]
+        );
+        let order = &classes[0].1;
+        assert!(order.starts_with(
+            "/**\n * Autogenerated by ts-to-avro, do not edit.\n */\npackage com.shop;\n\n"
+        ));
+        assert!(order
+            .contains("public class Order extends org.apache.avro.specific.SpecificRecordBase"));
+        assert!(order.contains("parse(\"{\\\"fields\\\":[{\\\"name\\\":\\\"id\\\""));
+        assert!(order.contains("\\\"type\\\":\\\"record\\\"}\");\n"));
+        for declaration in [
+            "  private java.lang.String id;\n",
+            "  private double total;\n",
+            "  private java.lang.Double discount;\n",
+            "  private java.time.Instant placedAt;\n",
+            "{ null, null, null, new org.apache.avro.data.TimeConversions.TimestampMillisConversion(), null, null, null };\n",
+            "  private com.shop.Status status;\n",
+            "  private java.util.List<com.shop.Line> lines;\n",
+            "  private java.lang.CharSequence class$;\n",
+            "    case 1: total = (java.lang.Double)value$; break;\n",
+            "  public java.lang.CharSequence getClass$() {\n",
+        ] {
+            assert!(order.contains(declaration), "{} in {}", declaration, order);
+        }
+
+        // a type used before by name is written in full in the SCHEMA$ of its own class
+        let line = &classes[2].1;
+        assert!(
+            line.contains("\\\"name\\\":\\\"status\\\",\\\"type\\\":{\\\"name\\\":\\\"Status\\\"")
+        );
+        assert!(line.contains("  private com.shop.Status status;\n"));
+        assert!(classes[1].1.contains(
+            "public enum Status implements org.apache.avro.generic.GenericEnumSymbol<Status> {\n  Open, Closed;\n"
+        ));
+    }
+}
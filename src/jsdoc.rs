@@ -0,0 +1,224 @@
+use crate::avro;
+use serde_json::{json, Value};
+
+pub fn parse_typedefs(comment: &str) -> Vec<Value> {
+    let mut records = Vec::new();
+    let mut current: Option<(String, Vec<Value>)> = None;
+
+    for tag in tags(comment) {
+        if let Some(rest) = tag.strip_prefix("@typedef") {
+            if let Some((name, fields)) = current.take() {
+                records.push(record(name, fields));
+            }
+            if let Some((ptype, rest)) = braced(rest) {
+                let name = rest.split_whitespace().next().unwrap_or_default();
+                if is_object(&ptype) && !name.is_empty() {
+                    current = Some((name.to_owned(), Vec::new()));
+                }
+            }
+        } else if let Some(rest) = tag
+            .strip_prefix("@property")
+            .or_else(|| tag.strip_prefix("@prop"))
+        {
+            let fields = match current.as_mut() {
+                Some((_, fields)) => fields,
+                None => continue,
+            };
+            if let Some((ptype, rest)) = braced(rest) {
+                if let Some(field) = get_property(&ptype, rest) {
+                    fields.push(field);
+                }
+            }
+        }
+    }
+
+    if let Some((name, fields)) = current {
+        records.push(record(name, fields));
+    }
+
+    records
+}
+
+// `@tag value` pairs of a doc comment, e.g. `("avro.key", "")`
+pub fn annotations(comment: &str) -> Vec<(String, String)> {
+    tags(comment)
+        .iter()
+        .map(|tag| {
+            let tag = tag.trim_start_matches('@');
+            match tag.split_once(char::is_whitespace) {
+                Some((name, value)) => (name.to_owned(), value.trim().to_owned()),
+                None => (tag.to_owned(), String::new()),
+            }
+        })
+        .collect()
+}
+
+// the free text of a comment, before its first `@tag`
+pub fn description(comment: &str) -> String {
+    let body = comment
+        .trim()
+        .trim_start_matches("/**")
+        .trim_start_matches("//")
+        .trim_end_matches("*/");
+
+    body.lines()
+        .map(|line| line.trim().trim_start_matches('*').trim())
+        .take_while(|line| !line.starts_with('@'))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn record(name: String, fields: Vec<Value>) -> Value {
+    json!({
+        "type": "record",
+        "name": name,
+        "fields": fields
+    })
+}
+
+fn get_property(ptype: &str, rest: &str) -> Option<Value> {
+    let word = rest.split_whitespace().next()?;
+    let (name, optional) = match word.strip_prefix('[') {
+        Some(inner) => {
+            let inner = inner.trim_end_matches(']');
+            (inner.split('=').next().unwrap_or_default(), true)
+        }
+        None => (word, false),
+    };
+
+    // dotted names describe members of a nested object, which has no record of its own here
+    if name.is_empty() || name.contains('.') {
+        return None;
+    }
+
+    let mut members: Vec<Value> = match ptype.strip_prefix('?') {
+        Some(inner) => vec![avro::named_type(inner.trim()), json!("null")],
+        None => ptype
+            .split('|')
+            .map(|member| avro::named_type(member.trim()))
+            .collect(),
+    };
+    if optional {
+        members.push(json!("null"));
+    }
+
+    let ptype = avro::union(members);
+
+    Some(crate::field(name, ptype))
+}
+
+fn is_object(ptype: &str) -> bool {
+    ptype == "Object" || ptype == "object"
+}
+
+// joins every `@tag` with its continuation lines, dropping the comment decoration
+fn tags(comment: &str) -> Vec<String> {
+    let body = comment
+        .trim()
+        .trim_start_matches("/**")
+        .trim_end_matches("*/");
+
+    let mut tags: Vec<String> = Vec::new();
+    for line in body.lines() {
+        let line = line.trim().trim_start_matches('*').trim();
+        if line.starts_with('@') {
+            tags.push(line.to_owned());
+        } else if let Some(last) = tags.last_mut() {
+            if !line.is_empty() {
+                last.push(' ');
+                last.push_str(line);
+            }
+        }
+    }
+    tags
+}
+
+// returns the contents of a leading `{...}` group and the text after it
+fn braced(text: &str) -> Option<(String, &str)> {
+    let text = text.trim_start();
+    if !text.starts_with('{') {
+        return None;
+    }
+
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((text[1..i].trim().to_owned(), &text[i + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jsdoc::{annotations, description, parse_typedefs};
+
+    #[test]
+    fn test_typedef() {
+        let comment = r#"/**
+         * A person.
+         * @typedef {Object} Person
+         * @property {number} age - how old
+         * @property {string|null} location
+         * @property {?string} nickname
+         * @property {string} [email]
+         * @property {Object} address
+         * @property {string} address.city
+         */"#;
+
+        let schemas = parse_typedefs(comment);
+
+        assert_eq!(schemas.len(), 1);
+        assert_eq!(schemas[0]["type"], "record");
+        assert_eq!(schemas[0]["name"], "Person");
+        assert_eq!(schemas[0]["fields"][0]["name"], "age");
+        assert_eq!(schemas[0]["fields"][0]["type"], "double");
+        assert_eq!(schemas[0]["fields"][1]["type"][0], "null");
+        assert_eq!(schemas[0]["fields"][1]["type"][1], "string");
+        assert_eq!(schemas[0]["fields"][1]["default"], serde_json::Value::Null);
+        assert_eq!(schemas[0]["fields"][2]["name"], "nickname");
+        assert_eq!(schemas[0]["fields"][2]["type"][0], "null");
+        assert_eq!(schemas[0]["fields"][3]["name"], "email");
+        assert_eq!(schemas[0]["fields"][3]["type"][0], "null");
+        assert_eq!(schemas[0]["fields"][4]["name"], "address");
+        assert_eq!(schemas[0]["fields"].as_array().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_non_object_typedef() {
+        let comment = "/** @typedef {string} Id */";
+
+        assert!(parse_typedefs(comment).is_empty());
+    }
+
+    #[test]
+    fn test_annotations() {
+        let comment = "/**\n * Customer id.\n * @avro.key\n * @avro.namespace com.acme\n */";
+
+        assert_eq!(
+            annotations(comment),
+            vec![
+                ("avro.key".to_owned(), String::new()),
+                ("avro.namespace".to_owned(), "com.acme".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_description() {
+        assert_eq!(
+            description("/**\n * Customer id,\n * unique.\n * @avro.key\n */"),
+            "Customer id, unique."
+        );
+        assert_eq!(description("// plain green"), "plain green");
+        assert_eq!(description("/** @avro.enumDefault */"), "");
+    }
+}
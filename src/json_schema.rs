@@ -0,0 +1,205 @@
+use serde_json::{json, Map, Value};
+
+// A JSON Schema accepting the plain JSON documents of a resolved Avro schema, for validating
+// payloads before they are produced: named types go under `$defs` and are referred to from
+// where they're used, the root as `#`, a field is required when it has no default and a
+// union accepts any of its branches unwrapped. Logical types keep the JSON type of the type
+// underneath, so that a timestamp is still the number Avro carries.
+pub fn json_schema(schema: &Value) -> Value {
+    let mut converter = Converter {
+        root: full_name(schema, ""),
+        defs: Map::new(),
+    };
+    let namespace = schema["namespace"].as_str().unwrap_or_default();
+    let mut out = converter.declaration(schema, namespace);
+    out["$schema"] = json!("https://json-schema.org/draft/2020-12/schema");
+    out["title"] = schema["name"].clone();
+    if !converter.defs.is_empty() {
+        out["$defs"] = Value::Object(converter.defs);
+    }
+    out
+}
+
+struct Converter {
+    root: String,
+    defs: Map<String, Value>,
+}
+
+impl Converter {
+    fn convert(&mut self, ptype: &Value, namespace: &str) -> Value {
+        match ptype {
+            Value::String(name) => match name.as_str() {
+                "null" => json!({ "type": "null" }),
+                "boolean" => json!({ "type": "boolean" }),
+                "int" => json!({ "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX }),
+                "long" => json!({ "type": "integer" }),
+                "float" | "double" => json!({ "type": "number" }),
+                "bytes" | "string" => json!({ "type": "string" }),
+                name => self.reference(&qualified(name, namespace)),
+            },
+            Value::Array(members) => {
+                let members: Vec<Value> = members
+                    .iter()
+                    .map(|member| self.convert(member, namespace))
+                    .collect();
+                json!({ "anyOf": members })
+            }
+            Value::Object(object) if object.contains_key("name") => {
+                let full = full_name(ptype, namespace);
+                if full != self.root && !self.defs.contains_key(&full) {
+                    // taken before converting, so that a type containing itself ends
+                    self.defs.insert(full.clone(), Value::Null);
+                    let inner = full.rsplit_once('.').map_or("", |(namespace, _)| namespace);
+                    let converted = self.declaration(ptype, inner);
+                    self.defs.insert(full.clone(), converted);
+                }
+                self.reference(&full)
+            }
+            Value::Object(_) => match ptype["type"].as_str() {
+                Some("array") => json!({
+                    "type": "array",
+                    "items": self.convert(&ptype["items"], namespace)
+                }),
+                Some("map") => json!({
+                    "type": "object",
+                    "additionalProperties": self.convert(&ptype["values"], namespace)
+                }),
+                _ => {
+                    let mut converted = self.convert(&ptype["type"], namespace);
+                    if ptype["logicalType"] == "uuid" {
+                        converted["format"] = json!("uuid");
+                    }
+                    converted
+                }
+            },
+            _ => json!({}),
+        }
+    }
+
+    fn declaration(&mut self, schema: &Value, namespace: &str) -> Value {
+        let kind = schema["type"].as_str().unwrap_or_default();
+        let mut converted = match kind.to_ascii_lowercase().as_str() {
+            "enum" => json!({ "enum": schema["symbols"] }),
+            "fixed" => {
+                let size = schema["size"].as_u64().unwrap_or_default();
+                json!({ "type": "string", "minLength": size, "maxLength": size })
+            }
+            _ => {
+                let mut properties = Map::new();
+                let mut required = Vec::new();
+                for field in schema["fields"].as_array().into_iter().flatten() {
+                    let name = field["name"].as_str().unwrap_or_default();
+                    let mut property = self.convert(&field["type"], namespace);
+                    if let Some(doc) = field.get("doc") {
+                        property["description"] = doc.clone();
+                    }
+                    match field.get("default") {
+                        Some(default) => property["default"] = default.clone(),
+                        None => required.push(json!(name)),
+                    }
+                    properties.insert(name.to_owned(), property);
+                }
+                json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false
+                })
+            }
+        };
+        if let Some(doc) = schema.get("doc") {
+            converted["description"] = doc.clone();
+        }
+        converted
+    }
+
+    fn reference(&self, full: &str) -> Value {
+        match full == self.root {
+            true => json!({ "$ref": "#" }),
+            false => {
+                // a reference by short name, as the tool writes them within a namespace
+                let def = match self.defs.contains_key(full) {
+                    true => full.to_owned(),
+                    false => self
+                        .defs
+                        .keys()
+                        .find(|def| def.rsplit('.').next() == full.rsplit('.').next())
+                        .cloned()
+                        .unwrap_or_else(|| full.to_owned()),
+                };
+                json!({ "$ref": format!("#/$defs/{}", def) })
+            }
+        }
+    }
+}
+
+fn qualified(name: &str, namespace: &str) -> String {
+    match namespace.is_empty() || name.contains('.') {
+        true => name.to_owned(),
+        false => format!("{}.{}", namespace, name),
+    }
+}
+
+fn full_name(schema: &Value, namespace: &str) -> String {
+    let namespace = schema["namespace"].as_str().unwrap_or(namespace);
+    qualified(schema["name"].as_str().unwrap_or_default(), namespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_schema::json_schema;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_schema() {
+        let schema = json!({
+            "type": "record",
+            "name": "Order",
+            "namespace": "com.shop",
+            "doc": "A placed order",
+            "fields": [
+                { "name": "id", "type": { "type": "string", "logicalType": "uuid" } },
+                { "name": "quantity", "type": "int" },
+                { "name": "note", "type": ["null", "string"], "default": null, "doc": "Left by the customer" },
+                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["Open", "Closed"] } },
+                { "name": "lines", "type": { "type": "array", "items": {
+                    "type": "record", "name": "Line", "fields": [{ "name": "status", "type": "Status" }]
+                } } },
+                { "name": "parent", "type": ["null", "Order"], "default": null }
+            ]
+        });
+
+        assert_eq!(
+            json_schema(&schema),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "Order",
+                "description": "A placed order",
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "format": "uuid" },
+                    "quantity": { "type": "integer", "minimum": -2147483648, "maximum": 2147483647 },
+                    "note": {
+                        "anyOf": [{ "type": "null" }, { "type": "string" }],
+                        "default": null,
+                        "description": "Left by the customer"
+                    },
+                    "status": { "$ref": "#/$defs/com.shop.Status" },
+                    "lines": { "type": "array", "items": { "$ref": "#/$defs/com.shop.Line" } },
+                    "parent": { "anyOf": [{ "type": "null" }, { "$ref": "#" }], "default": null }
+                },
+                "required": ["id", "quantity", "status", "lines"],
+                "additionalProperties": false,
+                "$defs": {
+                    "com.shop.Status": { "enum": ["Open", "Closed"] },
+                    "com.shop.Line": {
+                        "type": "object",
+                        "properties": { "status": { "$ref": "#/$defs/com.shop.Status" } },
+                        "required": ["status"],
+                        "additionalProperties": false
+                    }
+                }
+            })
+        );
+    }
+}
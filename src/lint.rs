@@ -0,0 +1,231 @@
+use crate::diagnostic::Diagnostic;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+// The policy `lint` checks declarations against, from the `[lint]` table of the config:
+//
+//   [lint]
+//   record_namespace = true
+//   field_doc = true
+//   field_case = "snake_case"
+//   record_case = "PascalCase"
+//   warn = ["field_doc"]
+//
+// Rules listed in `warn` are reported as warnings, the others as errors.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Rules {
+    pub record_namespace: bool,
+    pub field_doc: bool,
+    pub field_case: Option<Case>,
+    pub record_case: Option<Case>,
+    pub warn: BTreeSet<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Case {
+    Snake,
+    Camel,
+    Pascal,
+}
+
+impl Case {
+    pub fn parse(name: &str) -> Option<Case> {
+        match name {
+            "snake_case" => Some(Case::Snake),
+            "camelCase" => Some(Case::Camel),
+            "PascalCase" => Some(Case::Pascal),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Case::Snake => "snake_case",
+            Case::Camel => "camelCase",
+            Case::Pascal => "PascalCase",
+        }
+    }
+
+    fn matches(self, name: &str) -> bool {
+        let mut chars = name.chars();
+        let first = chars.next().unwrap_or_default();
+        let rest = chars.as_str();
+        match self {
+            Case::Snake => {
+                first.is_ascii_lowercase()
+                    && name.split('_').all(|word| !word.is_empty())
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            }
+            Case::Camel | Case::Pascal => {
+                let initial = match self {
+                    Case::Camel => first.is_ascii_lowercase(),
+                    _ => first.is_ascii_uppercase(),
+                };
+                initial && rest.chars().all(|c| c.is_ascii_alphanumeric())
+            }
+        }
+    }
+}
+
+const RULES: [&str; 4] = ["record_namespace", "field_doc", "field_case", "record_case"];
+
+pub fn rules(lint: &Value) -> Result<Rules, String> {
+    let mut rules = Rules::default();
+    let table = match lint.as_object() {
+        Some(table) => table,
+        None => return Ok(rules),
+    };
+    for (key, value) in table {
+        let case = || {
+            value.as_str().and_then(Case::parse).ok_or(format!(
+                "lint.{}: expected snake_case, camelCase or PascalCase",
+                key
+            ))
+        };
+        match key.as_str() {
+            "record_namespace" => rules.record_namespace = value == true,
+            "field_doc" => rules.field_doc = value == true,
+            "field_case" => rules.field_case = Some(case()?),
+            "record_case" => rules.record_case = Some(case()?),
+            "warn" => {
+                for rule in value.as_array().into_iter().flatten() {
+                    let rule = rule.as_str().unwrap_or_default();
+                    if !RULES.contains(&rule) {
+                        return Err(format!("lint.warn: unknown rule {}", rule));
+                    }
+                    rules.warn.insert(rule.to_owned());
+                }
+            }
+            _ => return Err(format!("lint.{}: unknown rule", key)),
+        }
+    }
+    Ok(rules)
+}
+
+impl Rules {
+    pub fn is_empty(&self) -> bool {
+        !self.record_namespace
+            && !self.field_doc
+            && self.field_case.is_none()
+            && self.record_case.is_none()
+    }
+
+    pub fn severity(&self, rule: &str) -> &'static str {
+        match self.warn.contains(rule) {
+            true => "warning",
+            false => "error",
+        }
+    }
+}
+
+// every violation in the declarations, with the rule it breaks; a record-level one has an
+// empty field. Field docs aren't part of the schemas, so `documented` looks for them in the
+// sources.
+pub fn lint(
+    schemas: &[Value],
+    rules: &Rules,
+    documented: &dyn Fn(&str, &str) -> bool,
+) -> Vec<(&'static str, Diagnostic)> {
+    let mut violations = Vec::new();
+    for schema in schemas {
+        let record = schema["name"].as_str().unwrap_or_default();
+        let mut violation = |rule: &'static str, field: &str, message: String| {
+            violations.push((
+                rule,
+                Diagnostic {
+                    record: record.to_owned(),
+                    field: field.to_owned(),
+                    message: format!("{} ({})", message, rule),
+                },
+            ));
+        };
+
+        if rules.record_namespace && schema["namespace"].as_str().unwrap_or_default().is_empty() {
+            violation("record_namespace", "", "has no namespace".to_owned());
+        }
+        if let Some(case) = rules.record_case.filter(|case| !case.matches(record)) {
+            violation("record_case", "", format!("the name isn't {}", case.name()));
+        }
+        for field in schema["fields"].as_array().into_iter().flatten() {
+            let name = field["name"].as_str().unwrap_or_default();
+            if rules.field_doc
+                && field["doc"].as_str().unwrap_or_default().is_empty()
+                && !documented(record, name)
+            {
+                violation("field_doc", name, "has no doc".to_owned());
+            }
+            if let Some(case) = rules.field_case.filter(|case| !case.matches(name)) {
+                violation(
+                    "field_case",
+                    name,
+                    format!("the name isn't {}", case.name()),
+                );
+            }
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::{lint, rules, Case};
+    use serde_json::json;
+
+    #[test]
+    fn test_lint() {
+        let config = crate::config::parse(
+            "[lint]\nrecord_namespace = true\nfield_doc = true\nfield_case = \"snake_case\"\nwarn = [\"field_doc\"]\n",
+        )
+        .unwrap();
+        let rules = rules(&config["lint"]).unwrap();
+        let schemas = vec![
+            json!({
+                "type": "record",
+                "name": "Order",
+                "fields": [
+                    { "name": "order_id", "type": "string", "doc": "The id" },
+                    { "name": "lineItems", "type": "string" },
+                    { "name": "total", "type": "double" }
+                ]
+            }),
+            json!({ "type": "enum", "name": "Status", "namespace": "com.shop", "symbols": ["A"] }),
+        ];
+
+        let violations: Vec<(&str, String)> = lint(&schemas, &rules, &|_, field| field == "total")
+            .into_iter()
+            .map(|(rule, diagnostic)| (rules.severity(rule), diagnostic.to_string()))
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                (
+                    "error",
+                    "Order: has no namespace (record_namespace)".to_owned()
+                ),
+                (
+                    "warning",
+                    "Order.lineItems: has no doc (field_doc)".to_owned()
+                ),
+                (
+                    "error",
+                    "Order.lineItems: the name isn't snake_case (field_case)".to_owned()
+                ),
+            ]
+        );
+
+        assert!(Case::Camel.matches("lineItems"));
+        assert!(!Case::Camel.matches("LineItems"));
+        assert!(Case::Pascal.matches("LineItem"));
+        assert!(!Case::Snake.matches("line__items"));
+        assert_eq!(
+            crate::lint::rules(&json!({ "field_case": "kebab" })),
+            Err("lint.field_case: expected snake_case, camelCase or PascalCase".to_owned())
+        );
+        assert_eq!(
+            crate::lint::rules(&json!({ "field_docs": true })),
+            Err("lint.field_docs: unknown rule".to_owned())
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::Grammar;
+use std::collections::BTreeSet;
+use tree_sitter::{Node, Parser};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Declaration {
+    pub kind: &'static str,
+    pub name: String,
+    pub file: String,
+    pub line: usize,
+    pub exported: bool,
+    // every type name referenced by the declaration, declared or not
+    pub references: BTreeSet<String>,
+}
+
+pub fn declarations(code: &str, grammar: Grammar, file: &str) -> Vec<Declaration> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(grammar.language())
+        .expect("Error loading typescript grammar");
+    let parsed = parser.parse(code, None).unwrap();
+
+    let mut found = Vec::new();
+    collect(&parsed.root_node(), code, file, false, &mut found);
+    found
+}
+
+fn collect(parent: &Node, code: &str, file: &str, exported: bool, found: &mut Vec<Declaration>) {
+    let mut cursor = parent.walk();
+    for node in parent.named_children(&mut cursor) {
+        let (node, exported) = match node.kind() {
+            "export_statement" => match node.child_by_field_name("declaration") {
+                Some(declaration) => (declaration, true),
+                None => continue,
+            },
+            _ => (node, exported),
+        };
+        let node = match node.kind() {
+            "ambient_declaration" | "expression_statement" => match node.named_child(0) {
+                Some(declaration) => declaration,
+                None => continue,
+            },
+            _ => node,
+        };
+
+        let kind = match node.kind() {
+            "interface_declaration" => "interface",
+            "type_alias_declaration" => "type",
+            "enum_declaration" => "enum",
+            "module" | "internal_module" => {
+                if let Some(body) = node.child_by_field_name("body") {
+                    collect(&body, code, file, exported, found);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let name = match node.child_by_field_name("name") {
+            Some(name) => name.utf8_text(code.as_bytes()).unwrap().to_owned(),
+            None => continue,
+        };
+
+        let mut references = BTreeSet::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() != "type_identifier" && child.kind() != "identifier" {
+                type_references(&child, code, &mut references);
+            }
+        }
+        references.remove(&name);
+
+        found.push(Declaration {
+            kind,
+            name,
+            file: file.to_owned(),
+            line: node.start_position().row + 1,
+            exported,
+            references,
+        });
+    }
+}
+
+fn type_references(node: &Node, code: &str, references: &mut BTreeSet<String>) {
+    if node.kind() == "type_identifier" {
+        references.insert(node.utf8_text(code.as_bytes()).unwrap().to_owned());
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        type_references(&child, code, references);
+    }
+}
+
+// one row per declaration; dependencies count only the references declared in the listed files
+pub fn render(declarations: &[Declaration]) -> String {
+    let names: BTreeSet<&str> = declarations.iter().map(|x| x.name.as_str()).collect();
+    let mut rows = vec![[
+        "KIND".to_owned(),
+        "NAME".to_owned(),
+        "EXPORTED".to_owned(),
+        "DEPS".to_owned(),
+        "FILE".to_owned(),
+    ]];
+    for declaration in declarations {
+        let dependencies = declaration
+            .references
+            .iter()
+            .filter(|reference| names.contains(reference.as_str()))
+            .count();
+        rows.push([
+            declaration.kind.to_owned(),
+            declaration.name.clone(),
+            if declaration.exported { "yes" } else { "no" }.to_owned(),
+            dependencies.to_string(),
+            format!("{}:{}", declaration.file, declaration.line),
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..4)
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let mut out = String::new();
+    for row in rows {
+        for (cell, width) in row.iter().zip(&widths) {
+            out.push_str(&format!("{:width$}  ", cell, width = width));
+        }
+        out.push_str(&row[4]);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::list::{declarations, render};
+    use crate::Grammar;
+
+    #[test]
+    fn test_list() {
+        let code = r#"
+export interface User {
+    id: string;
+    address: Address;
+    roles: Array<Role>;
+    created: Date;
+}
+
+interface Address {
+    city: string;
+}
+
+export enum Role {
+    Admin,
+}
+
+declare namespace Api {
+    export type Page<T> = { items: T[]; user: User };
+}
+"#;
+
+        let found = declarations(code, Grammar::TypeScript, "src/user.ts");
+
+        assert_eq!(found.len(), 4);
+        assert_eq!(found[0].kind, "interface");
+        assert!(found[0].exported);
+        assert!(!found[1].exported);
+        assert_eq!(found[3].name, "Page");
+        assert_eq!(
+            render(&found),
+            "KIND       NAME     EXPORTED  DEPS  FILE
+interface  User     yes       2     src/user.ts:2
+interface  Address  no        0     src/user.ts:9
+enum       Role     yes       0     src/user.ts:13
+type       Page     yes       1     src/user.ts:18
+"
+        );
+    }
+}
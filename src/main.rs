@@ -1,192 +1,4576 @@
 use serde_json::{json, Map, Value};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashSet};
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
 use tree_sitter::Parser;
 
+mod avro;
+mod compat;
+mod completions;
+mod config;
+mod container;
+mod csharp;
+mod decorators;
+mod diagnostic;
+mod dictionary;
+mod diff;
+mod envelope;
+mod error;
+mod examples;
+mod faker;
+mod fix;
+mod fixtures;
+mod formats;
+mod graph;
+mod http;
+mod imports;
+mod interactive;
+mod ir;
+mod java;
+mod jsdoc;
+mod json_schema;
+mod lint;
+mod list;
+mod manifest;
+mod mapping;
+mod namespace;
+mod overlay;
+mod plugin;
+mod progress;
+mod python;
+mod registry;
+mod renames;
+#[cfg(feature = "rust-serde")]
+mod rust_serde;
+mod snapshot;
+mod summary;
+mod symbols;
+mod topic;
+mod transform;
+mod ts_const;
+mod verify;
+
+use avro::UnionStrategy;
+use error::ConversionError;
+use formats::sql::Dialect;
+use formats::Format;
+use mapping::{Mapping, Methods};
+use symbols::Symbols;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Grammar {
+    TypeScript,
+    Tsx,
+}
+
+impl Grammar {
+    fn from_path(file_path: &str, jsx: bool) -> Grammar {
+        if jsx || file_path.ends_with(".tsx") || file_path.ends_with(".jsx") {
+            Grammar::Tsx
+        } else {
+            Grammar::TypeScript
+        }
+    }
+
+    fn language(&self) -> tree_sitter::Language {
+        match self {
+            Grammar::TypeScript => tree_sitter_typescript::language_typescript(),
+            Grammar::Tsx => tree_sitter_typescript::language_tsx(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Args {
+    file_path: String,
+    jsx: bool,
+    format: Format,
+    format_options: formats::Options,
+    config: Option<String>,
+    profile: Option<String>,
+    schema_version: Option<String>,
+    version_from_git: bool,
+    version_property: Option<String>,
+    renames: Option<String>,
+    overlay: Option<String>,
+    envelope: Option<String>,
+    key_out: Option<String>,
+    unions: Option<UnionStrategy>,
+    mapping: Mapping,
+    type_map: Option<String>,
+    summary: bool,
+    interactive: bool,
+    quiet: bool,
+    fail_on_warnings: bool,
+    json_diagnostics: bool,
+    methods: Option<Methods>,
+    namespace: Option<String>,
+    namespace_from_path: Option<String>,
+    namespace_template: Option<String>,
+    require_namespace: bool,
+    topic_union: Option<String>,
+    root: Option<String>,
+    keep_unreachable: bool,
+    include_private: bool,
+    emit: Option<String>,
+    pydantic: bool,
+    html: bool,
+    out_dir: Option<String>,
+    plugins: Vec<String>,
+    transform: Option<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut file_path = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--jsx" => parsed.jsx = true,
+            "--format" => {
+                let name = iter.next().ok_or("--format expects a value")?;
+                parsed.format = Format::parse(name).ok_or(format!("Unknown format: {}", name))?;
+            }
+            "--dialect" => {
+                let name = iter.next().ok_or("--dialect expects a value")?;
+                parsed.format_options.dialect =
+                    Dialect::parse(name).ok_or(format!("Unknown SQL dialect: {}", name))?;
+            }
+            "--flatten-nested" => parsed.format_options.flatten_nested = true,
+            "--field-ids" => {
+                let path = iter.next().ok_or("--field-ids expects a file path")?;
+                parsed.format_options.field_ids = Some(path.to_owned());
+            }
+            "--config" => {
+                let path = iter.next().ok_or("--config expects a file path")?;
+                parsed.config = Some(path.to_owned());
+            }
+            "--profile" => {
+                let name = iter.next().ok_or("--profile expects a name")?;
+                parsed.profile = Some(name.to_owned());
+            }
+            "--schema-version" => {
+                let version = iter.next().ok_or("--schema-version expects a value")?;
+                parsed.schema_version = Some(version.to_owned());
+            }
+            "--schema-version-from-git" => parsed.version_from_git = true,
+            "--version-property" => {
+                let name = iter.next().ok_or("--version-property expects a name")?;
+                parsed.version_property = Some(name.to_owned());
+            }
+            "--renames" => {
+                let path = iter.next().ok_or("--renames expects a file path")?;
+                parsed.renames = Some(path.to_owned());
+            }
+            "--overlay" => {
+                let path = iter.next().ok_or("--overlay expects a file path")?;
+                parsed.overlay = Some(path.to_owned());
+            }
+            "--plugin" => {
+                let plugin = iter.next().ok_or("--plugin expects a command")?;
+                parsed.plugins.push(plugin.to_owned());
+            }
+            "--transform" => {
+                let expression = iter.next().ok_or("--transform expects an expression")?;
+                parsed.transform = Some(expression.to_owned());
+            }
+            "--envelope" => {
+                let name = iter.next().ok_or("--envelope expects a value")?;
+                if name != "cloudevents" {
+                    return Err(format!("Unknown envelope: {}", name));
+                }
+                parsed.envelope = Some(name.to_owned());
+            }
+            "--emit" => {
+                let name = iter.next().ok_or("--emit expects a value")?;
+                if ![
+                    "ts-const",
+                    "rust-serde",
+                    "python",
+                    "csharp",
+                    "java",
+                    "examples",
+                    "dictionary",
+                    "json-schema",
+                ]
+                .contains(&name.as_str())
+                {
+                    return Err(format!("Unknown output to emit: {}", name));
+                }
+                parsed.emit = Some(name.to_owned());
+            }
+            "--pydantic" => parsed.pydantic = true,
+            "--html" => parsed.html = true,
+            "--out-dir" => {
+                let dir = iter.next().ok_or("--out-dir expects a directory")?;
+                parsed.out_dir = Some(dir.to_owned());
+            }
+            "--key-out" => {
+                let path = iter.next().ok_or("--key-out expects a file path")?;
+                parsed.key_out = Some(path.to_owned());
+            }
+            "--root" => {
+                let name = iter.next().ok_or("--root expects a record name")?;
+                parsed.root = Some(name.to_owned());
+            }
+            "--keep-unreachable" => parsed.keep_unreachable = true,
+            "--include-private" => parsed.include_private = true,
+            "--include-classes" => parsed.mapping.classes = true,
+            "--emit-topic-union" => {
+                let topic = iter.next().ok_or("--emit-topic-union expects a topic")?;
+                parsed.topic_union = Some(topic.to_owned());
+            }
+            "--infer-timestamps" => parsed.mapping.infer_timestamps = true,
+            "--ids-as-long" => parsed.mapping.ids_as_long = true,
+            "--java-string" => parsed.mapping.java_string = true,
+            "--evolution-safe" => parsed.mapping.evolution_safe = true,
+            "--accessors-as-fields" => parsed.mapping.accessors = true,
+            "--strip-fields" => {
+                let patterns = iter.next().ok_or("--strip-fields expects patterns")?;
+                let patterns = patterns.split(',').map(str::trim).filter(|x| !x.is_empty());
+                parsed.mapping.strip.extend(patterns.map(str::to_owned));
+            }
+            "--summary" => parsed.summary = true,
+            "--interactive" => parsed.interactive = true,
+            "--quiet" => parsed.quiet = true,
+            "--fail-on-warnings" => parsed.fail_on_warnings = true,
+            "--type-map" => {
+                let path = iter.next().ok_or("--type-map expects a file path")?;
+                parsed.type_map = Some(path.to_owned());
+            }
+            "--methods" => {
+                let name = iter.next().ok_or("--methods expects a value")?;
+                parsed.methods = Some(
+                    Methods::parse(name).ok_or(format!("Unknown methods handling: {}", name))?,
+                );
+            }
+            "--namespace" => {
+                let name = iter.next().ok_or("--namespace expects a value")?;
+                parsed.namespace = Some(name.to_owned());
+            }
+            "--namespace-template" => {
+                let template = iter
+                    .next()
+                    .ok_or("--namespace-template expects a template")?;
+                parsed.namespace_template = Some(template.to_owned());
+            }
+            "--require-namespace" => parsed.require_namespace = true,
+            "--namespace-from-path" => {
+                let path = iter
+                    .next()
+                    .ok_or("--namespace-from-path expects a directory")?;
+                parsed.namespace_from_path = Some(path.to_owned());
+            }
+            "--diagnostics" => {
+                let name = iter.next().ok_or("--diagnostics expects a value")?;
+                parsed.json_diagnostics = match name.as_str() {
+                    "human" => false,
+                    "json" => true,
+                    _ => return Err(format!("Unknown diagnostics format: {}", name)),
+                };
+            }
+            "--unions" => {
+                let name = iter.next().ok_or("--unions expects a value")?;
+                parsed.unions = Some(
+                    UnionStrategy::parse(name)
+                        .ok_or(format!("Unknown union strategy: {}", name))?,
+                );
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option: {}", arg)),
+            _ if file_path.is_none() => file_path = Some(arg.to_owned()),
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+
+    parsed.file_path = file_path.ok_or("Missing <file_path>")?;
+    Ok(parsed)
+}
+
+fn usage(program: &str) -> String {
+    format!(
+        "Usage: {0} [--jsx] [--config <file>] [--profile <name>]
+          [--format avro|arrow|parquet|bigquery|sql|iceberg|thrift|fbs]
+          [--dialect postgres|snowflake] [--flatten-nested] [--field-ids <file>]
+          [--schema-version <version>] [--schema-version-from-git] [--version-property <name>]
+          [--renames <file>] [--overlay <file>] [--plugin <command>]...
+          [--transform <expression>] [--envelope cloudevents] [--key-out <file>]
+          [--emit-topic-union <topic>] [--root <name>] [--keep-unreachable]
+          [--include-private] [--include-classes]
+          [--emit ts-const|rust-serde|python|csharp|java|examples|dictionary|json-schema]
+          [--pydantic] [--html] [--out-dir <dir>]
+          [--unions union|string|strict] [--infer-timestamps] [--ids-as-long]
+          [--type-map <file>] [--java-string] [--methods skip|strict|getters]
+          [--accessors-as-fields] [--evolution-safe] [--strip-fields <patterns>]
+          [--namespace <name>] [--namespace-from-path <dir>]
+          [--namespace-template <template>] [--require-namespace] [--summary]
+          [--interactive] [--quiet] [--fail-on-warnings] [--diagnostics human|json]
+          <file_path>
+       {0} evolve [options] [--fix] [--renames <file>] <old_path> <new_path>
+       {0} list <path>
+       {0} graph [--format dot|json] <path>
+       {0} verify [options] <file_path>
+       {0} snapshot update|check [options] <path>
+       {0} fixtures [--count <n>] [--wire-format <dir>] [--schema-id <id>]
+          [--subject-map <file>] [registry options] <path>
+       {0} sample [options] [--format avro] [-n <count>] -o <file> <path>
+       {0} lint [--config <file>] [--profile <name>] [--diagnostics human|json]
+          [--fail-on-warnings] <path>
+       {0} publish [--all] [--dry-run] [--use-references] [--verify-level]
+          [--subject <name>] [--subject-map <file>] [--keep-going] [registry options]
+          [options] <path>
+       {0} drift [--all] [--subject <name>] [--subject-map <file>] [registry options]
+          [options] <path>
+       {0} batch [--dry-run] <manifest>
+       {0} pull [--out-dir <dir>] [--filter <pattern>]... [--config <file>]
+          [--profile <name>] [registry options]
+       {0} explain [options] <file_path> --field <Record.field>
+       {0} completions bash|zsh|fish|powershell
+
+Registry options: [--registry <url>] [--registry-user <user:password>]
+          [--registry-token <token>] [--registry-cert <file>] [--registry-key <file>]
+          [--registry-ca <file>] [--registry-timeout <seconds>] [--registry-retries <n>]
+          [--registry-allow-http]
+https:// registries are reached through curl and HTTPS_PROXY, http:// ones through
+HTTP_PROXY, unless NO_PROXY lists them. Credentials are only sent without TLS given
+--registry-allow-http.",
+        program
+    )
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <file_path>", args[0]);
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("evolve") => evolve(&args),
+        Some("list") => list(&args),
+        Some("graph") => graph(&args),
+        Some("snapshot") => snapshot(&args),
+        Some("fixtures") => fixtures(&args),
+        Some("sample") => sample(&args),
+        Some("lint") => lint(&args),
+        Some("publish") => publish(&args),
+        Some("drift") => drift(&args),
+        Some("batch") => batch(&args),
+        Some("pull") => pull(&args),
+        Some("explain") => explain(&args),
+        Some("completions") => completions(&args),
+        Some("verify") => {
+            let mut args = args.clone();
+            args.remove(1);
+            convert(&args, true)
+        }
+        _ => convert(&args, false),
+    }
+}
+
+// with `verify`, the Avro schema is checked instead of printed
+fn convert(args: &[String], verify: bool) {
+    let mut parsed = match parse_args(&args[1..]) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("{}", usage(&args[0]));
+            std::process::exit(1);
+        }
+    };
+    let file_path = &parsed.file_path.clone();
+    let format = parsed.format;
+    if verify && format != Format::Avro {
+        eprintln!("verify only checks Avro schemas");
+        std::process::exit(1);
+    }
+    if parsed.emit.is_some() && format != Format::Avro {
+        eprintln!("--emit only exports Avro schemas");
+        std::process::exit(1);
+    }
+    match (parsed.emit.as_deref(), &parsed.out_dir) {
+        (Some(emit @ ("java" | "json-schema")), None) => {
+            eprintln!("--emit {} needs an --out-dir for its files", emit);
+            std::process::exit(1);
+        }
+        (Some("java" | "json-schema" | "examples"), Some(_)) | (_, None) => {}
+        (_, Some(_)) => {
+            eprintln!("--out-dir is only for --emit java, json-schema and examples");
+            std::process::exit(1);
+        }
+    }
+
+    let mut config = match config::load(parsed.config.as_deref(), parsed.profile.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(err) = configure_mapping(&mut parsed, &config) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+
+    // a directory converts as one model, rooted at the first declaration found, and the
+    // files imported from it join the model
+    let files = imports::follow(source_files(Path::new(file_path)), parsed.jsx);
+    let batch = Path::new(file_path).is_dir();
+    let mut progress = progress::Progress::new(files.len(), batch && !parsed.quiet);
+    let mut summary = summary::Summary::default();
+    let mut inputs = Vec::new();
+    for path in files {
+        let path = path.to_string_lossy().into_owned();
+        let started = Instant::now();
+        let input = Input {
+            code: read_source(&path),
+            grammar: Grammar::from_path(&path, parsed.jsx),
+            namespace: input_namespace(&parsed, &config, &path),
+            path,
+        };
+        progress.time("read", started);
+
+        let started = Instant::now();
+        let source = input.source();
+        let errors = source.syntax_errors();
+        for span in &errors {
+            summary.warn("syntax");
+            let message = "syntax error, declarations here may be incomplete";
+            match parsed.json_diagnostics {
+                true => eprintln!(
+                    "{}",
+                    source.json_frame("warning", message, Some(span.clone()))
+                ),
+                false => eprint!("{}", source.frame("warning", message, Some(span.clone()))),
+            }
+        }
+        progress.time("parse", started);
+        let status = match errors.len() {
+            0 => "ok".to_owned(),
+            1 => "1 syntax error".to_owned(),
+            n => format!("{} syntax errors", n),
+        };
+        progress.file(&input.path, &status);
+        inputs.push(input);
+    }
+
+    let started = Instant::now();
+    parsed.mapping.trace = Some(RefCell::new(Vec::new()));
+    let private = match parsed.include_private || config["include_private"] == true {
+        true => BTreeSet::new(),
+        false => private_declarations(&inputs),
+    };
+    let mut schemas = match parsed.interactive {
+        true => interact(&mut parsed, &mut config, &inputs, &private),
+        false => load_inputs(&inputs, &parsed.mapping),
+    };
+    let mut dropped = Vec::new();
+    for member in parsed.mapping.dropped.take() {
+        // interactive runs load the sources again after each answer
+        if !dropped.contains(&member) {
+            dropped.push(member);
+        }
+    }
+    let severity = match parsed.mapping.methods {
+        Methods::Strict => "error",
+        _ => "warning",
+    };
+    for member in &dropped {
+        summary.warn("dropped members");
+        eprint!(
+            "{}",
+            render(&inputs, severity, member, parsed.json_diagnostics)
+        );
+    }
+    if severity == "error" && !dropped.is_empty() {
+        std::process::exit(1);
+    }
+    let mut sanitized = Vec::new();
+    for field in parsed.mapping.sanitized.take() {
+        if !sanitized.contains(&field) {
+            sanitized.push(field);
+        }
+    }
+    for field in &sanitized {
+        summary.warn("sanitized names");
+        eprint!(
+            "{}",
+            render(&inputs, "warning", field, parsed.json_diagnostics)
+        );
+    }
+    let root = parsed.root.as_deref().or(config["root"].as_str());
+    if let Some(root) = root {
+        match schemas.iter().position(|x| x["name"] == root) {
+            Some(i) => {
+                let root = schemas.remove(i);
+                schemas.insert(0, root);
+            }
+            None => {
+                eprintln!("The root {} is not declared in {}", root, file_path);
+                std::process::exit(1);
+            }
+        }
+        // helper declarations the root doesn't use are left out of every output
+        if !parsed.keep_unreachable && config["keep_unreachable"] != true {
+            schemas = avro::reachable(schemas);
+        }
+    } else if let Some(i) = schemas.iter().position(|x| !is_private(x, &private)) {
+        // internal declarations are only there to resolve references from exported ones
+        let root = schemas.remove(i);
+        schemas.insert(0, root);
+    }
+    if parsed.require_namespace || config["namespaces"]["required"] == true {
+        let missing: Vec<diagnostic::Diagnostic> = schemas
+            .iter()
+            .filter(|schema| schema["namespace"].as_str().unwrap_or_default().is_empty())
+            .map(|schema| diagnostic::Diagnostic {
+                record: schema["name"].as_str().unwrap_or_default().to_owned(),
+                field: String::new(),
+                message: "no namespace could be determined; set --namespace, --namespace-from-path, --namespace-template or @avro.namespace".to_owned(),
+            })
+            .collect();
+        for diagnostic in &missing {
+            eprint!(
+                "{}",
+                render(&inputs, "error", diagnostic, parsed.json_diagnostics)
+            );
+        }
+        if !missing.is_empty() {
+            std::process::exit(1);
+        }
+    }
+
+    let renames_path = parsed.renames.as_deref().or(config["renames"].as_str());
+    if let Some(path) = renames_path {
+        match renames::load(path) {
+            Ok(renames) => renames::apply(&mut schemas, &renames),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+    let overlay_path = parsed.overlay.as_deref().or(config["overlay"].as_str());
+    if let Some(path) = overlay_path {
+        let applied =
+            overlay::load(path).and_then(|overlay| overlay::apply(&mut schemas, &overlay));
+        if let Err(err) = applied {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    let strategy = match union_strategy(&parsed, &config) {
+        Ok(strategy) => strategy,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    match avro::collapse_unions(&mut schemas, strategy) {
+        Ok(warnings) => {
+            for warning in warnings {
+                summary.warn("unions");
+                eprint!(
+                    "{}",
+                    render(&inputs, "warning", &warning, parsed.json_diagnostics)
+                );
+            }
+        }
+        Err(err) => {
+            eprint!(
+                "{}",
+                render(&inputs, "error", &err, parsed.json_diagnostics)
+            );
+            std::process::exit(1);
+        }
+    }
+    let violations = match avro::UnionPolicy::parse(&config["unions"]) {
+        Ok(policy) => policy.check(&schemas),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    for violation in &violations {
+        eprint!(
+            "{}",
+            render(&inputs, "error", violation, parsed.json_diagnostics)
+        );
+    }
+    if !violations.is_empty() {
+        std::process::exit(1);
+    }
+
+    let steps = parsed.mapping.trace.take().unwrap_or_default().into_inner();
+    for _ in mapping::heuristics_applied(&steps) {
+        summary.warn("heuristics");
+    }
+    summary.count(&schemas);
+    progress.time("convert", started);
+
+    let fail_on_warnings = parsed.fail_on_warnings || config["fail_on_warnings"] == true;
+    if fail_on_warnings && !summary.warnings.is_empty() {
+        let warnings: Vec<String> = summary
+            .warnings
+            .iter()
+            .map(|(category, count)| format!("{} {}", count, category))
+            .collect();
+        let message = format!(
+            "failing on warnings ({}) because --fail-on-warnings is set",
+            warnings.join(", ")
+        );
+        match parsed.json_diagnostics {
+            true => eprintln!("{}", json!({ "severity": "error", "message": message })),
+            false => eprintln!("error: {}", message),
+        }
         std::process::exit(1);
     }
+    let started = Instant::now();
+
+    if let Some(path) = &parsed.key_out {
+        if let Err(err) = write_key_schema(&schemas, path) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        summary.outputs.push(path.to_owned());
+    }
+
+    if let Some(topic) = &parsed.topic_union {
+        let path = topic::path(topic, &config);
+        let written = topic::union(&schemas, topic, &config, &private).and_then(|union| {
+            fs::write(&path, format!("{}\n", union))
+                .map_err(|err| format!("Failed to write {}: {}", path, err))
+        });
+        if let Err(err) = written {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        summary.outputs.push(path);
+    }
+
+    if format == Format::Avro {
+        // the module exports the other public records beside the root
+        let others = match parsed.emit {
+            Some(_) => other_records(&schemas, &private),
+            None => Ok(Vec::new()),
+        };
+        let others = match others {
+            Ok(others) => others,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        };
+        let mut candidate_schema = match merger(schemas) {
+            Ok(schema) => schema,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        };
+        if let Err(err) = envelope::append_fields(&mut candidate_schema, &config["envelope"]) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        let wrap = parsed
+            .envelope
+            .as_deref()
+            .or(config["envelope"]["mode"].as_str());
+        if wrap == Some("cloudevents") {
+            candidate_schema = envelope::wrap_cloudevents(candidate_schema, &config["envelope"]);
+        }
+        match schema_version(&parsed, &config) {
+            Ok(Some(version)) => {
+                let property = parsed
+                    .version_property
+                    .as_deref()
+                    .or(config["metadata"]["version_property"].as_str())
+                    .unwrap_or("version");
+                candidate_schema[property] = json!(version);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        let plugins = plugin::plugins(&parsed.plugins, &config);
+        candidate_schema = match plugin::run_all(&plugins, candidate_schema) {
+            Ok(schema) => schema,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        };
+        let expression = parsed.transform.as_deref().or(config["transform"].as_str());
+        match expression.map(transform::parse).transpose() {
+            Ok(Some(steps)) => transform::apply(&mut candidate_schema, &steps),
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        if let (false, Some(emit)) = (verify, &parsed.emit) {
+            let mut exported = vec![candidate_schema];
+            exported.extend(others);
+            let module = match emit.as_str() {
+                "rust-serde" => rust_models(&exported),
+                "python" => Ok(python::module(&exported, parsed.pydantic)),
+                "csharp" => Ok(csharp::module(&exported)),
+                "java" => {
+                    let out_dir = parsed.out_dir.as_deref().unwrap_or_default();
+                    write_classes(&exported, out_dir, &mut summary.outputs).map(|_| String::new())
+                }
+                "json-schema" => {
+                    let out_dir = parsed.out_dir.as_deref().unwrap_or_default();
+                    write_json_schemas(&exported, out_dir, &mut summary.outputs)
+                        .map(|_| String::new())
+                }
+                "dictionary" => {
+                    let dictionary = dictionary::dictionary(&exported);
+                    match parsed.html {
+                        true => Ok(dictionary::html(&dictionary)),
+                        false => Ok(format!("{:#}\n", dictionary)),
+                    }
+                }
+                "examples" => match parsed.out_dir.as_deref() {
+                    Some(out_dir) => write_examples(&exported, out_dir, &mut summary.outputs)
+                        .map(|_| String::new()),
+                    None => {
+                        let documents: serde_json::Map<String, Value> =
+                            examples::documents(&exported).into_iter().collect();
+                        Ok(format!("{:#}\n", Value::Object(documents)))
+                    }
+                },
+                _ => Ok(ts_const::module(&exported)),
+            };
+            match module {
+                Ok(module) => print!("{}", module),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+        } else if !verify {
+            println!("{}", json!(candidate_schema));
+        } else if let Err(problems) = verify::verify(&candidate_schema, VERIFIED_RECORDS) {
+            for problem in problems {
+                eprintln!("error: {}", problem);
+            }
+            std::process::exit(1);
+        } else {
+            println!(
+                "{}: {} random records written and read back",
+                candidate_schema["name"].as_str().unwrap_or_default(),
+                VERIFIED_RECORDS
+            );
+        }
+    } else {
+        let record = match ir::resolve(&schemas) {
+            Some(record) => record,
+            None => {
+                eprintln!("No interfaces found in {}", file_path);
+                std::process::exit(1);
+            }
+        };
+        match formats::render(format, &record, &parsed.format_options) {
+            Ok(rendered) => print!("{}", rendered),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        if let (Some(path), Format::Iceberg) = (&parsed.format_options.field_ids, format) {
+            summary.outputs.push(path.to_owned());
+        }
+    }
+
+    progress.time("render", started);
+    progress.finish();
+
+    if parsed.summary {
+        eprint!("{}", summary);
+    }
+}
+
+const VERIFIED_RECORDS: usize = 100;
+
+// a source file per named type, in the directories of its package
+fn write_classes(
+    schemas: &[Value],
+    out_dir: &str,
+    outputs: &mut Vec<String>,
+) -> Result<(), String> {
+    for (path, source) in java::classes(schemas) {
+        let path = Path::new(out_dir).join(path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| format!("Error creating {}: {}", dir.display(), err))?;
+        }
+        fs::write(&path, source)
+            .map_err(|err| format!("Error writing {}: {}", path.display(), err))?;
+        outputs.push(path.display().to_string());
+    }
+    Ok(())
+}
+
+// Each public record as `<full name>.avsc` next to the JSON Schema of its documents as
+// `<full name>.schema.json`, for clients validating what they produce.
+fn write_json_schemas(
+    schemas: &[Value],
+    out_dir: &str,
+    outputs: &mut Vec<String>,
+) -> Result<(), String> {
+    fs::create_dir_all(out_dir).map_err(|err| format!("Error creating {}: {}", out_dir, err))?;
+    for schema in schemas {
+        let name = symbols::full_name(schema);
+        let files = [
+            (format!("{}.avsc", name), schema.clone()),
+            (
+                format!("{}.schema.json", name),
+                json_schema::json_schema(schema),
+            ),
+        ];
+        for (file, content) in files {
+            let path = Path::new(out_dir).join(file);
+            fs::write(&path, format!("{:#}\n", content))
+                .map_err(|err| format!("Error writing {}: {}", path.display(), err))?;
+            outputs.push(path.display().to_string());
+        }
+    }
+    Ok(())
+}
+
+// a document per record, as `<full name>.json`
+fn write_examples(
+    schemas: &[Value],
+    out_dir: &str,
+    outputs: &mut Vec<String>,
+) -> Result<(), String> {
+    fs::create_dir_all(out_dir).map_err(|err| format!("Error creating {}: {}", out_dir, err))?;
+    for (name, document) in examples::documents(schemas) {
+        let path = Path::new(out_dir).join(format!("{}.json", name));
+        fs::write(&path, format!("{:#}\n", document))
+            .map_err(|err| format!("Error writing {}: {}", path.display(), err))?;
+        outputs.push(path.display().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "rust-serde")]
+fn rust_models(schemas: &[Value]) -> Result<String, String> {
+    Ok(rust_serde::module(schemas))
+}
+
+#[cfg(not(feature = "rust-serde"))]
+fn rust_models(_: &[Value]) -> Result<String, String> {
+    Err("--emit rust-serde needs a build with the rust-serde feature".to_owned())
+}
+
+// every public record but the root, each resolved as a root of its own
+fn other_records(
+    schemas: &[Value],
+    private: &BTreeSet<String>,
+) -> Result<Vec<Value>, ConversionError> {
+    let symbols = Symbols::new(schemas);
+    schemas
+        .iter()
+        .skip(1)
+        .filter(|schema| schema["type"] == "record" && !is_private(schema, private))
+        .map(|schema| resolve_root(schema, &symbols))
+        .collect()
+}
+
+struct Input {
+    path: String,
+    code: String,
+    grammar: Grammar,
+    namespace: Option<String>,
+}
+
+impl Input {
+    fn source(&self) -> diagnostic::Source<'_> {
+        diagnostic::Source {
+            path: &self.path,
+            code: &self.code,
+            grammar: self.grammar,
+        }
+    }
+}
+
+fn input_namespace(parsed: &Args, config: &Value, path: &str) -> Option<String> {
+    let namespace = parsed.namespace.as_deref().or(config["namespace"].as_str());
+    let base = parsed
+        .namespace_from_path
+        .as_deref()
+        .or(config["namespace_from_path"].as_str());
+    let found = match base {
+        Some(base) => namespace::from_path(Path::new(base), Path::new(path), namespace),
+        None => namespace.map(|namespace| namespace.to_owned()),
+    };
+    // the template only fills in for files nothing else gives a namespace
+    let template = parsed
+        .namespace_template
+        .as_deref()
+        .or(config["namespaces"]["template"].as_str());
+    found.or_else(|| template.map(|template| namespace::from_template(template, Path::new(path))))
+}
+
+// Declarations without `export` are internal to their module. Files exporting nothing, like
+// scripts documented with JSDoc typedefs, keep every declaration public.
+fn private_declarations(inputs: &[Input]) -> BTreeSet<String> {
+    let mut private = BTreeSet::new();
+    for input in inputs {
+        let declarations = list::declarations(&input.code, input.grammar, &input.path);
+        if declarations.iter().any(|declaration| declaration.exported) {
+            private.extend(
+                declarations
+                    .into_iter()
+                    .filter(|declaration| !declaration.exported)
+                    .map(|declaration| declaration.name),
+            );
+        }
+    }
+    private
+}
+
+fn is_private(schema: &Value, private: &BTreeSet<String>) -> bool {
+    schema["name"]
+        .as_str()
+        .is_some_and(|name| private.contains(name))
+}
+
+fn load_inputs(inputs: &[Input], mapping: &Mapping) -> Vec<Value> {
+    inputs
+        .iter()
+        .flat_map(|input| {
+            let path = Path::new(&input.path);
+            let mut schemas = match get_file_schema(&input.code, path, input.grammar, mapping) {
+                Ok(schemas) => schemas,
+                Err(err) => {
+                    eprintln!("Error converting {}: {}", input.path, err);
+                    std::process::exit(1);
+                }
+            };
+            if let Some(name) = &input.namespace {
+                namespace::apply(&mut schemas, name);
+            }
+            schemas
+        })
+        .collect()
+}
+
+// renders against the file declaring the field, when there is one
+fn render(
+    inputs: &[Input],
+    severity: &str,
+    diagnostic: &diagnostic::Diagnostic,
+    json: bool,
+) -> String {
+    let sources: Vec<diagnostic::Source> = inputs.iter().map(Input::source).collect();
+    let declaring = sources
+        .iter()
+        .find(|source| {
+            source
+                .field_span(&diagnostic.record, &diagnostic.field)
+                .is_some()
+        })
+        .or(sources.first());
+    match (declaring, json) {
+        (Some(source), true) => format!("{}\n", source.json(severity, diagnostic)),
+        (Some(source), false) => source.render(severity, diagnostic),
+        (None, true) => format!(
+            "{}\n",
+            json!({
+                "severity": severity,
+                "message": diagnostic.message,
+                "record": diagnostic.record,
+                "field": diagnostic.field
+            })
+        ),
+        (None, false) => format!("{}: {}\n", severity, diagnostic),
+    }
+}
+
+// config settings apply on top of the command line flags
+fn configure_mapping(parsed: &mut Args, config: &Value) -> Result<(), String> {
+    parsed.mapping.infer_timestamps |= config["mapping"]["infer_timestamps"] == true;
+    parsed.mapping.ids_as_long |= config["mapping"]["ids_as_long"] == true;
+    parsed.mapping.java_string |= config["mapping"]["java_string"] == true;
+    parsed.mapping.evolution_safe |= config["mapping"]["evolution_safe"] == true;
+    parsed.mapping.classes |= config["include_classes"] == true;
+    parsed.mapping.accessors |= config["mapping"]["accessors_as_fields"] == true;
+    parsed.mapping.decimals = mapping::decimal_rules(&config["mapping"])?;
+    for pattern in config["mapping"]["strip_fields"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        let pattern = pattern
+            .as_str()
+            .ok_or("mapping.strip_fields: expected a list of patterns")?;
+        parsed.mapping.strip.push(pattern.to_owned());
+    }
+    parsed.mapping.methods = match (parsed.methods, config["mapping"]["methods"].as_str()) {
+        (Some(methods), _) => methods,
+        (None, Some(name)) => Methods::parse(name).ok_or(format!(
+            "mapping.methods: unknown methods handling {}",
+            name
+        ))?,
+        (None, None) => Methods::default(),
+    };
+    let type_map = parsed
+        .type_map
+        .as_deref()
+        .or(config["mapping"]["type_map"].as_str());
+    if let Some(path) = type_map {
+        parsed.mapping.types = mapping::load_types(path)?;
+    }
+    if let Some(types) = config["mapping"]["types"].as_object() {
+        if !parsed.mapping.types.is_object() {
+            parsed.mapping.types = json!({});
+        }
+        for (name, mapped) in types {
+            parsed.mapping.types[name] = mapped.clone();
+        }
+    }
+    Ok(())
+}
+
+// asks about unknown types, the root and primitive unions when the config doesn't settle
+// them, then offers to save the answers into the config file
+fn interact(
+    parsed: &mut Args,
+    config: &mut Value,
+    inputs: &[Input],
+    private: &BTreeSet<String>,
+) -> Vec<Value> {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut output = std::io::stderr();
+    let mut session = interactive::Session::new(&mut input, &mut output);
+
+    let mut schemas = load_inputs(inputs, &parsed.mapping);
+    let mut mapped = false;
+    for name in avro::unresolved(&schemas) {
+        if let Some(ptype) = session.map_type(&name) {
+            if !parsed.mapping.types.is_object() {
+                parsed.mapping.types = json!({});
+            }
+            parsed.mapping.types[&name] = ptype;
+            mapped = true;
+        }
+    }
+    if mapped {
+        schemas = load_inputs(inputs, &parsed.mapping);
+    }
+
+    let candidates: Vec<String> = interactive::root_candidates(&schemas)
+        .into_iter()
+        .filter(|candidate| !private.contains(candidate))
+        .collect();
+    if config["root"].is_null() && parsed.root.is_none() && candidates.len() > 1 {
+        let root = session.choose_root(&candidates);
+        config["root"] = json!(root);
+    }
+
+    if parsed.unions.is_none() && config["unions"]["strategy"].is_null() {
+        let mut collapsed = schemas.clone();
+        let fields: Vec<String> = avro::collapse_unions(&mut collapsed, UnionStrategy::String)
+            .unwrap_or_default()
+            .iter()
+            .map(|warning| format!("{}.{}", warning.record, warning.field))
+            .collect();
+        if !fields.is_empty() {
+            parsed.unions = Some(session.union_strategy(&fields));
+        }
+    }
+
+    let answers = session.answers.clone();
+    let path = parsed.config.as_deref().unwrap_or(config::DEFAULT_PATH);
+    if answers
+        .as_object()
+        .is_some_and(|answers| !answers.is_empty())
+        && session.confirm(&format!("Save these answers to {}?", path))
+    {
+        interactive::merge(config, &answers);
+        // saved over the file as written, keeping its `${VAR}` placeholders
+        let saved = config::load_raw(parsed.config.as_deref()).map(|mut saved| {
+            interactive::merge(&mut saved, &answers);
+            saved
+        });
+        if let Err(err) = saved.and_then(|saved| config::save(path, &saved)) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+    schemas
+}
+
+fn union_strategy(parsed: &Args, config: &Value) -> Result<UnionStrategy, String> {
+    match (parsed.unions, config["unions"]["strategy"].as_str()) {
+        (Some(strategy), _) => Ok(strategy),
+        (None, Some(name)) => {
+            UnionStrategy::parse(name).ok_or(format!("Unknown union strategy in config: {}", name))
+        }
+        (None, None) => Ok(UnionStrategy::default()),
+    }
+}
+
+fn write_key_schema(schemas: &[Value], path: &str) -> Result<(), String> {
+    let name = format!(
+        "{}Key",
+        schemas
+            .first()
+            .map_or("", |x| x["name"].as_str().unwrap_or_default())
+    );
+    let position = schemas
+        .iter()
+        .position(|x| x["name"] == name)
+        .ok_or(format!(
+            "No @avro.key fields found for {}",
+            name.trim_end_matches("Key")
+        ))?;
+
+    let mut reordered = schemas.to_vec();
+    let key = reordered.remove(position);
+    reordered.insert(0, key);
+
+    let schema = merger(reordered).map_err(|err| err.to_string())?;
+    fs::write(path, format!("{}\n", schema))
+        .map_err(|err| format!("Failed to write {}: {}", path, err))
+}
+
+// an explicit version wins over the config file, which wins over the latest git tag
+fn schema_version(args: &Args, config: &Value) -> Result<Option<String>, String> {
+    if let Some(version) = &args.schema_version {
+        return Ok(Some(version.to_owned()));
+    }
+    if let Some(version) = config["metadata"]["version"].as_str() {
+        return Ok(Some(version.to_owned()));
+    }
+    if !args.version_from_git && config["metadata"]["version_from_git"] != true {
+        return Ok(None);
+    }
+
+    let output = Command::new("git")
+        .args(["describe", "--tags", "--abbrev=0"])
+        .output()
+        .map_err(|err| format!("Failed to run git: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to read the git tag: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let tag = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    Ok(Some(tag.trim_start_matches('v').to_owned()))
+}
+
+// Both versions are converted with the same options, as the main command takes them. Exits
+// non-zero when any record is incompatible, unless `--fix` made every new field nullable and
+// no other problem was found.
+fn evolve(args: &[String]) {
+    let usage = format!(
+        "Usage: {} evolve [options] [--fix] [--renames <file>] <old_path> <new_path>",
+        args[0]
+    );
+    let fix = args.iter().any(|arg| arg == "--fix");
+    let mut rest: Vec<String> = args[2..]
+        .iter()
+        .filter(|arg| *arg != "--fix")
+        .cloned()
+        .collect();
+    let new_path = match rest.pop() {
+        Some(path) if !path.starts_with("--") => path,
+        _ => {
+            eprintln!("{}", usage);
+            std::process::exit(1);
+        }
+    };
+    let (parsed, config) = load_options(&rest, &usage);
+    let paths = [parsed.file_path.as_str(), new_path.as_str()];
+
+    let (old, _) = load_path(&parsed, &config, paths[0]);
+    let (mut new, _) = load_path(&parsed, &config, paths[1]);
+    if let Some(path) = parsed.renames.as_deref().or(config["renames"].as_str()) {
+        match renames::load(path) {
+            Ok(renames) => renames::apply(&mut new, &renames),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+    let old_version = compat::Version::new(&old);
+    let new_version = compat::Version::new(&new);
+
+    let mut names: Vec<&str> = new.iter().filter_map(|x| x["name"].as_str()).collect();
+    names.extend(old.iter().filter_map(|x| x["name"].as_str()));
+    let mut seen = Vec::new();
+    let mut added_fields: Vec<(String, String)> = Vec::new();
+    // the problems `--fix` doesn't address
+    let mut unfixable = 0;
+
+    for name in names {
+        if seen.contains(&name) {
+            continue;
+        }
+        seen.push(name);
+
+        match compat::compare(name, &old_version, &new_version) {
+            Some(report) => {
+                println!("{}: {}", report.name, report.compatibility());
+                for issue in &report.backward {
+                    println!("  backward: {}", issue);
+                    println!("    hint: {}", report.remediation(issue));
+
+                    match &issue.problem {
+                        compat::Problem::MissingDefault {
+                            added: true,
+                            record,
+                            ..
+                        } => {
+                            let field = issue.path.rsplit('.').next().unwrap_or_default();
+                            let target = (record.to_owned(), field.to_owned());
+                            if !added_fields.contains(&target) {
+                                added_fields.push(target);
+                            }
+                        }
+                        _ => unfixable += 1,
+                    }
+                }
+                for issue in &report.forward {
+                    println!("  forward: {}", issue);
+                    println!("    hint: {}", report.remediation(issue));
+                    unfixable += 1;
+                }
+            }
+            None if new.iter().any(|x| x["name"] == name) => println!("{}: ADDED", name),
+            None => println!("{}: REMOVED", name),
+        }
+    }
+
+    if added_fields.is_empty() {
+        if unfixable > 0 {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // new fields without a default become nullable, defaulting to null
+    println!();
+    println!(
+        "{}",
+        if fix {
+            "Applied fixes:"
+        } else {
+            "Proposed fixes (apply with --fix):"
+        }
+    );
+    let mut rewritten = HashSet::new();
+    for file_path in source_files(Path::new(paths[1])) {
+        let file_path = file_path.to_string_lossy();
+        let code = read_source(&file_path);
+        let grammar = Grammar::from_path(&file_path, parsed.jsx);
+        let (fixed, rewrites) = fix_file(&code, grammar, &file_path, &added_fields);
+        rewritten.extend(
+            rewrites
+                .iter()
+                .map(|rewrite| (rewrite.record.clone(), rewrite.field.clone())),
+        );
+
+        for rewrite in &rewrites {
+            println!(
+                "  {}: {}.{}: `{}` -> `{}`",
+                file_path, rewrite.record, rewrite.field, rewrite.before, rewrite.after
+            );
+        }
+        if fix && !rewrites.is_empty() {
+            if let Err(err) = fs::write(file_path.as_ref(), fixed) {
+                eprintln!("Failed to write {}: {}", file_path, err);
+                std::process::exit(1);
+            }
+        }
+    }
+    if !fix || unfixable > 0 || added_fields.iter().any(|x| !rewritten.contains(x)) {
+        std::process::exit(1);
+    }
+}
+
+fn completions(args: &[String]) {
+    let program = Path::new(&args[0])
+        .file_name()
+        .map_or("ts-to-avro".into(), |name| name.to_string_lossy());
+    let shell = args.get(2).map_or("", |shell| shell.as_str());
+    match completions::generate(shell, &program) {
+        Some(script) => print!("{}", script),
+        None => {
+            eprintln!("Usage: {} completions bash|zsh|fish|powershell", args[0]);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn list(args: &[String]) {
+    let path = match args.get(2) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: {} list <path>", args[0]);
+            std::process::exit(1);
+        }
+    };
+
+    let mut declarations = Vec::new();
+    for file_path in source_files(Path::new(path)) {
+        let file_path = file_path.to_string_lossy();
+        let code = read_source(&file_path);
+        let grammar = Grammar::from_path(&file_path, false);
+        declarations.extend(file_declarations(&code, grammar, &file_path));
+    }
+    print!("{}", list::render(&declarations));
+}
+
+fn graph(args: &[String]) {
+    let mut format = "dot";
+    let mut path = None;
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => format = iter.next().map_or("", |format| format.as_str()),
+            _ => path = Some(arg),
+        }
+    }
+    let path = match (path, format) {
+        (Some(path), "dot" | "json") => path,
+        _ => {
+            eprintln!("Usage: {} graph [--format dot|json] <path>", args[0]);
+            std::process::exit(1);
+        }
+    };
+
+    let mut declarations = Vec::new();
+    for file_path in imports::follow(source_files(Path::new(path)), false) {
+        let file_path = file_path.to_string_lossy();
+        let code = read_source(&file_path);
+        let grammar = Grammar::from_path(&file_path, false);
+        declarations.extend(file_declarations(&code, grammar, &file_path));
+    }
+    match format {
+        "json" => println!("{}", graph::json(&declarations)),
+        _ => print!("{}", graph::dot(&declarations)),
+    }
+}
+
+// every record with its schema and examples, for tests of the code serializing them
+// With `--wire-format <dir>` each example is also written there as `<Record>-<n>.bin`, framed
+// with the `--schema-id` given or the id registered under the record's subject.
+fn fixtures(args: &[String]) {
+    let mut count = "10";
+    let mut path = None;
+    let mut wire_format = None;
+    let mut schema_id = None;
+    let mut subject_map = None;
+    let mut options = registry::Options::default();
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--count" => count = iter.next().map_or("", |count| count.as_str()),
+            "--wire-format" => wire_format = iter.next(),
+            "--schema-id" => schema_id = Some(iter.next().and_then(|id| id.parse::<u32>().ok())),
+            "--subject-map" => subject_map = iter.next(),
+            _ if options.flag(arg, &mut iter) => {}
+            _ => path = Some(arg),
+        }
+    }
+    let (path, count) = match (path, count.parse::<usize>(), schema_id) {
+        (Some(path), Ok(count), None | Some(Some(_))) => (path, count),
+        _ => {
+            eprintln!(
+                "Usage: {} fixtures [--count <n>] [--wire-format <dir>] [--schema-id <id>] [--subject-map <file>] [registry options] <path>",
+                args[0]
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let schemas = load_schemas(path);
+    let result = fixtures::fixtures(&schemas, count).and_then(|fixtures| {
+        if let Some(dir) = wire_format {
+            write_messages(
+                dir,
+                &fixtures,
+                &schemas,
+                schema_id.flatten(),
+                subject_map,
+                &options,
+            )?;
+        }
+        Ok(fixtures)
+    });
+    match result {
+        Ok(fixtures) => {
+            let fixtures: Vec<Value> = fixtures.iter().map(|x| x.json()).collect();
+            println!("{}", json!(fixtures));
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// an Avro container file of generated records, to smoke-test jobs reading the schema
+// the conversion options apply as for the main command, so the records match its schema
+fn sample(args: &[String]) {
+    let usage = format!(
+        "Usage: {} sample [options] [--format avro] [-n <count>] -o <file> <path>",
+        args[0]
+    );
+    let mut count = "100";
+    let mut output = None;
+    let mut format = "avro";
+    let mut rest = Vec::new();
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-n" => count = iter.next().map_or("", |count| count.as_str()),
+            "-o" => output = iter.next(),
+            "--format" => format = iter.next().map_or("", |format| format.as_str()),
+            _ => rest.push(arg.to_owned()),
+        }
+    }
+    let (output, count) = match (output, count.parse::<usize>(), format) {
+        (Some(output), Ok(count), "avro") => (output, count),
+        _ => {
+            eprintln!("{}", usage);
+            std::process::exit(1);
+        }
+    };
+
+    let (_, _, schemas, _) = load_model(&rest, &usage);
+    let schema = match merger(schemas) {
+        Ok(schema) => schema,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let file = match container::sample(&schema, count) {
+        Ok(file) => file,
+        Err(problems) => {
+            for problem in problems {
+                eprintln!("error: {}", problem);
+            }
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = fs::write(output, file) {
+        eprintln!("Error writing {}: {}", output, err);
+        std::process::exit(1);
+    }
+    println!("{} records written to {}", count, output);
+}
+
+// Checks every declaration against the `[lint]` policy of the config, for CI: errors fail
+// the run, and so do warnings with `--fail-on-warnings`.
+fn lint(args: &[String]) {
+    let mut config_path = None;
+    let mut profile = None;
+    let mut json_diagnostics = Some(false);
+    let mut fail_on_warnings = false;
+    let mut path = None;
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--config" => config_path = iter.next(),
+            "--profile" => profile = iter.next(),
+            "--diagnostics" => {
+                json_diagnostics = match iter.next().map(|name| name.as_str()) {
+                    Some("human") => Some(false),
+                    Some("json") => Some(true),
+                    _ => None,
+                }
+            }
+            "--fail-on-warnings" => fail_on_warnings = true,
+            _ => path = Some(arg),
+        }
+    }
+    let (path, json_diagnostics) = match (path, json_diagnostics) {
+        (Some(path), Some(json_diagnostics)) => (path, json_diagnostics),
+        _ => {
+            eprintln!(
+                "Usage: {} lint [--config <file>] [--profile <name>] [--diagnostics human|json] [--fail-on-warnings] <path>",
+                args[0]
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let config = match config::load(
+        config_path.map(|path| path.as_str()),
+        profile.map(|name| name.as_str()),
+    ) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let rules = match lint::rules(&config["lint"]) {
+        Ok(rules) => rules,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    if rules.is_empty() {
+        eprintln!("No lint rules are set in the [lint] table of the config");
+        return;
+    }
+
+    let inputs: Vec<Input> = source_files(Path::new(path))
+        .into_iter()
+        .map(|path| {
+            let path = path.to_string_lossy().into_owned();
+            Input {
+                code: read_source(&path),
+                grammar: Grammar::from_path(&path, false),
+                namespace: input_namespace(&Args::default(), &config, &path),
+                path,
+            }
+        })
+        .collect();
+    let schemas = load_inputs(&inputs, &Mapping::default());
+    let sources: Vec<diagnostic::Source> = inputs.iter().map(Input::source).collect();
+    let documented = |record: &str, field: &str| {
+        sources
+            .iter()
+            .any(|source| source.documented(record, field))
+    };
+    let violations = lint::lint(&schemas, &rules, &documented);
+    let mut failed = false;
+    for (rule, diagnostic) in &violations {
+        let severity = rules.severity(rule);
+        failed |= severity == "error" || fail_on_warnings;
+        eprint!(
+            "{}",
+            render(&inputs, severity, diagnostic, json_diagnostics)
+        );
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn write_messages(
+    dir: &str,
+    fixtures: &[fixtures::Fixture],
+    schemas: &[Value],
+    schema_id: Option<u32>,
+    subject_map: Option<&String>,
+    options: &registry::Options,
+) -> Result<(), String> {
+    let ids: Vec<(String, u32)> = match schema_id {
+        Some(id) => fixtures.iter().map(|x| (x.name.clone(), id)).collect(),
+        None => {
+            let config = config::load(None, None)?;
+            let map = load_subject_map(subject_map, &config)?;
+            let registry = registry::Registry::new(options, &config)?;
+            let mut ids = Vec::new();
+            for (subject, record) in registry::subjects(schemas, &BTreeSet::new(), &map) {
+                let name = record["name"].as_str().unwrap_or_default().to_owned();
+                let latest = registry.latest(&subject)?.ok_or(format!(
+                    "{} is not registered, pass --schema-id <id>",
+                    subject
+                ))?;
+                ids.push((name, latest.id as u32));
+            }
+            ids
+        }
+    };
+    fs::create_dir_all(dir).map_err(|err| format!("Error creating {}: {}", dir, err))?;
+    for fixture in fixtures {
+        let id = match ids.iter().find(|(name, _)| *name == fixture.name) {
+            Some((_, id)) => *id,
+            None => continue,
+        };
+        for (i, message) in fixture.messages(id).iter().enumerate() {
+            let path = Path::new(dir).join(format!("{}-{}.bin", fixture.name, i + 1));
+            fs::write(&path, message)
+                .map_err(|err| format!("Error writing {}: {}", path.display(), err))?;
+        }
+    }
+    Ok(())
+}
+
+// `--subject-map <file>`, or the `[subjects]` table of the config file
+fn load_subject_map(path: Option<&String>, config: &Value) -> Result<Value, String> {
+    match path {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|err| format!("Error opening the subject map {}: {}", path, err))
+            .and_then(|contents| {
+                config::parse(&contents).map_err(|err| format!("{}: {}", path, err))
+            }),
+        None => Ok(config["subjects"].clone()),
+    }
+}
+
+// the declarations of every file given or imported, as the subcommands working on the whole
+// model need them, with the names private to their files
+fn load_model(args: &[String], usage: &str) -> (Args, Value, Vec<Value>, BTreeSet<String>) {
+    let (parsed, config) = load_options(args, usage);
+    let (schemas, private) = load_path(&parsed, &config, &parsed.file_path);
+    (parsed, config, schemas, private)
+}
+
+// the conversion options, with the config they load
+fn load_options(args: &[String], usage: &str) -> (Args, Value) {
+    let mut parsed = match parse_args(args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("{}\n{}", err, usage);
+            std::process::exit(1);
+        }
+    };
+    let config = match config::load(parsed.config.as_deref(), parsed.profile.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = configure_mapping(&mut parsed, &config) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+    (parsed, config)
+}
+
+fn load_path(parsed: &Args, config: &Value, path: &str) -> (Vec<Value>, BTreeSet<String>) {
+    let files = imports::follow(source_files(Path::new(path)), parsed.jsx);
+    let inputs: Vec<Input> = files
+        .iter()
+        .map(|path| {
+            let path = path.to_string_lossy().into_owned();
+            Input {
+                code: read_source(&path),
+                grammar: Grammar::from_path(&path, parsed.jsx),
+                namespace: input_namespace(parsed, config, &path),
+                path,
+            }
+        })
+        .collect();
+    let private = match parsed.include_private || config["include_private"] == true {
+        true => BTreeSet::new(),
+        false => private_declarations(&inputs),
+    };
+    let mut schemas = load_inputs(&inputs, &parsed.mapping);
+    let collapsed = union_strategy(parsed, config).and_then(|strategy| {
+        avro::collapse_unions(&mut schemas, strategy).map_err(|x| x.to_string())
+    });
+    if let Err(err) = collapsed {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+    (schemas, private)
+}
+
+// What `publish` and `drift` work on: the registry, the subject map, and the records with the
+// subjects they are registered under.
+struct Connection {
+    registry: registry::Registry,
+    config: Value,
+    map: Value,
+    schemas: Vec<Value>,
+    subjects: Vec<(String, Value)>,
+    switches: HashSet<String>,
+}
+
+// Takes only the root record unless `--all` is given, under `--subject` when that is. Returns
+// which of the subcommand's own `switches` were set, and `--all`.
+fn connect(args: &[String], usage: &str, switches: &[&str]) -> Connection {
+    let mut set = HashSet::new();
+    let mut subject = None;
+    let mut subject_map = None;
+    let mut options = registry::Options::default();
+    let mut rest = Vec::new();
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--subject" => subject = iter.next(),
+            "--subject-map" => subject_map = iter.next(),
+            flag if flag == "--all" || switches.contains(&flag) => {
+                set.insert(flag.to_owned());
+            }
+            _ if options.flag(arg, &mut iter) => {}
+            _ => rest.push(arg.to_owned()),
+        }
+    }
+    let (parsed, config, schemas, private) = load_model(&rest, usage);
+
+    let map = load_subject_map(subject_map, &config);
+    let registry = map
+        .and_then(|map| registry::Registry::new(&options, &config).map(|registry| (map, registry)));
+    let (map, registry) = match registry {
+        Ok(found) => found,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let mut subjects = registry::subjects(&schemas, &private, &map);
+    if !set.contains("--all") {
+        let root = parsed.root.as_deref().or(config["root"].as_str());
+        subjects = subjects
+            .into_iter()
+            .filter(|(_, record)| root.is_none_or(|root| record["name"] == root))
+            .take(1)
+            .collect();
+    }
+    if subjects.is_empty() {
+        eprintln!("No records to register under a subject");
+        std::process::exit(1);
+    }
+    if let Some(subject) = subject {
+        if set.contains("--all") {
+            eprintln!("--subject names the root record's subject, it can't go with --all");
+            std::process::exit(1);
+        }
+        subjects[0].0 = subject.to_owned();
+    }
+    Connection {
+        registry,
+        config,
+        map,
+        schemas,
+        subjects,
+        switches: set,
+    }
+}
+
+// Registers the root record, or every public record with `--all`, under the subject mapped
+// to it in `--subject-map` or the config's `[subjects]`; stops at the first subject that
+// fails unless `--keep-going` is given.
+fn publish(args: &[String]) {
+    let usage = format!(
+        "Usage: {} publish [--all] [--dry-run] [--use-references] [--verify-level] [--subject <name>] [--subject-map <file>] [--keep-going] [registry options] [options] <path>",
+        args[0]
+    );
+    let Connection {
+        registry,
+        config,
+        map,
+        schemas,
+        subjects,
+        switches,
+    } = connect(
+        args,
+        &usage,
+        &[
+            "--dry-run",
+            "--keep-going",
+            "--use-references",
+            "--verify-level",
+        ],
+    );
+    let keep_going = switches.contains("--keep-going");
+    let verify_level = switches.contains("--verify-level");
+
+    let symbols = Symbols::new(&schemas);
+    if switches.contains("--dry-run") {
+        return preview(&registry, &config, &subjects, &symbols);
+    }
+    let mut references = registry::References::new(&registry, &symbols, &map);
+    let (mut registered, mut failed) = (0, 0);
+    for (subject, record) in &subjects {
+        let name = record["name"].as_str().unwrap_or_default();
+        let resolved = match switches.contains("--use-references") {
+            true => resolve_root(record, &symbols)
+                .map_err(|err| err.to_string())
+                .and_then(|_| references.resolve(record)),
+            false => resolve_root(record, &symbols)
+                .map(|schema| (schema, Vec::new()))
+                .map_err(|err| err.to_string()),
+        };
+        let result = resolved.and_then(|(schema, references)| {
+            check_level(&registry, &config, subject, record, verify_level)?;
+            match registry.is_compatible(subject, &schema, &references)? {
+                true => registry.register(subject, &schema, &references),
+                false => Err(format!("{} is incompatible with the latest version", name)),
+            }
+        });
+        match result {
+            Ok(id) => {
+                println!("{}: registered {} (id {})", subject, name, id);
+                registered += 1;
+            }
+            Err(err) => {
+                println!("{}: {}", subject, err);
+                failed += 1;
+                if !keep_going {
+                    break;
+                }
+            }
+        }
+    }
+    println!("{} of {} subjects registered", registered, subjects.len());
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+// the compatibility level `[compatibility]` wants for a subject, when it isn't at it yet
+fn level_change<'a>(
+    registry: &registry::Registry,
+    config: &'a Value,
+    subject: &str,
+    record: &Value,
+) -> Result<Option<(String, &'a str)>, String> {
+    let level = match registry::level(config, subject, record)? {
+        Some(level) => level,
+        None => return Ok(None),
+    };
+    match registry.compatibility(subject)? {
+        Some(current) if current == level => Ok(None),
+        current => Ok(Some((current.unwrap_or("unset".to_owned()), level))),
+    }
+}
+
+// Brings the subject to the level it should be at before its versions are checked at it, or
+// with `verify` fails when it isn't there.
+fn check_level(
+    registry: &registry::Registry,
+    config: &Value,
+    subject: &str,
+    record: &Value,
+    verify: bool,
+) -> Result<(), String> {
+    match level_change(registry, config, subject, record)? {
+        None => Ok(()),
+        Some((current, level)) if verify => {
+            Err(format!("compatibility is {} instead of {}", current, level))
+        }
+        Some((_, level)) => {
+            registry.set_compatibility(subject, level)?;
+            println!("{}: compatibility set to {}", subject, level);
+            Ok(())
+        }
+    }
+}
+
+// What publishing would change, as a diff of each subject's latest version against the
+// generated schema. Nothing is registered.
+fn preview(
+    registry: &registry::Registry,
+    config: &Value,
+    subjects: &[(String, Value)],
+    symbols: &Symbols,
+) {
+    let mut failed = false;
+    for (subject, record) in subjects {
+        let name = record["name"].as_str().unwrap_or_default();
+        let result = level_change(registry, config, subject, record)
+            .map(|change| {
+                if let Some((current, level)) = change {
+                    println!(
+                        "{}: would set compatibility from {} to {}",
+                        subject, current, level
+                    );
+                }
+            })
+            .and_then(|_| resolve_root(record, symbols).map_err(|err| err.to_string()))
+            .map_err(|err| err.to_string())
+            .and_then(|schema| Ok((registry.latest(subject)?, schema)));
+        match result {
+            Ok((None, _)) => println!("{}: new subject, would register {}", subject, name),
+            Ok((Some(latest), schema)) => match diff::diff(&latest.schema, &schema) {
+                lines if lines.is_empty() => {
+                    println!(
+                        "{}: {} is unchanged from version {}",
+                        subject, name, latest.version
+                    )
+                }
+                lines => {
+                    println!(
+                        "{}: {} changes from version {}",
+                        subject, name, latest.version
+                    );
+                    for line in lines {
+                        println!("  {}", line);
+                    }
+                }
+            },
+            Err(err) => {
+                println!("{}: {}", subject, err);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+// Runs each job of a manifest the way the conversion and `publish` would be run for it, as a
+// process of its own so that a failing job doesn't stop the others, then reports every job.
+fn batch(args: &[String]) {
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    let path = match args[2..].iter().find(|arg| *arg != "--dry-run") {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: {} batch [--dry-run] <manifest>", args[0]);
+            std::process::exit(1);
+        }
+    };
+    let jobs = fs::read_to_string(path)
+        .map_err(|err| format!("Error opening the manifest {}: {}", path, err))
+        .and_then(|contents| config::parse(&contents).map_err(|err| format!("{}: {}", path, err)))
+        .and_then(|manifest| {
+            let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+            manifest::jobs(&manifest, dir)
+        });
+    let (jobs, program) = match jobs.and_then(|jobs| {
+        let program = env::current_exe().map_err(|err| err.to_string())?;
+        Ok((jobs, program))
+    }) {
+        Ok(found) => found,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let run = |args: &[String]| -> Result<String, String> {
+        let output = Command::new(&program)
+            .args(args)
+            .output()
+            .map_err(|err| format!("Failed to run {}: {}", program.display(), err))?;
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        match output.status.success() {
+            true => Ok(stdout),
+            false => Err(format!(
+                "{}{}",
+                stdout,
+                String::from_utf8_lossy(&output.stderr)
+            )),
+        }
+    };
+    let mut succeeded = 0;
+    for job in &jobs {
+        let mut steps = Vec::new();
+        let result = run(&job.convert).and_then(|schema| {
+            match &job.output {
+                // a dry run converts and checks, but leaves the outputs as they are
+                Some(output) if dry_run => steps.push(format!("would write {}", output)),
+                Some(output) => {
+                    if let Some(dir) = Path::new(output).parent() {
+                        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                    }
+                    fs::write(output, schema)
+                        .map_err(|err| format!("Error writing {}: {}", output, err))?;
+                    steps.push(format!("wrote {}", output));
+                }
+                None => steps.push("converted".to_owned()),
+            }
+            if let Some(publish) = &job.publish {
+                let mut args = vec!["publish".to_owned()];
+                if dry_run {
+                    args.push("--dry-run".to_owned());
+                }
+                args.extend(publish.iter().cloned());
+                let published = run(&args)?;
+                steps.extend(
+                    published
+                        .lines()
+                        .filter(|line| !line.ends_with("subjects registered"))
+                        .map(str::to_owned),
+                );
+            }
+            Ok(())
+        });
+        match result {
+            Ok(()) => {
+                println!("{}: ok", job.name);
+                succeeded += 1;
+            }
+            Err(err) => {
+                println!("{}: failed", job.name);
+                steps.extend(err.lines().map(str::to_owned));
+            }
+        }
+        for step in steps.iter().filter(|step| !step.trim().is_empty()) {
+            println!("  {}", step);
+        }
+    }
+    println!("{} of {} jobs succeeded", succeeded, jobs.len());
+    if succeeded < jobs.len() {
+        std::process::exit(1);
+    }
+}
+
+// Downloads the latest schema of every subject, or of those matching a `--filter` pattern,
+// into `--out-dir` as `.avsc` files laid out by namespace.
+fn pull(args: &[String]) {
+    let usage = format!(
+        "Usage: {} pull [--out-dir <dir>] [--filter <pattern>]... [--config <file>] [--profile <name>] [registry options]",
+        args[0]
+    );
+    let mut out_dir = "schemas";
+    let mut filters = Vec::new();
+    let mut config_path = None;
+    let mut profile = None;
+    let mut options = registry::Options::default();
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        let expected = match arg.as_str() {
+            "--out-dir" => Some("a directory"),
+            "--filter" => Some("a pattern"),
+            "--config" => Some("a file path"),
+            "--profile" => Some("a name"),
+            _ => None,
+        };
+        if let Some(expected) = expected {
+            let value = match iter.next() {
+                Some(value) if !value.is_empty() => value,
+                _ => {
+                    eprintln!("{} expects {}\n{}", arg, expected, usage);
+                    std::process::exit(1);
+                }
+            };
+            match arg.as_str() {
+                "--out-dir" => out_dir = value,
+                "--filter" => filters.push(value),
+                "--config" => config_path = Some(value),
+                _ => profile = Some(value),
+            }
+            continue;
+        }
+        if !options.flag(arg, &mut iter) {
+            eprintln!("Unexpected argument: {}\n{}", arg, usage);
+            std::process::exit(1);
+        }
+    }
+
+    let registry = config::load(
+        config_path.map(|path| path.as_str()),
+        profile.map(|name| name.as_str()),
+    )
+    .and_then(|config| registry::Registry::new(&options, &config));
+    let subjects = registry.and_then(|registry| Ok((registry.subjects()?, registry)));
+    let (subjects, registry) = match subjects {
+        Ok(found) => found,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let subjects: Vec<String> = subjects
+        .into_iter()
+        .filter(|subject| {
+            filters.is_empty()
+                || filters
+                    .iter()
+                    .any(|pattern| mapping::glob(pattern, subject))
+        })
+        .collect();
+
+    let mut pulled = 0;
+    for subject in &subjects {
+        let result = registry.latest(subject).and_then(|latest| {
+            let latest = latest.ok_or("no versions".to_owned())?;
+            let path = Path::new(out_dir).join(registry::avsc_path(subject, &latest.schema));
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .map_err(|err| format!("Error creating {}: {}", dir.display(), err))?;
+            }
+            fs::write(&path, format!("{:#}\n", latest.schema))
+                .map_err(|err| format!("Error writing {}: {}", path.display(), err))?;
+            Ok((latest.version, path))
+        });
+        match result {
+            Ok((version, path)) => {
+                println!(
+                    "{}: version {} written to {}",
+                    subject,
+                    version,
+                    path.display()
+                );
+                pulled += 1;
+            }
+            Err(err) => println!("{}: {}", subject, err),
+        }
+    }
+    println!(
+        "{} of {} subjects pulled into {}",
+        pulled,
+        subjects.len(),
+        out_dir
+    );
+    if pulled < subjects.len() {
+        std::process::exit(1);
+    }
+}
+
+// Both directions of drift between the source and the registry: what is generated but not
+// published yet, and what is registered but no longer in the source. With `--all`, subjects
+// the subject map names for records that are gone are reported too.
+fn drift(args: &[String]) {
+    let usage = format!(
+        "Usage: {} drift [--all] [--subject <name>] [--subject-map <file>] [registry options] [options] <path>",
+        args[0]
+    );
+    let connection = connect(args, &usage, &[]);
+    let symbols = Symbols::new(&connection.schemas);
+    let (mut drifted, mut failed) = (0, false);
+    for (subject, record) in &connection.subjects {
+        let name = record["name"].as_str().unwrap_or_default();
+        let result = resolve_root(record, &symbols)
+            .map_err(|err| err.to_string())
+            .and_then(|schema| Ok((connection.registry.latest(subject)?, schema)));
+        match result {
+            Ok((None, _)) => {
+                println!("{}: {} is not published", subject, name);
+                drifted += 1;
+            }
+            Ok((Some(latest), schema)) => match diff::diff(&latest.schema, &schema) {
+                changes if changes.is_empty() => {
+                    println!("{}: {} matches version {}", subject, name, latest.version)
+                }
+                changes => {
+                    println!(
+                        "{}: {} has drifted from version {}",
+                        subject, name, latest.version
+                    );
+                    for line in diff::drift(&changes) {
+                        println!("  {}", line);
+                    }
+                    drifted += 1;
+                }
+            },
+            Err(err) => {
+                println!("{}: {}", subject, err);
+                failed = true;
+            }
+        }
+    }
+    if connection.switches.contains("--all") {
+        let mapped = connection
+            .map
+            .as_object()
+            .into_iter()
+            .flat_map(|map| map.values());
+        for subject in mapped.filter_map(Value::as_str) {
+            if connection.subjects.iter().any(|(x, _)| x == subject) {
+                continue;
+            }
+            match connection.registry.latest(subject) {
+                Ok(Some(latest)) => {
+                    println!(
+                        "{}: version {} is registered but not in the source",
+                        subject, latest.version
+                    );
+                    drifted += 1;
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    println!("{}: {}", subject, err);
+                    failed = true;
+                }
+            }
+        }
+    }
+    println!(
+        "{} of {} subjects drifted",
+        drifted,
+        connection.subjects.len()
+    );
+    if drifted > 0 || failed {
+        std::process::exit(1);
+    }
+}
+
+// `[snapshot] dir` sets where the snapshots live, `__schemas__` by default
+fn snapshot(args: &[String]) {
+    let usage = format!("Usage: {} snapshot update|check [options] <path>", args[0]);
+    let update = match args.get(2).map(|mode| mode.as_str()) {
+        Some("update") => true,
+        Some("check") => false,
+        _ => {
+            eprintln!("{}", usage);
+            std::process::exit(1);
+        }
+    };
+    let (_, config, schemas, private) = load_model(&args[3..], &usage);
+
+    let dir = Path::new(
+        config["snapshot"]["dir"]
+            .as_str()
+            .unwrap_or(snapshot::DEFAULT_DIR),
+    );
+    let outcome = match update {
+        true => snapshot::update(dir, &schemas, &private),
+        false => snapshot::check(dir, &schemas, &private),
+    };
+    let outcome = match outcome {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    if update {
+        for change in &outcome.differences {
+            println!("updated: {}", change);
+        }
+        println!(
+            "{} snapshots in {}, {} updated",
+            outcome.snapshots,
+            dir.display(),
+            outcome.differences.len()
+        );
+    } else {
+        for difference in &outcome.differences {
+            println!("{}", difference);
+        }
+        if !outcome.differences.is_empty() {
+            eprintln!(
+                "{} snapshots out of date, run `{} snapshot update` to accept the changes",
+                outcome.differences.len(),
+                args[0]
+            );
+            std::process::exit(1);
+        }
+        println!("{} snapshots up to date", outcome.snapshots);
+    }
+}
+
+fn explain(args: &[String]) {
+    let mut target = None;
+    let mut rest = Vec::new();
+    let mut iter = args.iter().skip(2);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--field" => target = iter.next(),
+            _ => rest.push(arg.to_owned()),
+        }
+    }
+    let (mut parsed, target) = match (parse_args(&rest), target) {
+        (Ok(parsed), Some(target)) => (parsed, target),
+        (Err(err), _) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        (_, None) => {
+            eprintln!(
+                "Usage: {} explain [options] <file_path> --field <Record.field>",
+                args[0]
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let config = match config::load(parsed.config.as_deref(), parsed.profile.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let strategy =
+        configure_mapping(&mut parsed, &config).and_then(|_| union_strategy(&parsed, &config));
+    let explained = strategy.and_then(|strategy| {
+        let code = read_source(&parsed.file_path);
+        let source = diagnostic::Source {
+            path: &parsed.file_path,
+            code: &code,
+            grammar: Grammar::from_path(&parsed.file_path, parsed.jsx),
+        };
+        explain_field(&source, parsed.mapping, strategy, target)
+    });
+    match explained {
+        Ok(explanation) => print!("{}", explanation),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// lists every rule that changed or confirmed the field's type, in the order they ran
+fn explain_field(
+    source: &diagnostic::Source,
+    mut mapping: Mapping,
+    strategy: UnionStrategy,
+    target: &str,
+) -> Result<String, String> {
+    let (record, field) = target
+        .split_once('.')
+        .ok_or(format!("--field expects <Record.field>, got {}", target))?;
+
+    mapping.trace = Some(RefCell::new(Vec::new()));
+    let path = Path::new(source.path);
+    let mut schemas = get_file_schema(source.code, path, source.grammar, &mapping)
+        .map_err(|err| format!("Error converting {}: {}", source.path, err))?;
+    let steps: Vec<mapping::Step> = mapping
+        .trace
+        .take()
+        .unwrap_or_default()
+        .into_inner()
+        .into_iter()
+        .filter(|step| step.record == record && step.field == field)
+        .collect();
+    if steps.is_empty() {
+        return Err(format!("No field {} found in {}", target, source.path));
+    }
+
+    let declared = source
+        .field_span(record, field)
+        .map(|span| source.code[span].to_owned())
+        .unwrap_or("(JSDoc typedef)".to_owned());
+    let mut out = format!("{}: {}\n", target, declared);
+    for step in steps {
+        out.push_str(&format!("  {}: {}\n", step.rule, json!(step.ptype)));
+    }
+
+    let unions = match avro::collapse_unions(&mut schemas, strategy) {
+        Ok(warnings) => warnings,
+        Err(err) => vec![err],
+    };
+    for diagnostic in unions {
+        if diagnostic.record == record && diagnostic.field == field {
+            out.push_str(&format!("  union strategy: {}\n", diagnostic.message));
+        }
+    }
+
+    let result = schemas
+        .iter()
+        .filter(|x| x["name"] == record)
+        .flat_map(|x| x["fields"].as_array().into_iter().flatten())
+        .find(|x| x["name"] == field)
+        .map(|x| x["type"].clone())
+        .unwrap_or_default();
+    out.push_str(&format!("  result: {}\n", json!(result)));
+    Ok(out)
+}
+
+fn read_source(file_path: &str) -> String {
+    match File::open(file_path) {
+        Ok(mut file) => {
+            let mut contents = String::new();
+            if let Err(err) = file.read_to_string(&mut contents) {
+                eprintln!("Error reading {}: {}", file_path, err);
+                std::process::exit(1);
+            }
+            contents
+        }
+        Err(err) => {
+            eprintln!("Error opening {}: {}", file_path, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// An anonymous `export default interface { ... }` takes its name from the file, so that
+// `shipping-label.ts` declares `ShippingLabel`, or from the directory of an `index` file. The
+// grammar only knows named interfaces, so the parser reads a copy of the source where the
+// interface is named by a placeholder that keeps every offset in place, and the placeholder
+// is replaced by the name in what's found. The source itself is left as written.
+struct Anonymous {
+    parsable: String,
+    name: String,
+    // `default interface` as written, and as it reads in the copy
+    header: String,
+    masked: String,
+}
+
+const PLACEHOLDER: &str = "$$$$$$$";
+
+fn anonymous_default(code: &str, grammar: Grammar, path: &Path) -> Option<Anonymous> {
+    if !code.contains("default") || code.contains(PLACEHOLDER) {
+        return None;
+    }
+    let mut parser = Parser::new();
+    parser.set_language(grammar.language()).ok()?;
+    let parsed = parser.parse(code, None)?;
+    let root = parsed.root_node();
+    let mut cursor = root.walk();
+    let (start, gaps) = root
+        .children(&mut cursor)
+        .filter(|node| node.kind() == "export_statement")
+        .find_map(|node| {
+            let rest = code[node.start_byte()..].strip_prefix("export")?;
+            let start = code.len() - rest.trim_start().len();
+            let after_default = rest.trim_start().strip_prefix("default")?;
+            let after_interface = after_default.trim_start().strip_prefix("interface")?;
+            let first = &after_default[..after_default.len() - after_default.trim_start().len()];
+            let second =
+                &after_interface[..after_interface.len() - after_interface.trim_start().len()];
+            match after_interface.trim_start().starts_with('{') && !first.is_empty() {
+                true => Some((start, (first, second))),
+                false => None,
+            }
+        })?;
+
+    // `default interface` becomes `interface $$$$$$$`, each keeping its whitespace, so that
+    // the placeholder takes the seven bytes of `default` and nothing after it moves
+    let header = format!("default{}interface{}", gaps.0, gaps.1);
+    let masked = format!("interface{}{}{}", gaps.0, PLACEHOLDER, gaps.1);
+    let mut parsable = code.to_owned();
+    parsable.replace_range(start..start + header.len(), &masked);
+    Some(Anonymous {
+        parsable,
+        name: default_name(path),
+        header,
+        masked,
+    })
+}
+
+fn default_name(path: &Path) -> String {
+    let stem = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('.').next())
+        .unwrap_or_default();
+    let stem = match stem {
+        "index" => path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .and_then(|name| name.to_str())
+            .unwrap_or(stem),
+        stem => stem,
+    };
+    stem.split(|c: char| !c.is_ascii_alphanumeric())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect()
+}
+
+impl Anonymous {
+    fn rename(&self, value: &mut Value) {
+        match value {
+            Value::String(text) if text.contains(PLACEHOLDER) => {
+                *text = text.replace(PLACEHOLDER, &self.name);
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.rename(item)),
+            Value::Object(object) => object.values_mut().for_each(|item| self.rename(item)),
+            _ => {}
+        }
+    }
+}
+
+// the declarations of a file read from `path`, which names an anonymous default export
+fn get_file_schema(
+    code: &str,
+    path: &Path,
+    grammar: Grammar,
+    mapping: &Mapping,
+) -> Result<Vec<Value>, ConversionError> {
+    match anonymous_default(code, grammar, path) {
+        Some(anonymous) => {
+            let mut schemas = get_schema_with(&anonymous.parsable, grammar, mapping)?;
+            schemas
+                .iter_mut()
+                .for_each(|schema| anonymous.rename(schema));
+            Ok(schemas)
+        }
+        None => get_schema_with(code, grammar, mapping),
+    }
+}
+
+fn file_declarations(code: &str, grammar: Grammar, file: &str) -> Vec<list::Declaration> {
+    let anonymous = match anonymous_default(code, grammar, Path::new(file)) {
+        Some(anonymous) => anonymous,
+        None => return list::declarations(code, grammar, file),
+    };
+    let mut declarations = list::declarations(&anonymous.parsable, grammar, file);
+    for declaration in &mut declarations {
+        declaration.name = declaration.name.replace(PLACEHOLDER, &anonymous.name);
+    }
+    declarations
+}
+
+// `evolve --fix` edits the source as written; only the copy the fields are found in names
+// an anonymous default export
+fn fix_file(
+    code: &str,
+    grammar: Grammar,
+    file: &str,
+    targets: &[(String, String)],
+) -> (String, Vec<fix::Rewrite>) {
+    let anonymous = match anonymous_default(code, grammar, Path::new(file)) {
+        Some(anonymous) => anonymous,
+        None => return fix::make_nullable(code, grammar, targets),
+    };
+    let targets: Vec<(String, String)> = targets
+        .iter()
+        .map(|(record, field)| match *record == anonymous.name {
+            true => (PLACEHOLDER.to_owned(), field.clone()),
+            false => (record.clone(), field.clone()),
+        })
+        .collect();
+    let (fixed, mut rewrites) = fix::make_nullable(&anonymous.parsable, grammar, &targets);
+    for rewrite in &mut rewrites {
+        rewrite.record = rewrite.record.replace(PLACEHOLDER, &anonymous.name);
+    }
+    let fixed = fixed.replacen(&anonymous.masked, &anonymous.header, 1);
+    (fixed, rewrites)
+}
+
+// every declaration found in a file, or in all model files below a directory
+fn load_schemas(path: &str) -> Vec<Value> {
+    let mut schemas = Vec::new();
+    for file_path in source_files(Path::new(path)) {
+        let file_path = file_path.to_string_lossy();
+        let code = read_source(&file_path);
+        let grammar = Grammar::from_path(&file_path, false);
+        match get_file_schema(
+            &code,
+            Path::new(file_path.as_ref()),
+            grammar,
+            &Mapping::default(),
+        ) {
+            Ok(found) => schemas.extend(found),
+            Err(err) => {
+                eprintln!("Error converting {}: {}", file_path, err);
+                std::process::exit(1);
+            }
+        }
+    }
+    schemas
+}
+
+fn source_files(path: &Path) -> Vec<PathBuf> {
+    if !path.is_dir() {
+        return vec![path.to_path_buf()];
+    }
+
+    let mut entries: Vec<PathBuf> = match fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect(),
+        Err(err) => {
+            eprintln!("Error reading the directory {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+    };
+    entries.sort();
+
+    let extensions = ["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "flow"];
+    let mut files = Vec::new();
+    for entry in entries {
+        if entry.is_dir() {
+            if entry.file_name().is_some_and(|name| name != "node_modules") {
+                files.extend(source_files(&entry));
+            }
+        } else if entry
+            .extension()
+            .is_some_and(|extension| extensions.iter().any(|x| *x == extension))
+        {
+            files.push(entry);
+        }
+    }
+    files
+}
+
+// Resolves the first schema into a self-contained one in two passes: the declarations are
+// indexed by full name once, then references are replaced by their definition where a name
+// is first used. Avro allows a name to be defined only once, so later uses, including
+// recursive ones, stay references.
+fn merger(schemas: Vec<Value>) -> Result<Value, ConversionError> {
+    let root = schemas.first().ok_or(ConversionError::NoDeclarations)?;
+    resolve_root(root, &Symbols::new(&schemas))
+}
+
+// resolves one record against declarations indexed once, for callers resolving many
+fn resolve_root(root: &Value, symbols: &Symbols) -> Result<Value, ConversionError> {
+    if !root["fields"].is_array() {
+        return Err(ConversionError::NotARecord(
+            root["name"].as_str().unwrap_or_default().to_owned(),
+        ));
+    }
+    Ok(resolve(root, symbols, "", &mut HashSet::new()))
+}
+
+fn resolve(
+    ptype: &Value,
+    symbols: &Symbols,
+    namespace: &str,
+    defined: &mut HashSet<String>,
+) -> Value {
+    match ptype {
+        Value::String(name) => match symbols.lookup(name, namespace) {
+            Some((full, declaration)) if !defined.contains(full) => {
+                resolve(declaration, symbols, namespace, defined)
+            }
+            // a reference into another namespace has to be qualified
+            Some((full, declaration)) if declaration["namespace"].is_string() => {
+                match declaration["namespace"] == namespace {
+                    true => ptype.clone(),
+                    false => json!(full),
+                }
+            }
+            // a TypeScript qualified `Geo.Point` stays a reference by the declared name
+            Some((full, _)) => json!(full),
+            None => ptype.clone(),
+        },
+        Value::Array(members) => members
+            .iter()
+            .map(|member| resolve(member, symbols, namespace, defined))
+            .collect(),
+        Value::Object(object) => {
+            let mut resolved = object.clone();
+            if object.contains_key("name") {
+                defined.insert(symbols::full_name(ptype));
+            }
+            let namespace = object
+                .get("namespace")
+                .and_then(|namespace| namespace.as_str())
+                .unwrap_or(namespace);
+            if let Some(Value::Array(fields)) = resolved.get_mut("fields") {
+                for field in fields {
+                    field["type"] = resolve(&field["type"], symbols, namespace, defined);
+                }
+            }
+            for key in ["items", "values"] {
+                if let Some(inner) = resolved.get_mut(key) {
+                    *inner = resolve(inner, symbols, namespace, defined);
+                }
+            }
+            Value::Object(resolved)
+        }
+        _ => ptype.clone(),
+    }
+}
+
+#[cfg(test)]
+fn get_schema(code: &str, grammar: Grammar) -> Result<Vec<Value>, ConversionError> {
+    get_schema_with(code, grammar, &Mapping::default())
+}
+
+fn get_schema_with(
+    code: &str,
+    grammar: Grammar,
+    mapping: &Mapping,
+) -> Result<Vec<Value>, ConversionError> {
+    let mut vec_map = Vec::new();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(grammar.language())
+        .map_err(|err| ConversionError::Grammar(err.to_string()))?;
+    let parsed = parser.parse(code, None).ok_or(ConversionError::Parse)?;
+    let root = parsed.root_node();
+
+    let mut export_assignment = None;
+    collect_declarations(&root, code, mapping, &mut vec_map, &mut export_assignment)?;
+    let mut vec_map = merge_declarations(vec_map);
+    avro::hoist_anonymous(&mut vec_map);
+    let renamed = imports::renamed(&root, code);
+    if !renamed.is_empty() {
+        vec_map
+            .iter_mut()
+            .for_each(|schema| imports::unalias(schema, &renamed));
+    }
+
+    // `export = Name` makes that declaration the module's entry point
+    if let Some(name) = export_assignment {
+        if let Some(i) = vec_map.iter().position(|x| x["name"] == name) {
+            let exported = vec_map.remove(i);
+            vec_map.insert(0, exported);
+        }
+    }
+
+    Ok(vec_map)
+}
+
+// Interfaces declared more than once in a scope are one type with the members of all of
+// them, as in TypeScript. A member declared again keeps its first declaration.
+fn merge_declarations(schemas: Vec<Value>) -> Vec<Value> {
+    let mut merged: Vec<Value> = Vec::new();
+    for schema in schemas {
+        let existing = merged
+            .iter_mut()
+            .find(|x| x["type"] == "record" && x["name"] == schema["name"]);
+        let existing = match existing {
+            Some(existing) if schema["type"] == "record" => existing,
+            _ => {
+                merged.push(schema);
+                continue;
+            }
+        };
+        if let Value::Object(attributes) = schema {
+            for (key, value) in attributes {
+                let fields = match key.as_str() {
+                    "fields" => existing["fields"].as_array_mut(),
+                    _ => None,
+                };
+                if let Some(fields) = fields {
+                    for field in value.as_array().into_iter().flatten() {
+                        if !fields.iter().any(|x| x["name"] == field["name"]) {
+                            fields.push(field.clone());
+                        }
+                    }
+                } else if existing.get(&key).is_none() {
+                    existing[key] = value;
+                }
+            }
+        }
+    }
+    merged
+}
+
+fn collect_declarations(
+    parent: &tree_sitter::Node,
+    code: &str,
+    mapping: &Mapping,
+    vec_map: &mut Vec<Value>,
+    export_assignment: &mut Option<String>,
+) -> Result<(), ConversionError> {
+    let mut root_iter = parent.walk();
+    let mut leading = None;
+    for node in parent.children(&mut root_iter) {
+        if node.kind() == "comment" {
+            let comment = text(&node, code)?;
+            let typedefs = jsdoc::parse_typedefs(comment);
+            // a typedef comment documents its own records, not the declaration after it
+            leading = match typedefs.is_empty() {
+                true => Some((comment, node.end_position().row)),
+                false => None,
+            };
+            for mut typedef in typedefs {
+                let record = typedef["name"].as_str().unwrap_or_default().to_owned();
+                for field in typedef["fields"].as_array_mut().into_iter().flatten() {
+                    mapping.apply(&record, field, &[]);
+                }
+                vec_map.push(typedef);
+            }
+            continue;
+        }
+        // only a comment directly above the declaration documents it
+        let comment = leading
+            .take()
+            .filter(|(_, row)| node.start_position().row <= row + 1)
+            .map(|(comment, _)| comment);
+        let doc = comment.map(jsdoc::description);
+        let record_annotations = comment.map(jsdoc::annotations).unwrap_or_default();
+        // `@avro.namespace` wins over the configured and path-derived namespaces
+        let namespace = record_annotations
+            .iter()
+            .find(|(tag, _)| tag == "avro.namespace")
+            .map(|(_, namespace)| json!(namespace));
+
+        let node = match node.kind() {
+            "export_statement" => match node.child_by_field_name("declaration") {
+                Some(declaration) => declaration,
+                None => {
+                    if let (Some("="), Some(name)) =
+                        (node.child(1).map(|c| c.kind()), node.named_child(0))
+                    {
+                        *export_assignment = Some(text(&name, code)?.to_owned());
+                    }
+                    continue;
+                }
+            },
+            _ => node,
+        };
+
+        let node = match node.kind() {
+            "ambient_declaration" | "expression_statement" => match node.named_child(0) {
+                Some(declaration) => declaration,
+                None => continue,
+            },
+            _ => node,
+        };
+
+        if node.kind() == "enum_declaration" || node.kind() == "lexical_declaration" {
+            let enums = match node.kind() {
+                "enum_declaration" => enum_schema(&node, code, doc)?.into_iter().collect(),
+                _ => const_enums(&node, code, doc)?,
+            };
+            vec_map.extend(enums.into_iter().map(|mut schema| {
+                if let Some(namespace) = &namespace {
+                    schema["namespace"] = namespace.clone();
+                }
+                schema
+            }));
+        } else if node.kind() == "module" || node.kind() == "internal_module" {
+            if let Some(body) = node.child_by_field_name("body") {
+                collect_declarations(&body, code, mapping, vec_map, export_assignment)?;
+            }
+        } else if node.kind() == "interface_declaration"
+            || is_object_alias(&node)
+            || (mapping.classes && is_class(&node))
+        {
+            let mut map = Map::new();
+            map.insert("type".to_owned(), Value::String("record".to_owned()));
+            let mut fields = Vec::new();
+            let mut key_fields = Vec::new();
+            let mut defaults = Vec::new();
+            let mut interface = node.walk();
+
+            for node in node.children(&mut interface) {
+                let iname = text(&node, code)?;
+
+                match node.kind() {
+                    "type_identifier" => {
+                        map.insert("name".to_owned(), Value::String(iname.to_owned()));
+                    }
+                    "type_parameters" => defaults = type_defaults(&node, code)?,
+                    "object_type" | "generic_type" | "class_body" => {
+                        let node = match node.kind() {
+                            "class_body" => node,
+                            _ => match unwrap_flow_object(&node) {
+                                Some(object) => object,
+                                None => continue,
+                            },
+                        };
+                        let mut oter = node.walk();
+                        let mut annotations = Vec::new();
+                        let mut decorators = Vec::new();
+                        for node in node.children(&mut oter) {
+                            let member = text(&node, code)?;
+                            if node.kind() == "comment" && member.starts_with("/**") {
+                                annotations = jsdoc::annotations(member);
+                                continue;
+                            }
+                            if node.kind() == "decorator" {
+                                decorators.extend(decorators::parse(&node, code));
+                                continue;
+                            }
+                            let decorators = std::mem::take(&mut decorators);
+
+                            let record = map
+                                .get("name")
+                                .and_then(|name| name.as_str())
+                                .unwrap_or_default();
+                            let getter = match (mapping.methods, accessor(&node)) {
+                                (_, Some("get")) if mapping.accessors => getter(&node, code),
+                                (Methods::Getters, _) => getter(&node, code),
+                                _ => None,
+                            };
+                            // the setter of an accessor read as a field writes that field
+                            if mapping.accessors && accessor(&node) == Some("set") {
+                                annotations.clear();
+                                continue;
+                            }
+                            let stripped = |value: &Value| {
+                                mapping.is_stripped(value["name"].as_str().unwrap_or_default())
+                            };
+                            if let Some(mut value) = getter.filter(|x| !stripped(x)) {
+                                // a getter for a declared property adds nothing to it, in
+                                // either order
+                                mapping.apply(record, &mut value, &annotations);
+                                if !fields.iter().any(|x: &Value| x["name"] == value["name"]) {
+                                    fields.push(value);
+                                }
+                                annotations.clear();
+                                continue;
+                            }
+                            if is_constructor(&node, code) {
+                                annotations.clear();
+                                continue;
+                            }
+                            if let Some(kind) = dropped_member(&node) {
+                                mapping.dropped.borrow_mut().push(diagnostic::Diagnostic {
+                                    record: record.to_owned(),
+                                    field: member_name(&node, code).unwrap_or_default(),
+                                    message: format!("dropped {}, it has no Avro equivalent", kind),
+                                });
+                                annotations.clear();
+                                continue;
+                            }
+
+                            let mut prop = match node.kind() {
+                                "public_field_definition" => class_field(&node, code)?,
+                                _ => get_prop_type(&node, code)?,
+                            };
+                            if let Some(value) = prop.as_mut() {
+                                mapping.sanitize(record, value);
+                            }
+
+                            if let Some(mut value) = prop.filter(|x| !stripped(x)) {
+                                let decorated = decorators::apply(&mut value, &decorators);
+                                annotations.extend(decorated);
+                                mapping.apply(record, &mut value, &annotations);
+                                if annotations.iter().any(|(tag, _)| tag == "avro.key") {
+                                    key_fields.push(value.clone());
+                                }
+                                fields.retain(|x| x["name"] != value["name"]);
+                                fields.push(value);
+                            }
+                            if node.is_named() {
+                                annotations.clear();
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            // a generic record is used as written bare, with the defaults of its parameters
+            if !defaults.is_empty() {
+                for field in fields.iter_mut().chain(key_fields.iter_mut()) {
+                    avro::substitute(&mut field["type"], &defaults);
+                    if avro::is_nullable(&field["type"]) && field.get("default").is_none() {
+                        field["default"] = Value::Null;
+                    }
+                }
+            }
+            if let Some(conditional) = fields
+                .iter()
+                .find_map(|field| avro::undecided(&field["type"]))
+            {
+                return Err(ConversionError::conditional(conditional));
+            }
+            map.insert("fields".to_owned(), Value::Array(fields));
+            if let Some(doc) = doc.filter(|doc| !doc.is_empty()) {
+                map.insert("doc".to_owned(), Value::String(doc));
+            }
+            // `@avro.aliases OldName, older.Name` keeps readers of the old names working
+            let aliases: Vec<&str> = record_annotations
+                .iter()
+                .filter(|(tag, _)| tag == "avro.aliases")
+                .flat_map(|(_, value)| value.split([',', ' ']))
+                .filter(|alias| !alias.is_empty())
+                .collect();
+            if !aliases.is_empty() {
+                map.insert("aliases".to_owned(), json!(aliases));
+            }
+            if let Some(namespace) = &namespace {
+                map.insert("namespace".to_owned(), namespace.clone());
+            }
+            let key_record = match (&map["name"], key_fields.is_empty()) {
+                (Value::String(name), false) => {
+                    let mut key = json!({
+                        "type": "record",
+                        "name": format!("{}Key", name),
+                        "fields": key_fields
+                    });
+                    if let Some(namespace) = &namespace {
+                        key["namespace"] = namespace.clone();
+                    }
+                    Some(key)
+                }
+                _ => None,
+            };
+            let json_value = json!(map);
+            vec_map.push(json_value);
+            // `@avro.key` fields also form a key record, for key/value schema pairs
+            vec_map.extend(key_record);
+        }
+    }
+    Ok(())
+}
+
+// Members keep their explicit string value as the symbol, otherwise their name, which has
+// to be a valid Avro name: a value such as "in progress" is an error at its member. Member
+// comments are listed in the enum doc, and the member annotated `@avro.enumDefault`
+// becomes the default that readers fall back to for symbols they don't know.
+fn enum_schema(
+    node: &tree_sitter::Node,
+    code: &str,
+    doc: Option<String>,
+) -> Result<Option<Value>, ConversionError> {
+    let (name, body) = match (
+        node.child_by_field_name("name"),
+        node.child_by_field_name("body"),
+    ) {
+        (Some(name), Some(body)) => (text(&name, code)?, body),
+        _ => return Ok(None),
+    };
+
+    let mut symbols = Vec::new();
+    let mut docs: Vec<String> = doc.into_iter().filter(|doc| !doc.is_empty()).collect();
+    let mut default = None;
+    let mut comment = None;
+
+    let mut cursor = body.walk();
+    for member in body.named_children(&mut cursor) {
+        let member_text = text(&member, code)?;
+        let symbol = match member.kind() {
+            "comment" => {
+                comment = Some(member_text.to_owned());
+                continue;
+            }
+            "enum_assignment" => {
+                let value = member.child_by_field_name("value");
+                let name = member.child_by_field_name("name");
+                match (value.filter(|value| value.kind() == "string"), name) {
+                    (Some(value), _) => text(&value, code)?
+                        .trim_matches(|c| c == '"' || c == '\'')
+                        .to_owned(),
+                    (None, Some(name)) => text(&name, code)?.to_owned(),
+                    (None, None) => return Ok(None),
+                }
+            }
+            _ => member_text.to_owned(),
+        };
+        check_symbol(&symbol, &member)?;
+
+        if let Some(comment) = comment.take() {
+            let description = jsdoc::description(&comment);
+            if !description.is_empty() {
+                docs.push(format!("{}: {}", symbol, description));
+            }
+            if jsdoc::annotations(&comment)
+                .iter()
+                .any(|(tag, _)| tag == "avro.enumDefault")
+            {
+                default = Some(symbol.clone());
+            }
+        }
+        symbols.push(symbol);
+    }
+
+    let mut schema = json!({
+        "type": "enum",
+        "name": name,
+        "symbols": symbols
+    });
+    if !docs.is_empty() {
+        schema["doc"] = json!(docs.join("\n"));
+    }
+    if let Some(default) = default {
+        schema["default"] = json!(default);
+    }
+    Ok(Some(schema))
+}
+
+// `const STATUSES = ["open", "closed"] as const` declares the enum that
+// `typeof STATUSES[number]` refers to, named after the constant
+fn const_enums(
+    node: &tree_sitter::Node,
+    code: &str,
+    doc: Option<String>,
+) -> Result<Vec<Value>, ConversionError> {
+    let mut enums = Vec::new();
+    let mut cursor = node.walk();
+    for declarator in node.named_children(&mut cursor) {
+        let (name, value) = match (
+            declarator.child_by_field_name("name"),
+            declarator.child_by_field_name("value"),
+        ) {
+            (Some(name), Some(value)) if value.kind() == "as_expression" => (name, value),
+            _ => continue,
+        };
+        let array = match value.named_child(0) {
+            Some(array) if array.kind() == "array" && text(&value, code)?.ends_with("const") => {
+                array
+            }
+            _ => continue,
+        };
+        let mut symbols = Vec::new();
+        let mut elements = array.walk();
+        for element in array.named_children(&mut elements) {
+            if element.kind() != "string" {
+                symbols.clear();
+                break;
+            }
+            let symbol = text(&element, code)?.trim_matches(|c| c == '"' || c == '\'');
+            check_symbol(symbol, &element)?;
+            symbols.push(symbol.to_owned());
+        }
+        if symbols.is_empty() {
+            continue;
+        }
+        let mut schema = json!({
+            "type": "enum",
+            "name": text(&name, code)?,
+            "symbols": symbols
+        });
+        if let Some(doc) = doc.as_ref().filter(|doc| !doc.is_empty()) {
+            schema["doc"] = json!(doc);
+        }
+        enums.push(schema);
+    }
+    Ok(enums)
+}
+
+fn check_symbol(symbol: &str, node: &tree_sitter::Node) -> Result<(), ConversionError> {
+    let valid = symbol.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && symbol
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match valid {
+        true => Ok(()),
+        false => Err(ConversionError::symbol(symbol, node)),
+    }
+}
+
+// `T = string` in `interface Page<T = string>`, for the parameters that have a default
+fn type_defaults(
+    node: &tree_sitter::Node,
+    code: &str,
+) -> Result<Vec<(String, Value)>, ConversionError> {
+    let mut defaults = Vec::new();
+    let mut cursor = node.walk();
+    for parameter in node.named_children(&mut cursor) {
+        let default = parameter
+            .child_by_field_name("value")
+            .and_then(|value| value.named_child(0));
+        if let (Some(name), Some(default)) = (parameter.child_by_field_name("name"), default) {
+            defaults.push((
+                text(&name, code)?.to_owned(),
+                avro::from_node(&default, code),
+            ));
+        }
+    }
+    Ok(defaults)
+}
+
+fn is_class(node: &tree_sitter::Node) -> bool {
+    node.kind() == "class_declaration" || node.kind() == "abstract_class_declaration"
+}
+
+fn is_object_alias(node: &tree_sitter::Node) -> bool {
+    node.kind() == "type_alias_declaration"
+        && node
+            .child_by_field_name("value")
+            .and_then(|value| unwrap_flow_object(&value))
+            .is_some()
+}
+
+// Flow models are usually written as `type X = {| ... |}`, often wrapped in
+// `$ReadOnly<...>` or `$Exact<...>`; both still describe a plain record.
+fn unwrap_flow_object<'a>(node: &tree_sitter::Node<'a>) -> Option<tree_sitter::Node<'a>> {
+    match node.kind() {
+        "object_type" => Some(*node),
+        "generic_type" => {
+            let arguments = node.child_by_field_name("type_arguments")?;
+            let mut cursor = arguments.walk();
+            let mut objects = arguments
+                .named_children(&mut cursor)
+                .filter(|argument| argument.kind() == "object_type");
+            objects.next()
+        }
+        _ => None,
+    }
+}
+
+// interface members that describe behaviour rather than data
+fn dropped_member(node: &tree_sitter::Node) -> Option<&'static str> {
+    match node.kind() {
+        "method_signature" | "method_definition" => Some("method"),
+        "call_signature" => Some("call signature"),
+        "construct_signature" => Some("construct signature"),
+        _ => None,
+    }
+}
+
+// a class constructor only sets the properties up, it isn't dropped from the data
+fn is_constructor(node: &tree_sitter::Node, code: &str) -> bool {
+    node.kind() == "method_definition"
+        && node
+            .child_by_field_name("name")
+            .is_some_and(|name| name.utf8_text(code.as_bytes()) == Ok("constructor"))
+}
+
+// a zero-argument method returning a value, as the field it reads
+fn getter(node: &tree_sitter::Node, code: &str) -> Option<Value> {
+    if node.kind() != "method_signature" {
+        return None;
+    }
+    let parameters = node.child_by_field_name("parameters")?;
+    if parameters.named_child_count() > 0 {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let children: Vec<tree_sitter::Node> = node.children(&mut cursor).collect();
+    let returned = children
+        .iter()
+        .find(|child| child.kind() == "type_annotation")?
+        .named_child(0)?;
+    let ptype = avro::from_node(&returned, code);
+    if ptype == "null" {
+        return None;
+    }
+    let name = match accessor(node) {
+        Some(_) => member_name(node, code)?,
+        None => mapping::getter_field_name(&member_name(node, code)?),
+    };
+    match children.iter().any(|child| child.kind() == "?") {
+        true => Some(field(&name, avro::union(vec![json!("null"), ptype]))),
+        false => Some(field(&name, ptype)),
+    }
+}
+
+// `get` or `set` for an accessor signature such as `get name(): string`
+fn accessor(node: &tree_sitter::Node) -> Option<&'static str> {
+    if node.kind() != "method_signature" {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let kind = node
+        .children(&mut cursor)
+        .find_map(|child| match child.kind() {
+            "get" => Some("get"),
+            "set" => Some("set"),
+            _ => None,
+        });
+    kind
+}
+
+// the name diagnostics refer to a member by; signatures without one go by `()` and `new()`
+pub fn member_name(node: &tree_sitter::Node, code: &str) -> Option<String> {
+    match node.kind() {
+        "call_signature" => Some("()".to_owned()),
+        "construct_signature" => Some("new()".to_owned()),
+        _ => node
+            .child_by_field_name("name")
+            .and_then(|name| name.utf8_text(code.as_bytes()).ok())
+            .map(|name| name.trim_matches(|c| c == '"' || c == '\'').to_owned()),
+    }
+}
+
+fn get_prop_type(c_node: &tree_sitter::Node, code: &str) -> Result<Option<Value>, ConversionError> {
+    let mut pptype: Option<Value> = None;
+    let mut ppvalue: Option<&str> = None;
+    let mut optional = false;
+
+    let mut cursor = c_node.walk();
+    for node in c_node.children(&mut cursor) {
+        let propd = text(&node, code)?;
+        if propd == "?" {
+            optional = true;
+        } else if propd.starts_with(':') {
+            let mut subtype = node.walk();
+            for node in node.children(&mut subtype) {
+                if node.is_named() {
+                    pptype = Some(avro::from_node(&node, code));
+                }
+            }
+        } else {
+            ppvalue = Some(propd);
+        }
+    }
+
+    Ok(match (ppvalue, pptype) {
+        (Some(name), Some(ptype)) if optional => {
+            Some(field(name, avro::union(vec![json!("null"), ptype])))
+        }
+        (Some(name), Some(ptype)) => Some(field(name, ptype)),
+        _ => None,
+    })
+}
+
+// A class property, `id!: string` or `age: number = 0`, with its initializer as the default.
+// Static and `#private` properties aren't part of the data, and an unannotated property is
+// only typed from a literal initializer.
+fn class_field(node: &tree_sitter::Node, code: &str) -> Result<Option<Value>, ConversionError> {
+    let mut cursor = node.walk();
+    let children: Vec<tree_sitter::Node> = node.children(&mut cursor).collect();
+    if children.iter().any(|child| child.kind() == "static") {
+        return Ok(None);
+    }
+    let name = match node.child_by_field_name("name") {
+        Some(name) if name.kind() != "private_property_identifier" => text(&name, code)?,
+        _ => return Ok(None),
+    };
+    let value = node.child_by_field_name("value");
+    let annotated = node
+        .child_by_field_name("type")
+        .and_then(|annotation| annotation.named_child(0));
+    let ptype = match (annotated, value.map(|value| value.kind())) {
+        (Some(ptype), _) => avro::from_node(&ptype, code),
+        (None, Some("string") | Some("template_string")) => json!("string"),
+        (None, Some("number")) => json!("double"),
+        (None, Some("true") | Some("false")) => json!("boolean"),
+        _ => return Ok(None),
+    };
+    let ptype = match children.iter().any(|child| child.kind() == "?") {
+        true => avro::union(vec![json!("null"), ptype]),
+        false => ptype,
+    };
+
+    let mut value = field(name, ptype);
+    let default = match node.child_by_field_name("value") {
+        Some(initializer) => initial_value(&initializer, code)?,
+        None => None,
+    };
+    if let Some(default) = default {
+        // a union's default is of its first member
+        if let Value::Array(members) = &mut value["type"] {
+            if !default.is_null() && members[0] == "null" {
+                members.rotate_left(1);
+            }
+        }
+        value["default"] = default;
+    }
+    Ok(Some(value))
+}
+
+// initializers that are a constant, as JSON
+pub fn initial_value(
+    node: &tree_sitter::Node,
+    code: &str,
+) -> Result<Option<Value>, ConversionError> {
+    let value = text(node, code)?;
+    Ok(match node.kind() {
+        "string" => Some(json!(unescape(&value[1..value.len() - 1]))),
+        "number" | "unary_expression" => serde_json::from_str(value).ok(),
+        "true" | "false" => Some(json!(value == "true")),
+        "null" => Some(Value::Null),
+        "array" if node.named_child_count() == 0 => Some(json!([])),
+        "object" if node.named_child_count() == 0 => Some(json!({})),
+        // `Role.Admin`, a symbol of the enum
+        "member_expression" => match node.child_by_field_name("property") {
+            Some(property) => Some(json!(text(&property, code)?)),
+            None => None,
+        },
+        _ => None,
+    })
+}
+
+// the value of a JS string literal's contents, `it\'s` as `it's`; an escape that names no
+// character is kept as written
+fn unescape(literal: &str) -> String {
+    let mut units: Vec<u16> = Vec::new();
+    let mut chars = literal.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            units.extend(c.encode_utf16(&mut [0; 2]).iter());
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some(escaped) => escaped,
+            None => break,
+        };
+        let unescaped = match escaped {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            'b' => Some('\u{8}'),
+            'f' => Some('\u{c}'),
+            'v' => Some('\u{b}'),
+            '0' if !chars.peek().is_some_and(char::is_ascii_digit) => Some('\0'),
+            // a line continuation
+            '\n' | '\u{2028}' | '\u{2029}' => None,
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                None
+            }
+            'x' | 'u' => {
+                let digits: String = match (escaped, chars.peek()) {
+                    ('u', Some('{')) => {
+                        chars.next();
+                        chars.by_ref().take_while(|c| *c != '}').collect()
+                    }
+                    _ => {
+                        let length = if escaped == 'x' { 2 } else { 4 };
+                        let mut digits = String::new();
+                        while digits.len() < length {
+                            match chars.next_if(char::is_ascii_hexdigit) {
+                                Some(digit) => digits.push(digit),
+                                None => break,
+                            }
+                        }
+                        digits
+                    }
+                };
+                match u32::from_str_radix(&digits, 16) {
+                    // a surrogate half, paired with the next one when decoded
+                    Ok(unit @ 0xd800..=0xdfff) => {
+                        units.push(unit as u16);
+                        continue;
+                    }
+                    Ok(code) if char::from_u32(code).is_some() => char::from_u32(code),
+                    _ => {
+                        units.extend(format!("\\{}{}", escaped, digits).encode_utf16());
+                        continue;
+                    }
+                }
+            }
+            other => Some(other),
+        };
+        if let Some(c) = unescaped {
+            units.extend(c.encode_utf16(&mut [0; 2]).iter());
+        }
+    }
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+fn text<'a>(node: &tree_sitter::Node, code: &'a str) -> Result<&'a str, ConversionError> {
+    node.utf8_text(code.as_bytes())
+        .map_err(|_| ConversionError::malformed(node))
+}
+
+// Unions are normalized with null first, and a nullable field defaults to null: Avro takes a
+// union's default from its first branch, so this is the one order that lets the field have a
+// default, and a field with a default can be added or removed compatibly in both directions.
+fn field(name: &str, ptype: Value) -> Value {
+    let ptype = match ptype {
+        Value::Array(members) => avro::union(members),
+        ptype => ptype,
+    };
+
+    if avro::is_nullable(&ptype) {
+        json!({
+            "name": name,
+            "type": ptype,
+            "default": null
+        })
+    } else {
+        json!({
+            "name": name,
+            "type": ptype
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostic::Source;
+    use crate::{
+        anonymous_default, explain_field, fix_file, get_file_schema, get_schema, get_schema_with,
+        merger, parse_args, private_declarations, schema_version, Grammar, Input, Mapping, Methods,
+        UnionStrategy,
+    };
+    use serde_json::json;
+    use std::collections::BTreeSet;
+    use std::path::Path;
+
+    #[test]
+    fn test_basic_model() {
+        let code = r#"
+        interface Person {
+            age: number;
+            location: string | null;
+        }
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let schema = merger(schemas).unwrap();
+
+        assert_eq!(schema["type"], "record");
+        assert_eq!(schema["name"], "Person");
+        assert_eq!(schema["fields"][0]["name"], "age");
+        assert_eq!(schema["fields"][0]["type"], "double");
+        assert_eq!(schema["fields"][1]["name"], "location");
+        assert_eq!(schema["fields"][1]["type"][0], "null");
+        assert_eq!(schema["fields"][1]["type"][1], "string");
+        assert_eq!(schema["fields"][1]["default"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_nested_model() {
+        let code = r#"
+        interface Person {
+            age: number;
+            location: Location;
+        }
+
+        interface Location {
+            city: string;
+            state: string;
+        }
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let schema = merger(schemas).unwrap();
+
+        assert_eq!(schema["type"], "record");
+        assert_eq!(schema["name"], "Person");
+        assert_eq!(schema["fields"][0]["name"], "age");
+        assert_eq!(schema["fields"][0]["type"], "double");
+        assert_eq!(schema["fields"][1]["name"], "location");
+        assert_eq!(schema["fields"][1]["type"]["name"], "Location");
+        assert_eq!(schema["fields"][1]["type"]["fields"][0]["name"], "city");
+        assert_eq!(schema["fields"][1]["type"]["fields"][0]["type"], "string");
+        assert_eq!(schema["fields"][1]["type"]["fields"][1]["name"], "state");
+        assert_eq!(schema["fields"][1]["type"]["fields"][1]["type"], "string");
+    }
+
+    #[test]
+    fn test_references_resolved_once() {
+        let code = r#"
+        interface Order {
+            customer: Customer;
+            billing: Address | null;
+            shipping: Address;
+        }
+
+        interface Customer {
+            address: Address;
+            referrer?: Customer;
+        }
+
+        interface Address {
+            city: string;
+        }
+        "#;
+
+        let schema = merger(get_schema(code, Grammar::TypeScript).unwrap()).unwrap();
+        let customer = &schema["fields"][0]["type"];
+
+        assert_eq!(customer["name"], "Customer");
+        assert_eq!(customer["fields"][0]["type"]["name"], "Address");
+        assert_eq!(customer["fields"][1]["type"], json!(["null", "Customer"]));
+        assert_eq!(schema["fields"][1]["type"], json!(["null", "Address"]));
+        assert_eq!(schema["fields"][2]["type"], "Address");
+        assert_eq!(crate::verify::verify(&schema, 10), Ok(()));
+    }
+
+    #[test]
+    fn test_references_across_namespaces() {
+        let mut schemas = get_schema(
+            "interface Order { buyer: Customer; payer: Customer; }",
+            Grammar::TypeScript,
+        )
+        .unwrap();
+        let mut customers =
+            get_schema("interface Customer { name: string; }", Grammar::TypeScript).unwrap();
+        crate::namespace::apply(&mut schemas, "shop");
+        crate::namespace::apply(&mut customers, "crm");
+        schemas.extend(customers);
+
+        let schema = merger(schemas).unwrap();
+
+        assert_eq!(schema["fields"][0]["type"]["namespace"], "crm");
+        assert_eq!(schema["fields"][1]["type"], "crm.Customer");
+        assert_eq!(crate::verify::verify(&schema, 10), Ok(()));
+    }
+
+    #[test]
+    fn test_qualified_references() {
+        let code = r#"
+        interface Place { address: Geo.Point; nearby?: Geo.Point[]; owner: Crm.Customer; }
+        namespace Geo {
+            export interface Point { lat: number; lng: number; }
+        }
+        "#;
+        let mut schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let mut customers =
+            get_schema("interface Customer { name: string; }", Grammar::TypeScript).unwrap();
+        crate::namespace::apply(&mut customers, "com.crm");
+        schemas.extend(customers);
+
+        assert!(crate::avro::unresolved(&schemas).is_empty());
+        let schema = merger(crate::avro::reachable(schemas)).unwrap();
+
+        assert_eq!(schema["fields"][0]["type"]["name"], "Point");
+        assert_eq!(schema["fields"][1]["type"][1]["items"], "Point");
+        assert_eq!(schema["fields"][2]["type"]["namespace"], "com.crm");
+        assert_eq!(crate::verify::verify(&schema, 10), Ok(()));
+    }
+
+    #[test]
+    fn test_renamed_type_imports() {
+        let code = r#"
+        import type { Address as ShippingAddress } from "./address";
+        export interface Order { to: ShippingAddress; previous: ShippingAddress[]; }
+        "#;
+        let mut schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        schemas.extend(
+            get_schema(
+                "export interface Address { street: string; }",
+                Grammar::TypeScript,
+            )
+            .unwrap(),
+        );
+
+        let schema = merger(schemas).unwrap();
+
+        assert_eq!(schema["fields"][0]["type"]["name"], "Address");
+        assert_eq!(schema["fields"][1]["type"]["items"], "Address");
+    }
+
+    #[test]
+    fn test_default_export() {
+        let code = "// export default interface { in a comment }\nexport default interface { id: string }\n";
+        let schemas = get_file_schema(
+            code,
+            Path::new("models/shipping-label.ts"),
+            Grammar::TypeScript,
+            &Mapping::default(),
+        )
+        .unwrap();
+        assert_eq!(schemas[0]["name"], "ShippingLabel");
+        assert_eq!(schemas[0]["fields"][0]["name"], "id");
+
+        let anonymous = anonymous_default(
+            "export default  interface\n{}\n",
+            Grammar::TypeScript,
+            Path::new("models/refund/index.ts"),
+        )
+        .unwrap();
+        assert_eq!(anonymous.name, "Refund");
+        assert_eq!(anonymous.parsable, "export interface  $$$$$$$\n{}\n");
+
+        let code = "export default interface Order { id: string }\n";
+        assert!(anonymous_default(code, Grammar::TypeScript, Path::new("a.ts")).is_none());
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        assert_eq!(schemas[0]["name"], "Order");
+    }
+
+    #[test]
+    fn test_fix_default_export() {
+        let code = "export default interface {\n    id: string;\n    label: string;\n}\n";
+        let targets = vec![("ShippingLabel".to_owned(), "label".to_owned())];
+        let (fixed, rewrites) = fix_file(code, Grammar::TypeScript, "shipping-label.ts", &targets);
+        assert_eq!(
+            fixed,
+            "export default interface {\n    id: string;\n    label: string | null;\n}\n"
+        );
+        assert_eq!(rewrites[0].record, "ShippingLabel");
+    }
+
+    #[test]
+    fn test_generic_defaults() {
+        let code = r#"
+        interface Page<K, T = string> { items: T[]; next?: T; key: K }
+        export interface Feed { page: Page }
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let page = &schemas[0];
+        assert_eq!(
+            page["fields"][0]["type"],
+            json!({ "type": "array", "items": "string" })
+        );
+        assert_eq!(page["fields"][1]["type"], json!(["null", "string"]));
+        assert_eq!(page["fields"][1]["default"], json!(null));
+        assert_eq!(page["fields"][2]["type"], "K");
+    }
+
+    #[test]
+    fn test_conditional_types() {
+        let code = r#"
+        interface Labelled<T = string> {
+            label: T extends string ? string : number;
+            kind: "a" extends "b" ? boolean : string;
+            count: number extends string ? string : number;
+            anything: string extends unknown ? string : never;
+        }
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let types: Vec<&serde_json::Value> = schemas[0]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| &field["type"])
+            .collect();
+        assert_eq!(types, vec!["string", "string", "double", "string"]);
+    }
+
+    #[test]
+    fn test_const_array_enums() {
+        let code = r#"
+        export interface Ticket {
+            status: typeof STATUSES[number];
+            priority?: (typeof PRIORITIES)[number];
+        }
+        /** Where a ticket is */
+        export const STATUSES = ["open", "closed"] as const;
+        const PRIORITIES = ['low', 'high'] as const, LIMIT = 3;
+        const SIZES = ["s", "m"];
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        assert_eq!(
+            merger(schemas).unwrap()["fields"],
+            json!([
+                {
+                    "name": "status",
+                    "type": {
+                        "type": "enum",
+                        "name": "STATUSES",
+                        "doc": "Where a ticket is",
+                        "symbols": ["open", "closed"]
+                    }
+                },
+                {
+                    "name": "priority",
+                    "type": ["null", { "type": "enum", "name": "PRIORITIES", "symbols": ["low", "high"] }],
+                    "default": null
+                }
+            ])
+        );
+    }
 
-    let file_path = &args[1];
+    #[test]
+    fn test_classes() {
+        let code = r#"
+        export class UserDto {
+            static VERSION = 2;
+            #secret: string;
+            id!: string;
+            age: number = 0;
+            nickname?: string = 'anon';
+            active = true;
+            role: Role = Role.Admin;
+            tags: string[] = [];
+            constructor() {}
+            greet(): string { return this.id; }
+        }
+        "#;
+        let mapping = Mapping {
+            classes: true,
+            ..Mapping::default()
+        };
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        assert_eq!(
+            schemas[0]["fields"],
+            json!([
+                { "name": "id", "type": "string" },
+                { "name": "age", "type": "double", "default": 0 },
+                { "name": "nickname", "type": ["string", "null"], "default": "anon" },
+                { "name": "active", "type": "boolean", "default": true },
+                { "name": "role", "type": "Role", "default": "Admin" },
+                { "name": "tags", "type": { "type": "array", "items": "string" }, "default": [] }
+            ])
+        );
+        let dropped: Vec<String> = mapping
+            .dropped
+            .take()
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(
+            dropped,
+            vec!["UserDto.greet: dropped method, it has no Avro equivalent"]
+        );
 
-    let code = match File::open(file_path) {
-        Ok(mut file) => {
-            let mut contents = String::new();
-            if file.read_to_string(&mut contents).is_ok() {
-                contents
-            } else {
-                eprintln!("Failed to read the file.");
-                std::process::exit(1);
-            }
+        assert!(get_schema(code, Grammar::TypeScript).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_escaped_initializers() {
+        let code = r#"
+        export class Note {
+            title = 'it\'s';
+            body = "a\nb\t\"c\"";
+            path = 'C:\\temp';
+            symbol = '\u00e9\x41\u{1F600}\uD83D\uDE00';
         }
-        Err(err) => {
-            eprintln!("Error opening the file: {}", err);
-            std::process::exit(1);
+        "#;
+        let mapping = Mapping {
+            classes: true,
+            ..Mapping::default()
+        };
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        let defaults: Vec<&serde_json::Value> = schemas[0]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| &field["default"])
+            .collect();
+        assert_eq!(
+            defaults,
+            vec![
+                "it's",
+                "a\nb\t\"c\"",
+                "C:\\temp",
+                "\u{e9}A\u{1F600}\u{1F600}"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_class_validator_decorators() {
+        let code = r#"
+        export class CreateUserDto {
+            @IsUUID('4')
+            id!: string;
+
+            /** @avro.example 42 */
+            @IsInt() @Min(0) @Max(150)
+            @IsOptional()
+            age?: number;
+
+            @IsOptional
+            @MaxLength(20)
+            nickname: string;
         }
+        "#;
+        let mapping = Mapping {
+            classes: true,
+            ..Mapping::default()
+        };
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        assert_eq!(
+            schemas[0]["fields"],
+            json!([
+                { "name": "id", "type": { "type": "string", "logicalType": "uuid" } },
+                {
+                    "name": "age",
+                    "type": ["null", "int"],
+                    "default": null,
+                    "minimum": 0,
+                    "maximum": 150,
+                    "example": 42
+                },
+                { "name": "nickname", "type": ["null", "string"], "default": null, "maxLength": 20 }
+            ])
+        );
     }
-    .to_owned();
 
-    let schemas = get_schema(code);
-    let candidate_schema = merger(schemas);
+    #[test]
+    fn test_orm_entities() {
+        let code = r#"
+        @Entity()
+        export class Product {
+            @PrimaryGeneratedColumn()
+            id: number;
 
-    println!("{}", json!(candidate_schema));
-}
+            @Column({ type: 'numeric', precision: 12, scale: 2, default: "0.00" })
+            price: string;
+
+            @CreateDateColumn({ type: 'timestamptz' })
+            createdAt: Date;
+        }
+        "#;
+        let mapping = Mapping {
+            classes: true,
+            ..Mapping::default()
+        };
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        assert_eq!(
+            schemas[0]["fields"],
+            json!([
+                { "name": "id", "type": "int" },
+                {
+                    "name": "price",
+                    "type": { "type": "bytes", "logicalType": "decimal", "precision": 12, "scale": 2 },
+                    "default": "0.00"
+                },
+                { "name": "createdAt", "type": { "type": "long", "logicalType": "timestamp-millis" } }
+            ])
+        );
+
+        // as `prisma generate` writes the model types
+        let code = r#"
+        export type Invoice = {
+          id: string
+          total: Prisma.Decimal
+          metadata: Prisma.JsonValue | null
+          pdf: Buffer
+        }
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        assert_eq!(
+            schemas[0]["fields"],
+            json!([
+                { "name": "id", "type": "string" },
+                {
+                    "name": "total",
+                    "type": { "type": "bytes", "logicalType": "decimal", "precision": 65, "scale": 30 }
+                },
+                { "name": "metadata", "type": ["null", "string"], "default": null },
+                { "name": "pdf", "type": "bytes" }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_flow_model() {
+        let code = r#"
+        // @flow
+        export type Person = $ReadOnly<{|
+            +name: string,
+            nickname: ?string,
+        |}>;
 
-fn merger(schemas: Vec<Value>) -> Value {
-    let mut candidate_schema = schemas[0].clone();
+        export type Id = string;
+        "#;
 
-    let base_types = ["string", "number", "null", "Date", "boolean"];
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let schema = merger(schemas.clone()).unwrap();
 
-    for (i, entry) in schemas[0]["fields"].as_array().unwrap().iter().enumerate() {
-        if !base_types.iter().any(|&x| *x == entry["type"]) {
-            let sub_schema = schemas.iter().find(|&x| x["name"] == entry["type"]);
-            if let Some(value) = sub_schema {
-                candidate_schema["fields"].as_array_mut().unwrap()[i] = value.clone();
-            }
+        assert_eq!(schemas.len(), 1);
+        assert_eq!(schema["name"], "Person");
+        assert_eq!(schema["fields"][0]["name"], "name");
+        assert_eq!(schema["fields"][0]["type"], "string");
+        assert_eq!(schema["fields"][1]["name"], "nickname");
+        assert_eq!(schema["fields"][1]["type"][0], "null");
+        assert_eq!(schema["fields"][1]["type"][1], "string");
+    }
+
+    #[test]
+    fn test_tsx_model() {
+        let code = r#"
+        export interface ButtonProps {
+            label: string;
+            disabled: boolean;
         }
+
+        export const Button = (props: ButtonProps) => <button>{props.label}</button>;
+        "#;
+
+        let schemas = get_schema(code, Grammar::Tsx).unwrap();
+        let schema = merger(schemas).unwrap();
+
+        assert_eq!(schema["name"], "ButtonProps");
+        assert_eq!(schema["fields"][0]["name"], "label");
+        assert_eq!(schema["fields"][1]["name"], "disabled");
+        assert_eq!(schema["fields"][1]["type"], "boolean");
     }
 
-    candidate_schema
-}
+    #[test]
+    fn test_grammar_from_path() {
+        assert_eq!(Grammar::from_path("button.tsx", false), Grammar::Tsx);
+        assert_eq!(Grammar::from_path("person.ts", true), Grammar::Tsx);
+        assert_eq!(Grammar::from_path("person.ts", false), Grammar::TypeScript);
+    }
 
-fn get_schema(code: String) -> Vec<Value> {
-    let mut vec_map = Vec::new();
+    #[test]
+    fn test_jsdoc_model() {
+        let code = r#"
+        /**
+         * @typedef {Object} Person
+         * @property {number} age
+         * @property {Location} location
+         */
 
-    let mut parser = Parser::new();
-    parser
-        .set_language(tree_sitter_typescript::language_typescript())
-        .expect("Error loading typescript grammar");
-    let parsed = parser.parse(code.clone(), None).unwrap();
-    let root = parsed.root_node();
-    let mut root_iter = root.walk();
-    for node in root_iter.node().children(&mut root_iter) {
-        if node.kind() == "interface_declaration" {
-            let mut map = Map::new();
-            map.insert("type".to_owned(), Value::String("Record".to_owned()));
-            let mut fields = Vec::new();
-            let mut interface = node.walk();
+        /**
+         * @typedef {Object} Location
+         * @property {string} city
+         */
 
-            node.children(&mut interface).for_each(|node| {
-                let iname = node.utf8_text(code.as_bytes()).unwrap();
+        function greet(person) {}
+        "#;
 
-                match node.kind() {
-                    "type_identifier" => {
-                        map.insert("name".to_owned(), Value::String(iname.to_owned()));
-                    }
-                    "object_type" => {
-                        let mut oter = node.walk();
-                        node.children(&mut oter).for_each(|node| {
-                            let prop = get_prop_type(&node, code.clone());
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let schema = merger(schemas).unwrap();
 
-                            if let Some(value) = prop {
-                                fields.push(value);
-                            }
-                        });
-                    }
-                    _ => {}
+        assert_eq!(schema["name"], "Person");
+        assert_eq!(schema["fields"][0]["type"], "double");
+        assert_eq!(schema["fields"][1]["name"], "location");
+        assert_eq!(schema["fields"][1]["type"]["fields"][0]["name"], "city");
+    }
+
+    #[test]
+    fn test_declaration_file() {
+        let code = r#"
+        declare module "people" {
+            interface Location {
+                city: string;
+            }
+
+            namespace People {
+                export interface Person {
+                    age: number;
+                    location: Location;
                 }
-            });
+            }
 
-            map.insert("fields".to_owned(), Value::Array(fields));
-            let json_value = json!(map);
-            vec_map.push(json_value);
+            export = Person;
         }
+
+        export declare type Id = { value: string };
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let schema = merger(schemas.clone()).unwrap();
+
+        assert_eq!(schemas.len(), 3);
+        assert_eq!(schemas[2]["name"], "Id");
+        assert_eq!(schema["name"], "Person");
+        assert_eq!(schema["fields"][1]["name"], "location");
+        assert_eq!(schema["fields"][1]["type"]["fields"][0]["name"], "city");
     }
 
-    vec_map
-}
+    #[test]
+    fn test_schema_version_precedence() {
+        let config = json!({ "metadata": { "version": "1.4.0" } });
 
-fn get_prop_type(c_node: &tree_sitter::Node, code: String) -> Option<Value> {
-    let mut pptype: Option<Value> = None;
-    let mut ppvalue: Option<String> = None;
+        let args = parse_args(&["person.ts".to_owned()]).unwrap();
+        assert_eq!(
+            schema_version(&args, &config).unwrap(),
+            Some("1.4.0".to_owned())
+        );
 
-    let mut cursor = c_node.walk();
-    c_node.children(&mut cursor).for_each(|node| {
-        let propd = node.utf8_text(code.as_bytes()).unwrap();
-        if propd.chars().collect::<Vec<char>>()[0] == ':' {
-            let mut subtype = node.walk();
-            node.children(&mut subtype).for_each(|node| {
-                let typed = node.utf8_text(code.as_bytes()).unwrap().to_owned();
-                if typed != ":" {
-                    if typed.contains('|') {
-                        let mut col = Vec::new();
-                        typed.split('|').for_each(|c| {
-                            col.push(Value::String(c.trim().to_owned()));
-                        });
-                        pptype = Some(Value::Array(col));
-                    } else {
-                        pptype = Some(Value::String(typed));
-                    }
-                }
-            });
-        } else {
-            ppvalue = Some(propd.to_string());
+        let args = parse_args(&[
+            "--schema-version".to_owned(),
+            "2.0.0".to_owned(),
+            "person.ts".to_owned(),
+        ])
+        .unwrap();
+        assert_eq!(
+            schema_version(&args, &config).unwrap(),
+            Some("2.0.0".to_owned())
+        );
+
+        assert_eq!(
+            schema_version(&args, &json!({})).unwrap(),
+            Some("2.0.0".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_union_normalization() {
+        let code = r#"
+        interface Person {
+            nickname: string | null | undefined;
+            value: Location | string | number | null;
+            other: number | string;
+            same: string | string;
         }
-    });
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
 
-    if ppvalue.is_some() && pptype.is_some() {
-        return Some(json!({
-            "name": ppvalue.unwrap(),
-            "type": pptype.unwrap()
-        }));
+        assert_eq!(schemas[0]["fields"][0]["type"], json!(["null", "string"]));
+        assert_eq!(schemas[0]["fields"][0]["default"], serde_json::Value::Null);
+        assert_eq!(
+            schemas[0]["fields"][1]["type"],
+            json!(["null", "double", "string", "Location"])
+        );
+        assert_eq!(schemas[0]["fields"][2]["type"], json!(["double", "string"]));
+        assert!(schemas[0]["fields"][2].get("default").is_none());
+        assert_eq!(schemas[0]["fields"][3]["type"], "string");
     }
-    None
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{get_schema, merger};
+    #[test]
+    fn test_key_record() {
+        let code = r#"
+        interface Order {
+            /** @avro.key */
+            tenantId: string;
+            /**
+             * The order number.
+             * @avro.key
+             */
+            orderId: number;
+            // not a key
+            total: number;
+        }
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        assert_eq!(schemas.len(), 2);
+        assert_eq!(schemas[0]["fields"].as_array().unwrap().len(), 3);
+        assert_eq!(schemas[1]["name"], "OrderKey");
+        assert_eq!(schemas[1]["fields"][0]["name"], "tenantId");
+        assert_eq!(schemas[1]["fields"][1]["name"], "orderId");
+        assert_eq!(schemas[1]["fields"].as_array().unwrap().len(), 2);
+    }
 
     #[test]
-    fn test_basic_model() {
+    fn test_optional_logical_type() {
         let code = r#"
         interface Person {
-            age: number;
-            location: string | null;
+            createdAt?: Date;
+            deletedAt: Date | null | undefined;
+            bornAt: Date;
         }
         "#;
 
-        let schemas = get_schema(code.to_string());
-        let schema = merger(schemas);
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let timestamp = json!({ "type": "long", "logicalType": "timestamp-millis" });
 
-        assert_eq!(schema["type"], "Record");
-        assert_eq!(schema["name"], "Person");
-        assert_eq!(schema["fields"][0]["name"], "age");
-        assert_eq!(schema["fields"][0]["type"], "number");
-        assert_eq!(schema["fields"][1]["name"], "location");
-        assert_eq!(schema["fields"][1]["type"][0], "string");
-        assert_eq!(schema["fields"][1]["type"][1], "null");
+        assert_eq!(schemas[0]["fields"][0]["name"], "createdAt");
+        assert_eq!(schemas[0]["fields"][0]["type"], json!(["null", timestamp]));
+        assert_eq!(schemas[0]["fields"][0]["default"], serde_json::Value::Null);
+        assert_eq!(schemas[0]["fields"][1]["type"], json!(["null", timestamp]));
+        assert_eq!(schemas[0]["fields"][2]["type"], timestamp);
     }
 
     #[test]
-    fn test_nested_model() {
+    fn test_quoted_keys() {
         let code = r#"
         interface Person {
-            age: number;
-            location: Location;
+            "ok": boolean;
+            'first-name': string;
+            "2fa"?: boolean;
+            last_name: string;
+        }
+        "#;
+
+        let mapping = Mapping::default();
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+
+        let names: Vec<&serde_json::Value> = schemas[0]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| &field["name"])
+            .collect();
+        assert_eq!(names, vec!["ok", "first_name", "_2fa", "last_name"]);
+        let sanitized: Vec<String> = mapping
+            .sanitized
+            .take()
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(
+            sanitized,
+            vec![
+                "Person.first-name: sanitized the name to first_name, which Avro accepts",
+                "Person.2fa: sanitized the name to _2fa, which Avro accepts"
+            ]
+        );
+        let source = crate::diagnostic::Source {
+            path: "person.ts",
+            code,
+            grammar: Grammar::TypeScript,
+        };
+        assert!(source.field_span("Person", "first-name").is_some());
+    }
+
+    #[test]
+    fn test_enum() {
+        let code = r#"
+        /** Paint colours */
+        export enum Color {
+            /** the red one */
+            Red = "RED",
+            // plain green
+            Green,
+            /** @avro.enumDefault */
+            Unknown = 3,
+        }
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        assert_eq!(
+            schemas[0],
+            json!({
+                "type": "enum",
+                "name": "Color",
+                "symbols": ["RED", "Green", "Unknown"],
+                "doc": "Paint colours\nRED: the red one\nGreen: plain green",
+                "default": "Unknown"
+            })
+        );
+    }
+
+    #[test]
+    fn test_nested_generics() {
+        let code = r#"
+        interface Inventory {
+            shelves: Array<Map<string, Item>>;
+            grid: Item[][];
+            counts: Record<string, number>;
+            labels: readonly string[];
+        }
+
+        interface Item {
+            sku: string;
+        }
+        "#;
+
+        let schema = merger(get_schema(code, Grammar::TypeScript).unwrap()).unwrap();
+        let item = json!({
+            "type": "record",
+            "name": "Item",
+            "fields": [{ "name": "sku", "type": "string" }]
+        });
+
+        assert_eq!(
+            schema["fields"][0]["type"],
+            json!({ "type": "array", "items": { "type": "map", "values": item } })
+        );
+        // Item is defined where it's first used, and referred to by name afterwards
+        assert_eq!(
+            schema["fields"][1]["type"],
+            json!({ "type": "array", "items": { "type": "array", "items": "Item" } })
+        );
+        assert_eq!(
+            schema["fields"][2]["type"],
+            json!({ "type": "map", "values": "double" })
+        );
+        assert_eq!(
+            schema["fields"][3]["type"],
+            json!({ "type": "array", "items": "string" })
+        );
+    }
+
+    #[test]
+    fn test_undefined_is_nullable() {
+        let code = r#"
+        interface Person {
+            name: string | undefined;
+            nickname?: string | undefined;
+            age: undefined | number;
+        }
+
+        /**
+         * @typedef {Object} Pet
+         * @property {string|undefined} name
+         */
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        for (schema, i) in [(&schemas[0], 0), (&schemas[0], 1), (&schemas[1], 0)] {
+            assert_eq!(schema["fields"][i]["type"], json!(["null", "string"]));
+            assert_eq!(schema["fields"][i]["default"], serde_json::Value::Null);
+        }
+        assert_eq!(schemas[0]["fields"][2]["type"], json!(["null", "double"]));
+    }
+
+    #[test]
+    fn test_optional_and_nullable() {
+        let code = r#"
+        interface Person {
+            nickname?: string | null;
+            email?: null | string | null;
+        }
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        assert_eq!(schemas[0]["fields"][0]["type"], json!(["null", "string"]));
+        assert_eq!(schemas[0]["fields"][1]["type"], json!(["null", "string"]));
+    }
+
+    #[test]
+    fn test_dropped_members() {
+        let code = r#"
+        interface Person {
+            name: string;
+            getName(): string;
+            (x: number): string;
+            new (x: number): Person;
+        }
+        "#;
+        let mapping = Mapping::default();
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+
+        assert_eq!(
+            schemas[0]["fields"],
+            json!([{ "name": "name", "type": "string" }])
+        );
+        assert_eq!(
+            mapping
+                .dropped
+                .take()
+                .iter()
+                .map(|member| member.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "Person.getName: dropped method, it has no Avro equivalent",
+                "Person.(): dropped call signature, it has no Avro equivalent",
+                "Person.new(): dropped construct signature, it has no Avro equivalent"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_getters() {
+        let code = r#"
+        interface Person {
+            getName(): string;
+            getNickname?(): string;
+            nickname?: string;
+            isActive(): boolean;
+            greet(other: Person): string;
+            reset(): void;
+        }
+        "#;
+        let mapping = Mapping {
+            methods: Methods::Getters,
+            ..Mapping::default()
+        };
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+
+        assert_eq!(
+            schemas[0]["fields"],
+            json!([
+                { "name": "name", "type": "string" },
+                { "name": "nickname", "type": ["null", "string"], "default": null },
+                { "name": "isActive", "type": "boolean" }
+            ])
+        );
+        assert_eq!(mapping.dropped.take().len(), 2);
+    }
+
+    #[test]
+    fn test_accessors_as_fields() {
+        let code = r#"
+        interface Person {
+            get name(): string;
+            set name(value: string);
+            get getter(): boolean;
+            getAge(): number;
+        }
+        "#;
+        let mapping = Mapping {
+            accessors: true,
+            ..Mapping::default()
+        };
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+
+        assert_eq!(
+            schemas[0]["fields"],
+            json!([
+                { "name": "name", "type": "string" },
+                { "name": "getter", "type": "boolean" }
+            ])
+        );
+        let dropped: Vec<String> = mapping
+            .dropped
+            .take()
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(
+            dropped,
+            vec!["Person.getAge: dropped method, it has no Avro equivalent"]
+        );
+    }
+
+    #[test]
+    fn test_record_doc() {
+        let code = r#"
+        /**
+         * Someone who can place orders.
+         * @avro.namespace com.acme
+         */
+        export interface Person {
+            /** The display name. */
+            name: string;
         }
 
+        /** Not about Location. */
+
         interface Location {
             city: string;
-            state: string;
         }
         "#;
 
-        let schemas = get_schema(code.to_string());
-        let schema = merger(schemas);
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
 
-        assert_eq!(schema["type"], "Record");
-        assert_eq!(schema["name"], "Person");
-        assert_eq!(schema["fields"][0]["name"], "age");
-        assert_eq!(schema["fields"][0]["type"], "number");
-        assert_eq!(schema["fields"][1]["name"], "Location");
-        assert_eq!(schema["fields"][1]["fields"][0]["name"], "city");
-        assert_eq!(schema["fields"][1]["fields"][0]["type"], "string");
-        assert_eq!(schema["fields"][1]["fields"][1]["name"], "state");
-        assert_eq!(schema["fields"][1]["fields"][1]["type"], "string");
+        assert_eq!(schemas[0]["doc"], "Someone who can place orders.");
+        assert_eq!(schemas[0]["fields"][0].get("doc"), None);
+        assert_eq!(schemas[1].get("doc"), None);
+    }
+
+    #[test]
+    fn test_record_aliases() {
+        let code = r#"
+        /** @avro.aliases OldPerson, com.acme.Customer */
+        interface Person {
+            name: string;
+        }
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        assert_eq!(
+            schemas[0]["aliases"],
+            json!(["OldPerson", "com.acme.Customer"])
+        );
+        assert_eq!(schemas[0].get("doc"), None);
+    }
+
+    #[test]
+    fn test_namespace_annotation() {
+        let code = r#"
+        /** @avro.namespace com.acme.billing */
+        interface Invoice {
+            /** @avro.key */
+            id: string;
+        }
+
+        /** @avro.namespace com.acme.shared */
+        enum Currency {
+            EUR,
+        }
+
+        interface Customer {
+            name: string;
+        }
+        "#;
+
+        let mut schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        crate::namespace::apply(&mut schemas, "com.acme.sales");
+
+        assert_eq!(schemas[0]["namespace"], "com.acme.billing");
+        assert_eq!(schemas[1]["name"], "InvoiceKey");
+        assert_eq!(schemas[1]["namespace"], "com.acme.billing");
+        assert_eq!(schemas[2]["namespace"], "com.acme.shared");
+        assert_eq!(schemas[3]["namespace"], "com.acme.sales");
+    }
+
+    #[test]
+    fn test_private_declarations() {
+        let module = Input {
+            path: "order.ts".to_owned(),
+            code: "interface Line { sku: string }\nexport interface Order { lines: Line[] }\n"
+                .to_owned(),
+            grammar: Grammar::TypeScript,
+            namespace: None,
+        };
+        let script = Input {
+            path: "person.js".to_owned(),
+            code: "/** @typedef {Object} Person */\ninterface Pet { name: string }\n".to_owned(),
+            grammar: Grammar::TypeScript,
+            namespace: None,
+        };
+
+        assert_eq!(
+            private_declarations(&[module, script]),
+            BTreeSet::from(["Line".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_declaration_merging() {
+        let code = r#"
+        /** A user. */
+        export interface User {
+            /** @avro.key */
+            id: string;
+            name: string;
+        }
+
+        interface Address {
+            city: string;
+        }
+
+        export interface User {
+            name: string;
+            address: Address;
+        }
+        "#;
+
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+
+        assert_eq!(schemas.len(), 3);
+        assert_eq!(schemas[0]["doc"], "A user.");
+        assert_eq!(
+            schemas[0]["fields"],
+            json!([
+                { "name": "id", "type": "string" },
+                { "name": "name", "type": "string" },
+                { "name": "address", "type": "Address" }
+            ])
+        );
+        assert_eq!(schemas[1]["name"], "UserKey");
+        assert_eq!(schemas[2]["name"], "Address");
+    }
+
+    #[test]
+    fn test_explain_field() {
+        let code = r#"
+        interface User {
+            createdAt?: string;
+            setting: string | number;
+        }
+        "#;
+        let source = Source {
+            path: "user.ts",
+            code,
+            grammar: Grammar::TypeScript,
+        };
+        let mapping = Mapping {
+            infer_timestamps: true,
+            ..Mapping::default()
+        };
+
+        assert_eq!(
+            explain_field(
+                &source,
+                mapping.clone(),
+                UnionStrategy::Union,
+                "User.createdAt"
+            ),
+            Ok(r#"User.createdAt: string
+  default mapping: ["null","string"]
+  heuristic --infer-timestamps: ["null",{"logicalType":"timestamp-millis","type":"long"}]
+  result: ["null",{"logicalType":"timestamp-millis","type":"long"}]
+"#
+            .to_owned())
+        );
+        assert_eq!(
+            explain_field(
+                &source,
+                mapping.clone(),
+                UnionStrategy::String,
+                "User.setting"
+            ),
+            Ok(r#"User.setting: string | number
+  default mapping: ["double","string"]
+  union strategy: collapsed double | string to string
+  result: "string"
+"#
+            .to_owned())
+        );
+        assert_eq!(
+            explain_field(&source, mapping, UnionStrategy::Union, "User.missing"),
+            Err("No field User.missing found in user.ts".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_namespace_template() {
+        let config = crate::config::parse("[namespaces]\ntemplate = \"com.shop.{dir}\"\n").unwrap();
+        let parsed = crate::Args {
+            namespace: Some("com.given".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            crate::input_namespace(
+                &crate::Args::default(),
+                &config,
+                "src/order-events/order.ts"
+            ),
+            Some("com.shop.order_events".to_owned())
+        );
+        assert_eq!(
+            crate::input_namespace(&parsed, &config, "src/order-events/order.ts"),
+            Some("com.given".to_owned())
+        );
+        let parsed = crate::Args {
+            namespace_template: Some("{file}".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            crate::input_namespace(&parsed, &serde_json::Value::Null, "order.ts"),
+            Some("order".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_strip_fields() {
+        let code = r#"
+        interface Account {
+            id: string;
+            password: string;
+            apiSecret: string;
+            internalNotes: string;
+            profile: { name: string; resetSecret: string };
+            get internalFlags(): number;
+        }
+        "#;
+        let mapping = Mapping {
+            accessors: true,
+            strip: vec![
+                "password".to_owned(),
+                "*Secret".to_owned(),
+                "internal*".to_owned(),
+            ],
+            ..Mapping::default()
+        };
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+
+        let fields: Vec<&serde_json::Value> = schemas[0]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| &field["name"])
+            .collect();
+        assert_eq!(fields, vec!["id", "profile"]);
+        assert_eq!(
+            schemas[1]["fields"],
+            json!([{ "name": "name", "type": "string" }])
+        );
     }
 }
@@ -0,0 +1,147 @@
+use serde_json::Value;
+use std::path::Path;
+
+// A batch of conversions from a TOML manifest, one `[[job]]` table each:
+//
+//     registry = "http://registry:8081"
+//     options = ["--ids-as-long"]
+//
+//     [[job]]
+//     name = "orders"
+//     input = "src/orders"
+//     root = "Order"
+//     namespace = "com.shop.orders"
+//     output = "schemas/order.avsc"
+//     subject = "orders-value"
+//
+// The top-level `options` go before each job's own, and a job with a `subject` publishes its
+// root record under it once converted. `input` and `output` are relative to the directory
+// of the manifest, so a batch runs the same from anywhere.
+#[derive(Debug, PartialEq)]
+pub struct Job {
+    pub name: String,
+    pub convert: Vec<String>,
+    pub output: Option<String>,
+    pub publish: Option<Vec<String>>,
+}
+
+pub fn jobs(manifest: &Value, dir: &Path) -> Result<Vec<Job>, String> {
+    let relative = |path: &str| dir.join(path).to_string_lossy().into_owned();
+    let mut jobs = Vec::new();
+    for (i, job) in manifest["job"].as_array().into_iter().flatten().enumerate() {
+        let name = job["name"]
+            .as_str()
+            .map_or_else(|| format!("job {}", i + 1), str::to_owned);
+        let input = job["input"]
+            .as_str()
+            .map(relative)
+            .ok_or(format!("{} has no input", name))?;
+
+        let mut options = Vec::new();
+        for list in [&manifest["options"], &job["options"]] {
+            for option in list.as_array().into_iter().flatten() {
+                let option = option
+                    .as_str()
+                    .ok_or(format!("{}: options have to be strings", name))?;
+                options.push(option.to_owned());
+            }
+        }
+        for (key, flag) in [("root", "--root"), ("namespace", "--namespace")] {
+            match &job[key] {
+                Value::Null => {}
+                Value::String(value) => options.extend([flag.to_owned(), value.to_owned()]),
+                _ => return Err(format!("{}: {} has to be a string", name, key)),
+            }
+        }
+
+        let mut convert = options.clone();
+        convert.push(input.clone());
+        let publish = job["subject"].as_str().map(|subject| {
+            let mut args = vec!["--subject".to_owned(), subject.to_owned()];
+            if let Some(registry) = job["registry"].as_str().or(manifest["registry"].as_str()) {
+                args.extend(["--registry".to_owned(), registry.to_owned()]);
+            }
+            args.extend(options);
+            args.push(input);
+            args
+        });
+        jobs.push(Job {
+            name,
+            convert,
+            output: job["output"].as_str().map(relative),
+            publish,
+        });
+    }
+    match jobs.is_empty() {
+        true => Err("The manifest has no [[job]] tables".to_owned()),
+        false => Ok(jobs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config;
+    use crate::manifest::{jobs, Job};
+    use std::path::Path;
+
+    #[test]
+    fn test_jobs() {
+        let manifest = config::parse(
+            r#"
+            registry = "http://registry:8081"
+            options = ["--ids-as-long"]
+
+            [[job]]
+            name = "orders"
+            input = "src/orders"
+            root = "Order"
+            output = "schemas/order.avsc"
+            subject = "orders-value"
+
+            [[job]]
+            input = "/src/refund.ts"
+            namespace = "com.shop"
+            options = ["--unions", "string"]
+            "#,
+        )
+        .unwrap();
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|x| x.to_string()).collect() };
+
+        assert_eq!(
+            jobs(&manifest, Path::new("pipelines")).unwrap(),
+            vec![
+                Job {
+                    name: "orders".to_owned(),
+                    convert: args(&["--ids-as-long", "--root", "Order", "pipelines/src/orders"]),
+                    output: Some("pipelines/schemas/order.avsc".to_owned()),
+                    publish: Some(args(&[
+                        "--subject",
+                        "orders-value",
+                        "--registry",
+                        "http://registry:8081",
+                        "--ids-as-long",
+                        "--root",
+                        "Order",
+                        "pipelines/src/orders"
+                    ])),
+                },
+                Job {
+                    name: "job 2".to_owned(),
+                    convert: args(&[
+                        "--ids-as-long",
+                        "--unions",
+                        "string",
+                        "--namespace",
+                        "com.shop",
+                        "/src/refund.ts"
+                    ]),
+                    output: None,
+                    publish: None,
+                },
+            ]
+        );
+        let dir = Path::new("");
+        assert!(jobs(&config::parse("[[job]]\nname = \"x\"\n").unwrap(), dir).is_err());
+        assert!(jobs(&config::parse("").unwrap(), dir).is_err());
+    }
+}
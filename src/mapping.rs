@@ -0,0 +1,728 @@
+use crate::diagnostic::Diagnostic;
+use crate::{avro, config};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs;
+
+// Opt-in rules refining how TypeScript types map to Avro, applied to each field once its
+// type is built. An `@avro.logicalType` annotation on the field takes precedence over any
+// heuristic, and `@avro.logicalType none` opts the field out of them.
+#[derive(Clone, Debug, Default)]
+pub struct Mapping {
+    pub infer_timestamps: bool,
+    pub ids_as_long: bool,
+    pub java_string: bool,
+    // every field but the `@avro.key` ones nullable with a null default, for `--evolution-safe`
+    pub evolution_safe: bool,
+    pub decimals: Vec<DecimalRule>,
+    // named TS types mapped to an Avro type, from a `--type-map` file
+    pub types: Value,
+    // when set, every rule applied to a field is recorded, for `explain`
+    pub trace: Option<RefCell<Vec<Step>>>,
+    pub methods: Methods,
+    // `get name(): string` accessors read as a `name` field, whatever `methods` says
+    pub accessors: bool,
+    // members with no field equivalent, such as methods, left out of their record
+    pub dropped: RefCell<Vec<Diagnostic>>,
+    // properties renamed because Avro names can't hold them, as `"first-name"`
+    pub sanitized: RefCell<Vec<Diagnostic>>,
+    // classes with property declarations are read like interfaces, for `--include-classes`
+    pub classes: bool,
+    // globs of the properties left out of every record, for `--strip-fields`
+    pub strip: Vec<String>,
+}
+
+// What becomes of method signatures in an interface: skipped with a warning, an error, or,
+// for zero-argument methods returning a value, a field named after the getter.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Methods {
+    #[default]
+    Skip,
+    Strict,
+    Getters,
+}
+
+impl Methods {
+    pub fn parse(name: &str) -> Option<Methods> {
+        match name {
+            "skip" => Some(Methods::Skip),
+            "strict" => Some(Methods::Strict),
+            "getters" => Some(Methods::Getters),
+            _ => None,
+        }
+    }
+}
+
+// `getName` is read as `name`; other getters keep their method name
+pub fn getter_field_name(method: &str) -> String {
+    match method.strip_prefix("get") {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_uppercase()) => {
+            let mut chars = rest.chars();
+            let first = chars.next().unwrap_or_default().to_ascii_lowercase();
+            format!("{}{}", first, chars.as_str())
+        }
+        _ => method.to_owned(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub record: String,
+    pub field: String,
+    pub rule: String,
+    pub ptype: Value,
+}
+
+impl Step {
+    pub fn is_heuristic(&self) -> bool {
+        self.rule.starts_with("heuristic") || self.rule.starts_with("decimal pattern")
+    }
+}
+
+// the (record, field, rule) of every heuristic that changed a field's type
+pub fn heuristics_applied(steps: &[Step]) -> BTreeSet<(String, String, String)> {
+    let mut applied = BTreeSet::new();
+    for (i, step) in steps.iter().enumerate() {
+        let previous = steps[..i]
+            .iter()
+            .rev()
+            .find(|x| x.record == step.record && x.field == step.field);
+        if step.is_heuristic() && previous.is_some_and(|previous| previous.ptype != step.ptype) {
+            applied.insert((step.record.clone(), step.field.clone(), step.rule.clone()));
+        }
+    }
+    applied
+}
+
+// `[[mapping.decimals]]` maps number fields whose name matches the glob to a decimal
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecimalRule {
+    pub pattern: String,
+    pub precision: u64,
+    pub scale: u64,
+}
+
+pub fn decimal_rules(mapping: &Value) -> Result<Vec<DecimalRule>, String> {
+    let mut rules = Vec::new();
+    for rule in mapping["decimals"].as_array().into_iter().flatten() {
+        let pattern = rule["pattern"]
+            .as_str()
+            .ok_or("mapping.decimals: every rule needs a pattern")?;
+        let precision = rule["precision"]
+            .as_u64()
+            .ok_or(format!("mapping.decimals {}: missing precision", pattern))?;
+        let scale = rule["scale"].as_u64().unwrap_or(0);
+        if scale > precision {
+            return Err(format!(
+                "mapping.decimals {}: scale {} exceeds precision {}",
+                pattern, scale, precision
+            ));
+        }
+        rules.push(DecimalRule {
+            pattern: pattern.to_owned(),
+            precision,
+            scale,
+        });
+    }
+    Ok(rules)
+}
+
+// The type map file maps project types to Avro types:
+//
+//   ObjectId = "string"
+//   Dayjs = { type = "long", logicalType = "timestamp-millis" }
+pub fn load_types(path: &str) -> Result<Value, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Error opening the type map {}: {}", path, err))?;
+    config::parse(&contents).map_err(|err| format!("{}: {}", path, err))
+}
+
+impl Mapping {
+    // A quoted key loses its quotes. Any other character Avro names can't hold becomes `_`,
+    // and so does a leading digit get a `_` before it, with a warning for the field.
+    pub fn sanitize(&self, record: &str, field: &mut Value) {
+        let name = field["name"].as_str().unwrap_or_default();
+        let name = name.trim_matches(|c| c == '"' || c == '\'').to_owned();
+        let mut sanitized: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if !sanitized.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            sanitized.insert(0, '_');
+        }
+        if sanitized != name {
+            self.sanitized.borrow_mut().push(Diagnostic {
+                record: record.to_owned(),
+                field: name,
+                message: format!("sanitized the name to {}, which Avro accepts", sanitized),
+            });
+        }
+        field["name"] = json!(sanitized);
+    }
+
+    pub fn apply(&self, record: &str, field: &mut Value, annotations: &[(String, String)]) {
+        let name = field["name"].as_str().unwrap_or_default().to_owned();
+        if !self.strip.is_empty() {
+            self.strip_inline(&mut field["type"]);
+        }
+        let step = |rule: &str, field: &Value| self.step(record, &name, rule, &field["type"]);
+        step("default mapping", field);
+
+        if self.types.is_object() {
+            let before = field["type"].clone();
+            map_types(&mut field["type"], &self.types);
+            if field["type"] != before {
+                step("type map", field);
+            }
+        }
+
+        if let Some((_, logical)) = annotations
+            .iter()
+            .find(|(tag, _)| tag == "avro.logicalType")
+        {
+            if let Some(ptype) = logical_type(logical) {
+                replace(
+                    &mut field["type"],
+                    &["string", "double", "long", "int"],
+                    ptype,
+                );
+            }
+            step(&format!("annotation @avro.logicalType {}", logical), field);
+        } else {
+            self.apply_heuristics(field, &name, &step);
+        }
+
+        if self.java_string {
+            java_strings(&mut field["type"]);
+            step("--java-string", field);
+        }
+
+        if self.evolution_safe && !annotations.iter().any(|(tag, _)| tag == "avro.key") {
+            evolution_safe(field);
+            step("--evolution-safe", field);
+        }
+
+        // `@avro.example "GB"` and `@faker internet.email` drive the generated sample data
+        for (tag, value) in annotations {
+            match tag.as_str() {
+                "avro.example" => {
+                    field["example"] = serde_json::from_str(value).unwrap_or_else(|_| json!(value))
+                }
+                "faker" => field["faker"] = json!(value),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn is_stripped(&self, field: &str) -> bool {
+        self.strip.iter().any(|pattern| glob(pattern, field))
+    }
+
+    // the properties of records declared inline in a field's type are stripped too
+    fn strip_inline(&self, ptype: &mut Value) {
+        match ptype {
+            Value::Array(members) => members.iter_mut().for_each(|x| self.strip_inline(x)),
+            Value::Object(object) => {
+                if let Some(Value::Array(fields)) = object.get_mut("fields") {
+                    fields.retain(|field| {
+                        !self.is_stripped(field["name"].as_str().unwrap_or_default())
+                    });
+                    for field in fields.iter_mut() {
+                        self.strip_inline(&mut field["type"]);
+                    }
+                }
+                for inner in ["items", "values"] {
+                    if let Some(inner) = object.get_mut(inner) {
+                        self.strip_inline(inner);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_heuristics(&self, field: &mut Value, name: &str, step: &dyn Fn(&str, &Value)) {
+        if self.infer_timestamps && is_timestamp_name(name) {
+            replace(
+                &mut field["type"],
+                &["string", "double"],
+                avro::timestamp_millis(),
+            );
+            step("heuristic --infer-timestamps", field);
+        }
+
+        if self.ids_as_long && is_id_name(name) {
+            replace(&mut field["type"], &["double"], json!("long"));
+            step("heuristic --ids-as-long", field);
+        }
+
+        if let Some(rule) = self.decimals.iter().find(|rule| glob(&rule.pattern, name)) {
+            let decimal = json!({
+                "type": "bytes",
+                "logicalType": "decimal",
+                "precision": rule.precision,
+                "scale": rule.scale
+            });
+            replace(&mut field["type"], &["double"], decimal);
+            step(&format!("decimal pattern {}", rule.pattern), field);
+        }
+    }
+
+    fn step(&self, record: &str, field: &str, rule: &str, ptype: &Value) {
+        if let Some(trace) = &self.trace {
+            trace.borrow_mut().push(Step {
+                record: record.to_owned(),
+                field: field.to_owned(),
+                rule: rule.to_owned(),
+                ptype: ptype.clone(),
+            });
+        }
+    }
+}
+
+// case-insensitive match where `*` stands for any run of characters
+pub fn glob(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let name = name.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+
+    let mut rest = name.as_str();
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(stripped) => rest = stripped,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(at) => rest = &rest[at + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    rest.is_empty()
+}
+
+// the field becomes nullable with a null default, and so do the fields of records declared
+// inline in its type
+fn evolution_safe(field: &mut Value) {
+    fn inline_records(ptype: &mut Value) {
+        match ptype {
+            Value::Array(members) => members.iter_mut().for_each(inline_records),
+            Value::Object(object) => {
+                if let Some(Value::Array(fields)) = object.get_mut("fields") {
+                    fields.iter_mut().for_each(evolution_safe);
+                }
+                for key in ["items", "values"] {
+                    if let Some(inner) = object.get_mut(key) {
+                        inline_records(inner);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    inline_records(&mut field["type"]);
+    field["type"] = avro::union(vec![json!("null"), field["type"].take()]);
+    field["default"] = Value::Null;
+}
+
+// the Java code generators otherwise read strings as `Utf8`
+fn java_strings(ptype: &mut Value) {
+    match ptype {
+        Value::String(name) if name == "string" => {
+            *ptype = json!({ "type": "string", "avro.java.string": "String" });
+        }
+        Value::Array(members) => members.iter_mut().for_each(java_strings),
+        Value::Object(object) => {
+            for key in ["items", "values"] {
+                if let Some(inner) = object.get_mut(key) {
+                    java_strings(inner);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn map_types(ptype: &mut Value, types: &Value) {
+    match ptype {
+        Value::String(name) => {
+            if let Some(mapped) = types.get(name.as_str()) {
+                *ptype = mapped.clone();
+            }
+        }
+        Value::Array(members) => {
+            for member in members.iter_mut() {
+                map_types(member, types);
+            }
+            *ptype = avro::union(members.clone());
+        }
+        Value::Object(object) => {
+            for key in ["items", "values"] {
+                if let Some(inner) = object.get_mut(key) {
+                    map_types(inner, types);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn logical_type(name: &str) -> Option<Value> {
+    let base = match name {
+        "timestamp-millis" | "timestamp-micros" | "local-timestamp-millis" => "long",
+        "date" | "time-millis" => "int",
+        "time-micros" => "long",
+        "uuid" => "string",
+        _ => return None,
+    };
+    Some(json!({ "type": base, "logicalType": name }))
+}
+
+// `createdAt`, `updated_at`, `eventTimestamp`
+fn is_timestamp_name(name: &str) -> bool {
+    let camel_at = name
+        .strip_suffix("At")
+        .and_then(|rest| rest.chars().last())
+        .is_some_and(|c| c.is_lowercase());
+    camel_at || name.ends_with("_at") || name.ends_with("Timestamp") || name == "timestamp"
+}
+
+// `id`, `userId`, `user_id`
+fn is_id_name(name: &str) -> bool {
+    let camel_id = name
+        .strip_suffix("Id")
+        .and_then(|rest| rest.chars().last())
+        .is_some_and(|c| c.is_lowercase());
+    camel_id || name == "id" || name.ends_with("_id")
+}
+
+// replaces the given primitives with `ptype`, in place of the type or of a union member
+pub fn replace(field_type: &mut Value, primitives: &[&str], ptype: Value) {
+    let is_target = |member: &Value| primitives.iter().any(|primitive| member == primitive);
+    let replaced = match &*field_type {
+        Value::Array(members) => avro::union(
+            members
+                .iter()
+                .map(|member| match is_target(member) {
+                    true => ptype.clone(),
+                    false => member.clone(),
+                })
+                .collect(),
+        ),
+        member if is_target(member) => ptype,
+        _ => return,
+    };
+    *field_type = replaced;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mapping::{decimal_rules, getter_field_name, glob, heuristics_applied, Mapping};
+    use crate::{config, get_schema_with, Grammar};
+    use serde_json::json;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_infer_timestamps() {
+        let code = r#"
+        interface Event {
+            createdAt: string;
+            updated_at?: number;
+            eventTimestamp: number;
+            chat: string;
+            /** @avro.logicalType none */
+            deletedAt: string;
+            /** @avro.logicalType timestamp-micros */
+            seenAt: number;
+        }
+        "#;
+        let mapping = Mapping {
+            infer_timestamps: true,
+            ..Mapping::default()
+        };
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        let fields = &schemas[0]["fields"];
+        let millis = json!({ "type": "long", "logicalType": "timestamp-millis" });
+
+        assert_eq!(fields[0]["type"], millis);
+        assert_eq!(fields[1]["type"], json!(["null", millis]));
+        assert_eq!(fields[2]["type"], millis);
+        assert_eq!(fields[3]["type"], "string");
+        assert_eq!(fields[4]["type"], "string");
+        assert_eq!(
+            fields[5]["type"],
+            json!({ "type": "long", "logicalType": "timestamp-micros" })
+        );
+    }
+
+    #[test]
+    fn test_ids_as_long() {
+        let code = r#"
+        interface Order {
+            id: number;
+            customerId?: number;
+            tenant_id: number;
+            externalId: string;
+            paid: number;
+        }
+        "#;
+        let mapping = Mapping {
+            ids_as_long: true,
+            ..Mapping::default()
+        };
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        let fields = &schemas[0]["fields"];
+
+        assert_eq!(fields[0]["type"], "long");
+        assert_eq!(fields[1]["type"], json!(["null", "long"]));
+        assert_eq!(fields[2]["type"], "long");
+        assert_eq!(fields[3]["type"], "string");
+        assert_eq!(fields[4]["type"], "double");
+    }
+
+    #[test]
+    fn test_heuristics_are_opt_in() {
+        let code = "interface Event { createdAt: string; id: number; }";
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &Mapping::default()).unwrap();
+
+        assert_eq!(schemas[0]["fields"][0]["type"], "string");
+        assert_eq!(schemas[0]["fields"][1]["type"], "double");
+    }
+
+    #[test]
+    fn test_evolution_safe() {
+        let code = r#"
+        interface Order {
+            /** @avro.key */
+            id: string;
+            total: number;
+            note?: string;
+            lines: { sku: string }[];
+        }
+        "#;
+        let mapping = Mapping {
+            evolution_safe: true,
+            ..Mapping::default()
+        };
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        let fields = &schemas[0]["fields"];
+
+        assert_eq!(fields[0], json!({ "name": "id", "type": "string" }));
+        assert_eq!(
+            fields[1],
+            json!({ "name": "total", "type": ["null", "double"], "default": null })
+        );
+        assert_eq!(fields[2]["type"], json!(["null", "string"]));
+        assert_eq!(fields[3]["default"], json!(null));
+        let lines = schemas.iter().find(|x| x["name"] == "OrderLines").unwrap();
+        assert_eq!(
+            lines["fields"][0],
+            json!({ "name": "sku", "type": ["null", "string"], "default": null })
+        );
+    }
+
+    #[test]
+    fn test_type_map() {
+        let code = r#"
+        interface Order {
+            _id: ObjectId;
+            placedAt?: Dayjs;
+            lines: Map<string, Decimal>;
+            customer: Customer;
+        }
+        "#;
+        let types = config::parse(
+            r#"
+            ObjectId = "string"
+            Dayjs = { type = "long", logicalType = "timestamp-millis" }
+            Decimal = { type = "bytes", logicalType = "decimal", precision = 18, scale = 4 }
+            "#,
+        )
+        .unwrap();
+        let mapping = Mapping {
+            types,
+            ..Mapping::default()
+        };
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        let fields = &schemas[0]["fields"];
+
+        assert_eq!(fields[0]["type"], "string");
+        assert_eq!(
+            fields[1]["type"],
+            json!(["null", { "type": "long", "logicalType": "timestamp-millis" }])
+        );
+        assert_eq!(fields[2]["type"]["values"]["logicalType"], "decimal");
+        assert_eq!(fields[2]["type"]["values"]["precision"], 18);
+        assert_eq!(fields[3]["type"], "Customer");
+    }
+
+    #[test]
+    fn test_java_string() {
+        let code = r#"
+        interface Person {
+            name: string;
+            nickname?: string;
+            tags: string[];
+            age: number;
+        }
+        "#;
+        let mapping = Mapping {
+            java_string: true,
+            ..Mapping::default()
+        };
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        let fields = &schemas[0]["fields"];
+        let string = json!({ "type": "string", "avro.java.string": "String" });
+
+        assert_eq!(fields[0]["type"], string);
+        assert_eq!(fields[1]["type"], json!(["null", string]));
+        assert_eq!(fields[2]["type"]["items"], string);
+        assert_eq!(fields[3]["type"], "double");
+    }
+
+    #[test]
+    fn test_sample_annotations() {
+        let code = r#"
+        interface Customer {
+            /** @faker internet.email */
+            email: string;
+            /** @avro.example "GB" */
+            country: string;
+            /** @avro.example 42 */
+            age: number;
+            /** @avro.example not json */
+            note: string;
+        }
+        "#;
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &Mapping::default()).unwrap();
+        let fields = &schemas[0]["fields"];
+
+        assert_eq!(fields[0]["faker"], "internet.email");
+        assert_eq!(fields[1]["example"], "GB");
+        assert_eq!(fields[2]["example"], 42);
+        assert_eq!(fields[3]["example"], "not json");
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(glob("*amount*", "totalAmount"));
+        assert!(glob("*amount*", "amount"));
+        assert!(glob("price", "Price"));
+        assert!(glob("*_cents", "total_cents"));
+        assert!(!glob("*_cents", "cents_total"));
+        assert!(!glob("price", "prices"));
+    }
+
+    #[test]
+    fn test_decimal_rules() {
+        let code = r#"
+        interface Invoice {
+            totalAmount: number;
+            amountLabel: string;
+            /** @avro.logicalType none */
+            amountIndex: number;
+            price?: number;
+            count: number;
+        }
+        "#;
+        let config = config::parse(
+            r#"
+            [[mapping.decimals]]
+            pattern = "*amount*"
+            precision = 18
+            scale = 4
+
+            [[mapping.decimals]]
+            pattern = "price"
+            precision = 10
+            scale = 2
+            "#,
+        )
+        .unwrap();
+        let mapping = Mapping {
+            decimals: decimal_rules(&config["mapping"]).unwrap(),
+            ..Mapping::default()
+        };
+
+        let schemas = get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        let fields = &schemas[0]["fields"];
+
+        assert_eq!(
+            fields[0]["type"],
+            json!({ "type": "bytes", "logicalType": "decimal", "precision": 18, "scale": 4 })
+        );
+        assert_eq!(fields[1]["type"], "string");
+        assert_eq!(fields[2]["type"], "double");
+        assert_eq!(fields[3]["type"][1]["precision"], 10);
+        assert_eq!(fields[4]["type"], "double");
+    }
+
+    #[test]
+    fn test_invalid_decimal_rule() {
+        let config = config::parse(
+            "[[mapping.decimals]]\npattern = \"*amount*\"\nprecision = 2\nscale = 4\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            decimal_rules(&config["mapping"]),
+            Err("mapping.decimals *amount*: scale 4 exceeds precision 2".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_heuristics_applied() {
+        let code = r#"
+        interface Event {
+            createdAt: string;
+            updatedAt: boolean;
+            id: number;
+        }
+        "#;
+        let mapping = Mapping {
+            infer_timestamps: true,
+            ids_as_long: true,
+            trace: Some(RefCell::new(Vec::new())),
+            ..Mapping::default()
+        };
+
+        get_schema_with(code, Grammar::TypeScript, &mapping).unwrap();
+        let steps = mapping.trace.unwrap().into_inner();
+
+        assert_eq!(
+            heuristics_applied(&steps).into_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    "Event".to_owned(),
+                    "createdAt".to_owned(),
+                    "heuristic --infer-timestamps".to_owned()
+                ),
+                (
+                    "Event".to_owned(),
+                    "id".to_owned(),
+                    "heuristic --ids-as-long".to_owned()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_getter_field_name() {
+        assert_eq!(getter_field_name("getName"), "name");
+        assert_eq!(getter_field_name("getURL"), "uRL");
+        assert_eq!(getter_field_name("getaway"), "getaway");
+        assert_eq!(getter_field_name("isActive"), "isActive");
+    }
+}
@@ -0,0 +1,146 @@
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+// With `--namespace-from-path src/models`, `src/models/billing/invoice.ts` declares its
+// records in the `billing` namespace, under the configured `namespace` when there is one.
+// Files outside the base directory only get the configured namespace.
+pub fn from_path(base: &Path, file: &Path, prefix: Option<&str>) -> Option<String> {
+    let base = fs::canonicalize(base).unwrap_or(base.to_path_buf());
+    let directory = file.parent().unwrap_or(Path::new(""));
+    let directory = fs::canonicalize(directory).unwrap_or(directory.to_path_buf());
+
+    let mut parts: Vec<String> = prefix
+        .into_iter()
+        .filter(|prefix| !prefix.is_empty())
+        .map(|prefix| prefix.to_owned())
+        .collect();
+    if let Ok(relative) = directory.strip_prefix(&base) {
+        parts.extend(
+            relative
+                .components()
+                .map(|part| identifier(&part.as_os_str().to_string_lossy())),
+        );
+    }
+
+    match parts.is_empty() {
+        true => None,
+        false => Some(parts.join(".")),
+    }
+}
+
+// namespace parts follow the Avro name rules, so `user-events` becomes `user_events`
+fn identifier(part: &str) -> String {
+    let mut identifier: String = part
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+// Expands `--namespace-template com.acme.{dir}` for a file: `{dir}` is the directory the
+// file is in and `{file}` its name without the extension, both as Avro names.
+pub fn from_template(template: &str, file: &Path) -> String {
+    let directory = file.parent().unwrap_or(Path::new(""));
+    let directory = fs::canonicalize(directory).unwrap_or(directory.to_path_buf());
+    let name = |path: &Path| {
+        path.file_name()
+            .map(|name| identifier(&name.to_string_lossy()))
+            .unwrap_or_default()
+    };
+    let stem = file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('.').next())
+        .map(identifier)
+        .unwrap_or_default();
+    let expanded = template
+        .replace("{dir}", &name(&directory))
+        .replace("{file}", &stem);
+    let parts: Vec<&str> = expanded
+        .split('.')
+        .filter(|part| !part.is_empty())
+        .collect();
+    parts.join(".")
+}
+
+// sets the namespace of every record and enum that doesn't declare its own
+pub fn apply(schemas: &mut [Value], namespace: &str) {
+    for schema in schemas.iter_mut() {
+        if schema.get("namespace").is_none() {
+            schema["namespace"] = json!(namespace);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::namespace::{apply, from_path, from_template};
+    use crate::{get_schema, Grammar};
+    use serde_json::json;
+    use std::path::Path;
+
+    #[test]
+    fn test_from_path() {
+        let base = Path::new("src/models");
+
+        assert_eq!(
+            from_path(base, Path::new("src/models/billing/invoice.ts"), None),
+            Some("billing".to_owned())
+        );
+        assert_eq!(
+            from_path(
+                base,
+                Path::new("src/models/billing/user-events/invoice.ts"),
+                Some("com.acme")
+            ),
+            Some("com.acme.billing.user_events".to_owned())
+        );
+        assert_eq!(
+            from_path(base, Path::new("src/models/invoice.ts"), None),
+            None
+        );
+        assert_eq!(
+            from_path(base, Path::new("lib/invoice.ts"), Some("com.acme")),
+            Some("com.acme".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let code = r#"
+        interface Invoice {
+            total: number;
+        }
+
+        enum Status {
+            Open,
+        }
+        "#;
+        let mut schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        schemas[1]["namespace"] = json!("com.acme.shared");
+
+        apply(&mut schemas, "com.acme.billing");
+
+        assert_eq!(schemas[0]["namespace"], "com.acme.billing");
+        assert_eq!(schemas[1]["namespace"], "com.acme.shared");
+    }
+
+    #[test]
+    fn test_from_template() {
+        assert_eq!(
+            from_template(
+                "com.acme.{dir}.{file}",
+                Path::new("src/models/billing/user-invoice.ts")
+            ),
+            "com.acme.billing.user_invoice"
+        );
+        assert_eq!(
+            from_template("com.acme.{file}", Path::new("order.d.ts")),
+            "com.acme.order"
+        );
+    }
+}
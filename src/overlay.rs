@@ -0,0 +1,106 @@
+use crate::interactive;
+use serde_json::Value;
+use std::fs;
+
+// An overlay holds what has no natural place in the TS source, keyed by record or
+// `Record.field`:
+//
+//   {
+//     "Order": { "x-owner": "payments" },
+//     "Order.total": { "doc": "Gross, in the order currency", "default": 0 }
+//   }
+//
+// Each entry is merged into the generated declaration, objects key by key.
+pub fn load(path: &str) -> Result<Value, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Error opening the overlay {}: {}", path, err))?;
+    let overlay: Value =
+        serde_json::from_str(&contents).map_err(|err| format!("{}: {}", path, err))?;
+    match overlay.is_object() {
+        true => Ok(overlay),
+        false => Err(format!("{}: expected an object keyed by record", path)),
+    }
+}
+
+// an entry that matches nothing is an error, so that a renamed field doesn't silently lose
+// what the overlay said about it
+pub fn apply(schemas: &mut [Value], overlay: &Value) -> Result<(), String> {
+    for (path, entry) in overlay.as_object().into_iter().flatten() {
+        let (record, field) = match path.split_once('.') {
+            Some((record, field)) => (record, Some(field)),
+            None => (path.as_str(), None),
+        };
+        let schema = schemas
+            .iter_mut()
+            .find(|schema| schema["name"] == record)
+            .ok_or(format!("overlay {}: no record {}", path, record))?;
+        let target = match field {
+            None => schema,
+            Some(field) => schema["fields"]
+                .as_array_mut()
+                .and_then(|fields| fields.iter_mut().find(|x| x["name"] == field))
+                .ok_or(format!(
+                    "overlay {}: {} has no field {}",
+                    path, record, field
+                ))?,
+        };
+        if !entry.is_object() {
+            return Err(format!("overlay {}: expected an object", path));
+        }
+        interactive::merge(target, entry);
+        // a union's default is of its first member
+        if let (Some(default), Value::Array(members)) = (entry.get("default"), &mut target["type"])
+        {
+            if !default.is_null() && members[0] == "null" {
+                members.rotate_left(1);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::overlay::apply;
+    use serde_json::json;
+
+    #[test]
+    fn test_overlay() {
+        let mut schemas = vec![json!({
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                { "name": "id", "type": "string" },
+                { "name": "total", "type": ["null", "double"], "default": null }
+            ]
+        })];
+        let overlay = json!({
+            "Order": { "doc": "A placed order", "x-owner": { "team": "payments" } },
+            "Order.total": { "doc": "Gross", "default": 0 }
+        });
+
+        apply(&mut schemas, &overlay).unwrap();
+        assert_eq!(
+            schemas[0],
+            json!({
+                "type": "record",
+                "name": "Order",
+                "doc": "A placed order",
+                "x-owner": { "team": "payments" },
+                "fields": [
+                    { "name": "id", "type": "string" },
+                    { "name": "total", "type": ["double", "null"], "default": 0, "doc": "Gross" }
+                ]
+            })
+        );
+
+        assert_eq!(
+            apply(&mut schemas, &json!({ "Order.amount": { "doc": "x" } })),
+            Err("overlay Order.amount: Order has no field amount".to_owned())
+        );
+        assert_eq!(
+            apply(&mut schemas, &json!({ "Invoice": {} })),
+            Err("overlay Invoice: no record Invoice".to_owned())
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use serde_json::Value;
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+// A plugin is a command the resolved schema is piped through before it's written: it reads
+// the schema JSON on stdin and prints the schema to use instead, e.g. to add the custom
+// properties an organization needs or to rewrite types. Plugins are given with `--plugin`,
+// or in the config as
+//
+//   plugins = ["node plugins/add-owner.js", "./plugins/pii"]
+//
+// and run in that order, each on the output of the one before. A command is run by `sh -c`,
+// so quoting, variables and pipes work as they would in a shell.
+pub fn plugins(given: &[String], config: &Value) -> Vec<String> {
+    let configured = config["plugins"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_owned);
+    configured.chain(given.iter().cloned()).collect()
+}
+
+pub fn run(plugin: &str, schema: Value) -> Result<Value, String> {
+    if plugin.trim().is_empty() {
+        return Err("A plugin can't be an empty command".to_owned());
+    }
+    let mut child = Command::new("sh")
+        .args(["-c", plugin])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to run the plugin {}: {}", plugin, err))?;
+    // written from a thread of its own while the output is read, as a plugin that streams,
+    // such as `cat`, blocks once the pipe buffers fill; stdin is closed once it's written
+    let mut stdin = child.stdin.take().ok_or("The plugin's stdin isn't piped")?;
+    let input = schema.to_string();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed to run the plugin {}: {}", plugin, err))?;
+    // a plugin that exits without reading is reported by its exit status below
+    match writer.join() {
+        Ok(Err(err)) if err.kind() != ErrorKind::BrokenPipe => {
+            return Err(format!("Failed to write to the plugin {}: {}", plugin, err))
+        }
+        Err(_) => return Err(format!("Failed to write to the plugin {}", plugin)),
+        _ => {}
+    }
+    if !output.status.success() {
+        let message = format!(
+            "The plugin {} failed with {}\n{}",
+            plugin,
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return Err(message.trim_end().to_owned());
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("The plugin {} didn't print a schema: {}", plugin, err))
+}
+
+pub fn run_all(plugins: &[String], schema: Value) -> Result<Value, String> {
+    plugins
+        .iter()
+        .try_fold(schema, |schema, plugin| run(plugin, schema))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plugin::{plugins, run, run_all};
+    use serde_json::json;
+
+    #[test]
+    fn test_plugins() {
+        let config = crate::config::parse("plugins = [\"cat\"]\n").unwrap();
+        let plugins = plugins(&["sed s/Order/Purchase/".to_owned()], &config);
+        assert_eq!(plugins, vec!["cat", "sed s/Order/Purchase/"]);
+
+        let schema = json!({ "type": "record", "name": "Order", "fields": [] });
+        assert_eq!(
+            run_all(&plugins, schema.clone()),
+            Ok(json!({ "type": "record", "name": "Purchase", "fields": [] }))
+        );
+        assert_eq!(
+            run("false", schema.clone()),
+            Err("The plugin false failed with exit status: 1".to_owned())
+        );
+        // more than the pipe buffers hold, so that a streaming plugin has to be read from
+        // while it's written to
+        let fields: Vec<_> = (0..5000)
+            .map(|i| json!({ "name": format!("field{}", i), "type": "string" }))
+            .collect();
+        let large = json!({ "type": "record", "name": "Order", "fields": fields });
+        assert!(large.to_string().len() > 1 << 17);
+        assert_eq!(run("cat", large.clone()), Ok(large));
+        assert_eq!(
+            run("sed 's/\"Order\"/\"Sales Order\"/' | cat", schema.clone()),
+            Ok(json!({ "type": "record", "name": "Sales Order", "fields": [] }))
+        );
+        assert!(run(" ", schema.clone()).is_err());
+        assert!(run("echo not json", schema)
+            .unwrap_err()
+            .starts_with("The plugin echo not json didn't print a schema"));
+    }
+}
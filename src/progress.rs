@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+const WIDTH: usize = 20;
+
+// Per-file progress for directory conversions, with the time spent in each phase.
+pub struct Progress {
+    total: usize,
+    done: usize,
+    enabled: bool,
+    started: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Progress {
+    pub fn new(total: usize, enabled: bool) -> Progress {
+        Progress {
+            total,
+            done: 0,
+            enabled,
+            started: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    pub fn file(&mut self, path: &str, status: &str) {
+        self.done += 1;
+        if self.enabled {
+            eprintln!("{}", self.line(path, status));
+        }
+    }
+
+    // adds the time since `since` to the phase
+    pub fn time(&mut self, phase: &'static str, since: Instant) {
+        let elapsed = since.elapsed();
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((phase, elapsed)),
+        }
+    }
+
+    pub fn finish(&self) {
+        if self.enabled {
+            eprintln!("{}", self.breakdown(self.started.elapsed()));
+        }
+    }
+
+    fn line(&self, path: &str, status: &str) -> String {
+        let filled = (self.done * WIDTH).checked_div(self.total).unwrap_or(WIDTH);
+        let digits = self.total.to_string().len();
+        format!(
+            "[{}{}] {:>digits$}/{} {} {}",
+            "#".repeat(filled),
+            " ".repeat(WIDTH - filled),
+            self.done,
+            self.total,
+            path,
+            status,
+            digits = digits
+        )
+    }
+
+    fn breakdown(&self, elapsed: Duration) -> String {
+        let phases: Vec<String> = self
+            .phases
+            .iter()
+            .map(|(name, duration)| format!("{} {:.2}s", name, duration.as_secs_f64()))
+            .collect();
+        format!(
+            "Converted {} files in {:.2}s ({})",
+            self.done,
+            elapsed.as_secs_f64(),
+            phases.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::progress::Progress;
+    use std::time::Duration;
+
+    #[test]
+    fn test_progress() {
+        let mut progress = Progress::new(12, false);
+        progress.file("src/a.ts", "ok");
+        progress.file("src/b.ts", "ok");
+        progress.file("src/c.ts", "1 syntax error");
+
+        assert_eq!(
+            progress.line("src/c.ts", "1 syntax error"),
+            "[#####               ]  3/12 src/c.ts 1 syntax error"
+        );
+
+        progress.phases = vec![
+            ("read", Duration::from_millis(120)),
+            ("parse", Duration::from_millis(1500)),
+        ];
+        assert_eq!(
+            progress.breakdown(Duration::from_millis(1700)),
+            "Converted 3 files in 1.70s (read 0.12s, parse 1.50s)"
+        );
+    }
+}
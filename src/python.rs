@@ -0,0 +1,288 @@
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet};
+
+// Python models of resolved schemas: a dataclass per record, or a Pydantic model with
+// `pydantic`, and an `Enum` per enum. Records come before the records using them, and the
+// annotations are postponed so that a record can contain itself.
+pub fn module(schemas: &[Value], pydantic: bool) -> String {
+    let mut models = Models {
+        pydantic,
+        ..Models::default()
+    };
+    for schema in schemas {
+        models.python_type(schema);
+    }
+
+    let mut out = String::from("# Generated by ts-to-avro, do not edit.\n\n");
+    out.push_str("from __future__ import annotations\n\n");
+    if !pydantic {
+        out.push_str("from dataclasses import dataclass, field\n");
+    }
+    if models.uses_enum {
+        out.push_str("from enum import Enum\n");
+    }
+    let typing: Vec<&str> = models.typing.iter().map(|x| x.as_str()).collect();
+    if !typing.is_empty() {
+        out.push_str(&format!("from typing import {}\n", typing.join(", ")));
+    }
+    if pydantic {
+        out.push_str("\nfrom pydantic import BaseModel, Field\n");
+    }
+    for item in &models.items {
+        out.push_str("\n\n");
+        out.push_str(item);
+    }
+    out
+}
+
+#[derive(Default)]
+struct Models {
+    pydantic: bool,
+    items: Vec<String>,
+    defined: HashSet<String>,
+    typing: BTreeSet<String>,
+    uses_enum: bool,
+}
+
+impl Models {
+    fn python_type(&mut self, ptype: &Value) -> String {
+        match ptype {
+            Value::String(name) => match name.as_str() {
+                "null" => "None".to_owned(),
+                "boolean" => "bool".to_owned(),
+                "int" | "long" => "int".to_owned(),
+                "float" | "double" => "float".to_owned(),
+                "bytes" => "bytes".to_owned(),
+                "string" => "str".to_owned(),
+                name => name.rsplit('.').next().unwrap_or(name).to_owned(),
+            },
+            Value::Array(members) => {
+                let nullable = members.iter().any(|member| member == "null");
+                let others: Vec<String> = members
+                    .iter()
+                    .filter(|member| *member != "null")
+                    .map(|member| self.python_type(member))
+                    .collect();
+                let inner = match others.as_slice() {
+                    [] => return "None".to_owned(),
+                    [only] => only.to_owned(),
+                    _ => {
+                        self.typing.insert("Union".to_owned());
+                        format!("Union[{}]", others.join(", "))
+                    }
+                };
+                match nullable {
+                    true => {
+                        self.typing.insert("Optional".to_owned());
+                        format!("Optional[{}]", inner)
+                    }
+                    false => inner,
+                }
+            }
+            Value::Object(object) => {
+                let kind = object
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                match kind.to_ascii_lowercase().as_str() {
+                    "record" => self.record(ptype),
+                    "enum" => self.enumeration(ptype),
+                    "fixed" => "bytes".to_owned(),
+                    "array" => {
+                        self.typing.insert("List".to_owned());
+                        format!("List[{}]", self.python_type(&ptype["items"]))
+                    }
+                    "map" => {
+                        self.typing.insert("Dict".to_owned());
+                        format!("Dict[str, {}]", self.python_type(&ptype["values"]))
+                    }
+                    // a logical type is written as the type underneath
+                    _ => self.python_type(&ptype["type"]),
+                }
+            }
+            _ => {
+                self.typing.insert("Any".to_owned());
+                "Any".to_owned()
+            }
+        }
+    }
+
+    fn record(&mut self, schema: &Value) -> String {
+        let name = schema["name"].as_str().unwrap_or("Anonymous").to_owned();
+        if !self.defined.insert(name.clone()) {
+            return name;
+        }
+        let mut fields = Vec::new();
+        for field in schema["fields"].as_array().into_iter().flatten() {
+            let field_name = field["name"].as_str().unwrap_or_default();
+            let python_type = self.python_type(&field["type"]);
+            fields.push((
+                field_name,
+                python_type,
+                field.get("default").and_then(default),
+            ));
+        }
+
+        let mut item = match self.pydantic {
+            true => format!("class {}(BaseModel):\n", name),
+            false => format!("@dataclass\nclass {}:\n", name),
+        };
+        if let Some(doc) = schema["doc"].as_str() {
+            item.push_str(&format!(
+                "    \"\"\"{}\"\"\"\n\n",
+                doc.replace("\"\"\"", "\\\"\\\"\\\"")
+            ));
+        }
+        // dataclass fields with a default have to follow those without
+        fields.sort_by_key(|(_, _, default)| default.is_some());
+        for (field_name, python_type, default) in &fields {
+            let ident = identifier(field_name);
+            let alias = match (self.pydantic, ident != *field_name) {
+                (true, true) => Some(format!("Field(alias=\"{}\"", field_name)),
+                _ => None,
+            };
+            let value = match (alias, default) {
+                (Some(alias), Some(default)) => format!(" = {}, default={})", alias, default),
+                (Some(alias), None) => format!(" = {})", alias),
+                (None, Some(default)) if default == "[]" || default == "{}" => {
+                    match self.pydantic {
+                        true => format!(" = {}", default),
+                        false => {
+                            let factory = if default == "[]" { "list" } else { "dict" };
+                            format!(" = field(default_factory={})", factory)
+                        }
+                    }
+                }
+                (None, Some(default)) => format!(" = {}", default),
+                (None, None) => String::new(),
+            };
+            item.push_str(&format!("    {}: {}{}\n", ident, python_type, value));
+        }
+        if fields.is_empty() {
+            item.push_str("    pass\n");
+        }
+        self.items.push(item);
+        name
+    }
+
+    fn enumeration(&mut self, schema: &Value) -> String {
+        let name = schema["name"].as_str().unwrap_or("Anonymous").to_owned();
+        if !self.defined.insert(name.clone()) {
+            return name;
+        }
+        self.uses_enum = true;
+        let mut item = format!("class {}(str, Enum):\n", name);
+        for symbol in schema["symbols"].as_array().into_iter().flatten() {
+            let symbol = symbol.as_str().unwrap_or_default();
+            item.push_str(&format!("    {} = \"{}\"\n", identifier(symbol), symbol));
+        }
+        self.items.push(item);
+        name
+    }
+}
+
+// a JSON default as a Python literal, none for a value Python can't spell simply
+fn default(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some("None".to_owned()),
+        Value::Bool(true) => Some("True".to_owned()),
+        Value::Bool(false) => Some("False".to_owned()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(_) => Some(value.to_string()),
+        Value::Array(items) if items.is_empty() => Some("[]".to_owned()),
+        Value::Object(entries) if entries.is_empty() => Some("{}".to_owned()),
+        _ => None,
+    }
+}
+
+const KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+fn identifier(name: &str) -> String {
+    let ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match ident.as_str() {
+        "" => "field_".to_owned(),
+        ident if KEYWORDS.contains(&ident) => format!("{}_", ident),
+        ident if ident.starts_with(|c: char| c.is_ascii_digit()) => format!("_{}", ident),
+        _ => ident,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::python::module;
+    use serde_json::json;
+
+    fn schema() -> serde_json::Value {
+        json!({
+            "type": "record",
+            "name": "Order",
+            "doc": "A placed order",
+            "fields": [
+                { "name": "note", "type": ["null", "string"], "default": null },
+                { "name": "id", "type": "string" },
+                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["Open", "Closed"] } },
+                { "name": "lines", "type": { "type": "array", "items": {
+                    "type": "record", "name": "Line", "fields": [{ "name": "sku", "type": "string" }]
+                } }, "default": [] },
+                { "name": "from", "type": { "type": "long", "logicalType": "timestamp-millis" } },
+                { "name": "parent", "type": ["null", "Order"] }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_dataclasses() {
+        assert_eq!(
+            module(&[schema()], false),
+            r#"# Generated by ts-to-avro, do not edit.
+
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import List, Optional
+
+
+class Status(str, Enum):
+    Open = "Open"
+    Closed = "Closed"
+
+
+@dataclass
+class Line:
+    sku: str
+
+
+@dataclass
+class Order:
+    """A placed order"""
+
+    id: str
+    status: Status
+    from_: int
+    parent: Optional[Order]
+    note: Optional[str] = None
+    lines: List[Line] = field(default_factory=list)
+"#
+        );
+    }
+
+    #[test]
+    fn test_pydantic() {
+        let module = module(&[schema()], true);
+
+        assert!(module.contains("\nfrom pydantic import BaseModel, Field\n"));
+        assert!(module.contains("class Order(BaseModel):\n"));
+        assert!(module.contains("    from_: int = Field(alias=\"from\")\n"));
+        assert!(module.contains("    lines: List[Line] = []\n"));
+        assert!(!module.contains("dataclass"));
+    }
+}
@@ -0,0 +1,826 @@
+use crate::http;
+use crate::symbols::{self, Symbols};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+
+// A Confluent-compatible schema registry, `--registry <url>` or `[registry] url`.
+pub struct Registry {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub client: http::Client,
+}
+
+// A registered version of a subject, with the global id messages written with it carry
+#[derive(Debug, PartialEq)]
+pub struct Registered {
+    pub version: u64,
+    pub id: u64,
+    pub schema: Value,
+}
+
+// The registry flags, each overriding its environment variable, which overrides the
+// `[registry]` key of the same name: `url`, `user_info` as `user:password`, `token`,
+// `cert`, `key` and `ca` for mutual TLS, `timeout` in seconds and `retries` for flaky
+// networks, and `allow_http` to send credentials over plain HTTP anyway. `HTTP_PROXY`,
+// `HTTPS_PROXY` and `NO_PROXY` are honoured.
+#[derive(Debug, Default)]
+pub struct Options {
+    pub url: Option<String>,
+    pub user_info: Option<String>,
+    pub token: Option<String>,
+    pub cert: Option<String>,
+    pub key: Option<String>,
+    pub ca: Option<String>,
+    pub timeout: Option<String>,
+    pub retries: Option<String>,
+    pub allow_http: Option<String>,
+}
+
+const SETTINGS: [(&str, &str, &str); 9] = [
+    ("--registry", "SCHEMA_REGISTRY_URL", "url"),
+    ("--registry-user", "SCHEMA_REGISTRY_USER_INFO", "user_info"),
+    ("--registry-token", "SCHEMA_REGISTRY_TOKEN", "token"),
+    ("--registry-cert", "SCHEMA_REGISTRY_CERT", "cert"),
+    ("--registry-key", "SCHEMA_REGISTRY_KEY", "key"),
+    ("--registry-ca", "SCHEMA_REGISTRY_CA", "ca"),
+    ("--registry-timeout", "SCHEMA_REGISTRY_TIMEOUT", "timeout"),
+    ("--registry-retries", "SCHEMA_REGISTRY_RETRIES", "retries"),
+    (
+        "--registry-allow-http",
+        "SCHEMA_REGISTRY_ALLOW_HTTP",
+        "allow_http",
+    ),
+];
+
+impl Options {
+    // takes the value of a registry flag, false for any other argument
+    pub fn flag<'a>(&mut self, arg: &str, mut values: impl Iterator<Item = &'a String>) -> bool {
+        let slot = match arg {
+            "--registry-allow-http" => {
+                self.allow_http = Some("true".to_owned());
+                return true;
+            }
+            "--registry" => &mut self.url,
+            "--registry-user" => &mut self.user_info,
+            "--registry-token" => &mut self.token,
+            "--registry-cert" => &mut self.cert,
+            "--registry-key" => &mut self.key,
+            "--registry-ca" => &mut self.ca,
+            "--registry-timeout" => &mut self.timeout,
+            "--registry-retries" => &mut self.retries,
+            _ => return false,
+        };
+        *slot = values.next().cloned();
+        true
+    }
+
+    fn get(&self, setting: &str) -> Option<&String> {
+        match setting {
+            "url" => self.url.as_ref(),
+            "user_info" => self.user_info.as_ref(),
+            "token" => self.token.as_ref(),
+            "cert" => self.cert.as_ref(),
+            "key" => self.key.as_ref(),
+            "ca" => self.ca.as_ref(),
+            "timeout" => self.timeout.as_ref(),
+            "allow_http" => self.allow_http.as_ref(),
+            _ => self.retries.as_ref(),
+        }
+    }
+}
+
+impl Registry {
+    pub fn new(options: &Options, config: &Value) -> Result<Registry, String> {
+        Registry::configure(options, config, |name| std::env::var(name).ok())
+    }
+
+    fn configure(
+        options: &Options,
+        config: &Value,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Registry, String> {
+        let setting = |key: &str| {
+            let (_, variable, _) = SETTINGS.iter().find(|(_, _, x)| *x == key)?;
+            options
+                .get(key)
+                .cloned()
+                .or_else(|| env(variable))
+                .or_else(|| match &config["registry"][key] {
+                    Value::String(value) => Some(value.to_owned()),
+                    Value::Number(value) => Some(value.to_string()),
+                    Value::Bool(value) => Some(value.to_string()),
+                    _ => None,
+                })
+        };
+        let url = setting("url")
+            .ok_or("No registry configured, pass --registry <url> or set [registry] url")?;
+        let mut headers = Vec::new();
+        if let Some(token) = setting("token") {
+            headers.push(("Authorization".to_owned(), format!("Bearer {}", token)));
+        } else if let Some(user_info) = setting("user_info") {
+            if !user_info.contains(':') {
+                return Err("[registry] user_info has to be user:password".to_owned());
+            }
+            headers.push((
+                "Authorization".to_owned(),
+                format!("Basic {}", base64(user_info.as_bytes())),
+            ));
+        }
+        let allow_http = setting("allow_http").is_some_and(|value| value == "true" || value == "1");
+        if !headers.is_empty() && !url.starts_with("https://") && !allow_http {
+            return Err(format!(
+                "[registry] refusing to send credentials to {} without TLS: use an https:// URL, or pass --registry-allow-http",
+                url
+            ));
+        }
+        let mut client = http::Client::default().proxy_from_env(&env);
+        client.tls = http::Tls {
+            cert: setting("cert"),
+            key: setting("key"),
+            ca: setting("ca"),
+        };
+        if let Some(key) = ["cert", "key", "ca"]
+            .into_iter()
+            .find(|key| setting(key).is_some() && !url.starts_with("https://"))
+        {
+            return Err(format!("[registry] {} needs an https:// registry URL", key));
+        }
+        if let Some(timeout) = setting("timeout") {
+            let seconds = timeout
+                .parse::<f64>()
+                .ok()
+                .filter(|seconds| *seconds > 0.0)
+                .ok_or(format!(
+                    "[registry] timeout has to be seconds, not {}",
+                    timeout
+                ))?;
+            client.timeout = std::time::Duration::from_secs_f64(seconds);
+        }
+        if let Some(retries) = setting("retries") {
+            client.retries = retries
+                .parse()
+                .map_err(|_| format!("[registry] retries has to be a count, not {}", retries))?;
+        }
+        Ok(Registry {
+            url: url.trim_end_matches('/').to_owned(),
+            headers,
+            client,
+        })
+    }
+
+    fn call(&self, method: &str, path: &str, body: Option<Value>) -> Result<(u16, Value), String> {
+        let body = body.map(|body| body.to_string());
+        let url = format!("{}{}", self.url, path);
+        let response = self
+            .client
+            .request(method, &url, &self.headers, body.as_deref())?;
+        let value = serde_json::from_str(&response.body).unwrap_or(Value::Null);
+        match response.status {
+            200..=299 | 404 => Ok((response.status, value)),
+            status => Err(format!(
+                "{} {} answered {}: {}",
+                method,
+                url,
+                status,
+                value["message"].as_str().unwrap_or(&response.body)
+            )),
+        }
+    }
+
+    // a subject registered for the first time is compatible with anything
+    pub fn is_compatible(
+        &self,
+        subject: &str,
+        schema: &Value,
+        references: &[Value],
+    ) -> Result<bool, String> {
+        let path = format!(
+            "/compatibility/subjects/{}/versions/latest",
+            encode(subject)
+        );
+        match self.call("POST", &path, Some(body(schema, references)))? {
+            (404, _) => Ok(true),
+            (_, answer) => Ok(answer["is_compatible"] == true),
+        }
+    }
+
+    pub fn subjects(&self) -> Result<Vec<String>, String> {
+        let (_, answer) = self.call("GET", "/subjects", None)?;
+        let subjects = answer
+            .as_array()
+            .ok_or(format!("{}/subjects didn't answer with a list", self.url))?;
+        Ok(subjects
+            .iter()
+            .filter_map(|subject| subject.as_str().map(str::to_owned))
+            .collect())
+    }
+
+    // the latest version of a subject with its schema, none before it is first registered
+    pub fn latest(&self, subject: &str) -> Result<Option<Registered>, String> {
+        let path = format!("/subjects/{}/versions/latest", encode(subject));
+        let answer = match self.call("GET", &path, None)? {
+            (404, _) => return Ok(None),
+            (_, answer) => answer,
+        };
+        // Protobuf and JSON Schema subjects say so, Avro ones leave the type out
+        if answer["schemaType"]
+            .as_str()
+            .is_some_and(|kind| kind != "AVRO")
+        {
+            return Err(format!(
+                "{} holds a {} schema, not Avro",
+                subject, answer["schemaType"]
+            ));
+        }
+        let schema = answer["schema"]
+            .as_str()
+            .and_then(|schema| serde_json::from_str(schema).ok())
+            .ok_or(format!("The latest version of {} has no schema", subject))?;
+        Ok(Some(Registered {
+            version: answer["version"].as_u64().unwrap_or_default(),
+            id: answer["id"].as_u64().unwrap_or_default(),
+            schema,
+        }))
+    }
+
+    // the level a subject's versions are checked at, the global one when it sets none
+    pub fn compatibility(&self, subject: &str) -> Result<Option<String>, String> {
+        let path = format!("/config/{}?defaultToGlobal=true", encode(subject));
+        match self.call("GET", &path, None)? {
+            (404, _) => Ok(None),
+            (_, answer) => Ok(answer["compatibilityLevel"].as_str().map(str::to_owned)),
+        }
+    }
+
+    pub fn set_compatibility(&self, subject: &str, level: &str) -> Result<(), String> {
+        let path = format!("/config/{}", encode(subject));
+        self.call("PUT", &path, Some(json!({ "compatibility": level })))?;
+        Ok(())
+    }
+
+    pub fn register(
+        &self,
+        subject: &str,
+        schema: &Value,
+        references: &[Value],
+    ) -> Result<u64, String> {
+        let path = format!("/subjects/{}/versions", encode(subject));
+        let (status, answer) = self.call("POST", &path, Some(body(schema, references)))?;
+        match (status, answer["id"].as_u64()) {
+            (200..=299, Some(id)) => Ok(id),
+            _ => Err(format!("Registering {} failed: {}", subject, answer)),
+        }
+    }
+}
+
+// Where a pulled schema goes under the output directory: `com/shop/Order.avsc` for a named
+// type in the `com.shop` namespace, the subject for anything else such as a `"string"` key.
+pub fn avsc_path(subject: &str, schema: &Value) -> PathBuf {
+    let mut path = PathBuf::new();
+    match schema["name"].as_str() {
+        Some(name) => {
+            let full = symbols::full_name(schema);
+            let mut parts: Vec<&str> = full.split('.').collect();
+            parts.pop();
+            path.extend(parts);
+            path.push(format!("{}.avsc", name.rsplit('.').next().unwrap_or(name)));
+        }
+        None => path.push(format!("{}.avsc", subject.replace(['/', '\\'], "_"))),
+    }
+    path
+}
+
+fn body(schema: &Value, references: &[Value]) -> Value {
+    let schema = schema.to_string();
+    match references.is_empty() {
+        true => json!({ "schema": schema }),
+        false => json!({ "schema": schema, "references": references }),
+    }
+}
+
+// Registers the records a record uses under their own subjects before it, so that it refers
+// to them by name with Confluent schema references instead of inlining them. Each is
+// registered once however many records use it.
+pub struct References<'a> {
+    registry: &'a Registry,
+    symbols: &'a Symbols<'a>,
+    map: &'a Value,
+    // the reference to each registered record by full name, with the names its schema defines
+    registered: HashMap<String, (Value, HashSet<String>)>,
+    visiting: Vec<String>,
+}
+
+impl<'a> References<'a> {
+    pub fn new(registry: &'a Registry, symbols: &'a Symbols<'a>, map: &'a Value) -> Self {
+        References {
+            registry,
+            symbols,
+            map,
+            registered: HashMap::new(),
+            visiting: Vec::new(),
+        }
+    }
+
+    // the schema of `record` with the references it needs, registering those that aren't yet
+    pub fn resolve(&mut self, record: &Value) -> Result<(Value, Vec<Value>), String> {
+        let full = symbols::full_name(record);
+        self.visiting.push(full.clone());
+        let mut references = Vec::new();
+        let mut defined = HashSet::new();
+        for dependency in self.dependencies(record) {
+            let name = symbols::full_name(dependency);
+            if self.visiting.contains(&name) {
+                return Err(format!(
+                    "{} and {} refer to each other, so they can't be registered apart",
+                    full, name
+                ));
+            }
+            if !self.registered.contains_key(&name) {
+                let (schema, nested) = self.resolve(dependency)?;
+                let subject = subject(dependency, self.map);
+                if !self.registry.is_compatible(&subject, &schema, &nested)? {
+                    return Err(format!("{} is incompatible with the latest version", name));
+                }
+                self.registry.register(&subject, &schema, &nested)?;
+                let version = self
+                    .registry
+                    .latest(&subject)?
+                    .ok_or(format!("{} was registered but has no versions", subject))?
+                    .version;
+                let mut defines = defines(&schema);
+                for reference in &nested {
+                    let name = reference["name"].as_str().unwrap_or_default();
+                    defines.extend(self.registered[name].1.iter().cloned());
+                }
+                let reference = json!({ "name": name, "subject": subject, "version": version });
+                self.registered.insert(name.clone(), (reference, defines));
+            }
+            let (reference, defines) = &self.registered[&name];
+            references.push(reference.clone());
+            defined.extend(defines.iter().cloned());
+        }
+        self.visiting.pop();
+        let schema = crate::resolve(record, self.symbols, "", &mut defined);
+        Ok((schema, references))
+    }
+
+    // the records a record's fields use, in the order they first appear
+    fn dependencies(&self, record: &Value) -> Vec<&'a Value> {
+        let namespace = record["namespace"].as_str().unwrap_or_default();
+        let mut found: Vec<&'a Value> = Vec::new();
+        let mut pending: Vec<&Value> = record["fields"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|field| &field["type"])
+            .collect();
+        pending.reverse();
+        while let Some(ptype) = pending.pop() {
+            match ptype {
+                Value::String(name) => match self.symbols.lookup(name, namespace) {
+                    Some((_, declaration))
+                        if declaration["type"] == "record"
+                            && !found.iter().any(|x| std::ptr::eq(*x, declaration)) =>
+                    {
+                        found.push(declaration)
+                    }
+                    _ => {}
+                },
+                Value::Array(members) => pending.extend(members.iter().rev()),
+                Value::Object(object) => {
+                    let fields = object.get("fields").and_then(Value::as_array);
+                    let inner = ["values", "items"]
+                        .into_iter()
+                        .filter_map(|key| object.get(key));
+                    pending.extend(inner);
+                    pending.extend(
+                        fields
+                            .into_iter()
+                            .flatten()
+                            .rev()
+                            .map(|field| &field["type"]),
+                    );
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+}
+
+// the full names of the types a resolved schema declares
+fn defines(schema: &Value) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut pending = vec![schema];
+    while let Some(ptype) = pending.pop() {
+        match ptype {
+            Value::Array(members) => pending.extend(members),
+            Value::Object(object) => {
+                if object.contains_key("name") {
+                    names.insert(symbols::full_name(ptype));
+                }
+                pending.extend(
+                    ["items", "values"]
+                        .into_iter()
+                        .filter_map(|key| object.get(key)),
+                );
+                let fields = object.get("fields").and_then(Value::as_array);
+                pending.extend(fields.into_iter().flatten().map(|field| &field["type"]));
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+// the subject map entry for a record's full or short name, or its full name
+fn subject(schema: &Value, map: &Value) -> String {
+    let full = symbols::full_name(schema);
+    map[&full]
+        .as_str()
+        .or(map[schema["name"].as_str().unwrap_or_default()].as_str())
+        .unwrap_or(&full)
+        .to_owned()
+}
+
+pub const LEVELS: [&str; 7] = [
+    "BACKWARD",
+    "BACKWARD_TRANSITIVE",
+    "FORWARD",
+    "FORWARD_TRANSITIVE",
+    "FULL",
+    "FULL_TRANSITIVE",
+    "NONE",
+];
+
+// The level `[compatibility]` wants for a subject, by subject or by the record's full or short
+// name, or its `default` key.
+pub fn level<'a>(
+    config: &'a Value,
+    subject: &str,
+    record: &Value,
+) -> Result<Option<&'a str>, String> {
+    let levels = &config["compatibility"];
+    let level = [
+        subject,
+        &symbols::full_name(record),
+        record["name"].as_str().unwrap_or_default(),
+        "default",
+    ]
+    .into_iter()
+    .find_map(|key| levels[key].as_str());
+    match level {
+        Some(level) if !LEVELS.contains(&level) => Err(format!(
+            "Unknown compatibility level {} for {}, expected one of {}",
+            level,
+            subject,
+            LEVELS.join(", ")
+        )),
+        level => Ok(level),
+    }
+}
+
+// Every public record with the subject it is registered under: the one its short or full
+// name maps to in the subject map, or its full name as with RecordNameStrategy.
+pub fn subjects(
+    schemas: &[Value],
+    private: &BTreeSet<String>,
+    map: &Value,
+) -> Vec<(String, Value)> {
+    let mut subjects: Vec<(String, Value)> = Vec::new();
+    for schema in schemas {
+        match schema["name"].as_str() {
+            Some(name) if schema["type"] == "record" && !private.contains(name) => name,
+            _ => continue,
+        };
+        let full = symbols::full_name(schema);
+        if !subjects.iter().any(|(_, x)| symbols::full_name(x) == full) {
+            subjects.push((subject(schema, map), schema.clone()));
+        }
+    }
+    subjects
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char),
+                false => out.push('='),
+            }
+        }
+    }
+    out
+}
+
+// subjects go in the URL path, where `/` and spaces have to be escaped
+fn encode(subject: &str) -> String {
+    subject
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::tests::serve;
+    use crate::registry::{
+        avsc_path, base64, encode, level, subjects, Options, References, Registered, Registry,
+    };
+    use crate::symbols::Symbols;
+    use crate::{config, get_schema, Grammar};
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_subjects() {
+        let schemas = get_schema(
+            "interface Order { id: string; }\ninterface Refund { id: string; }\ninterface Line { sku: string; }",
+            Grammar::TypeScript,
+        )
+        .unwrap();
+        let map = config::parse("Order = \"orders-value\"\n").unwrap();
+
+        let subjects = subjects(&schemas, &BTreeSet::from(["Line".to_owned()]), &map);
+
+        let names: Vec<&str> = subjects
+            .iter()
+            .map(|(subject, _)| subject.as_str())
+            .collect();
+        assert_eq!(names, vec!["orders-value", "Refund"]);
+        assert_eq!(encode("orders value/v1"), "orders%20value%2Fv1");
+    }
+
+    #[test]
+    fn test_register() {
+        let (url, received) = serve(vec![
+            (404, r#"{"error_code":40401,"message":"Subject not found"}"#),
+            (200, r#"{"id":7}"#),
+            (200, r#"{"is_compatible":false}"#),
+            (422, r#"{"error_code":42201,"message":"Invalid schema"}"#),
+        ]);
+        let registry = Registry::configure(
+            &Options {
+                url: Some(format!("{}/", url)),
+                ..Options::default()
+            },
+            &json!({}),
+            |_| None,
+        )
+        .unwrap();
+        let schema = json!({ "type": "record", "name": "Order", "fields": [] });
+
+        assert_eq!(
+            registry.is_compatible("orders-value", &schema, &[]),
+            Ok(true)
+        );
+        assert_eq!(registry.register("orders-value", &schema, &[]), Ok(7));
+        assert_eq!(
+            registry.is_compatible("orders-value", &schema, &[]),
+            Ok(false)
+        );
+        let err = registry.register("orders-value", &schema, &[]).unwrap_err();
+        assert!(err.ends_with("answered 422: Invalid schema"), "{}", err);
+
+        let received = received.lock().unwrap();
+        assert!(received[0]
+            .starts_with("POST /compatibility/subjects/orders-value/versions/latest HTTP/1.1"));
+        assert!(received[1].starts_with("POST /subjects/orders-value/versions HTTP/1.1"));
+        assert!(received[1]
+            .contains(r#"{"schema":"{\"fields\":[],\"name\":\"Order\",\"type\":\"record\"}"}"#));
+        assert!(received[0].contains(r#"\"type\":\"record\""#));
+    }
+
+    #[test]
+    fn test_latest() {
+        let (url, received) = serve(vec![
+            (
+                200,
+                r#"{"subject":"orders-value","version":3,"id":7,"schema":"{\"type\":\"record\",\"name\":\"Order\",\"fields\":[]}"}"#,
+            ),
+            (404, r#"{"error_code":40401,"message":"Subject not found"}"#),
+        ]);
+        let registry = Registry::configure(
+            &Options {
+                url: Some(url),
+                ..Options::default()
+            },
+            &json!({}),
+            |_| None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            registry.latest("orders-value"),
+            Ok(Some(Registered {
+                version: 3,
+                id: 7,
+                schema: json!({ "type": "record", "name": "Order", "fields": [] })
+            }))
+        );
+        assert_eq!(registry.latest("refunds-value"), Ok(None));
+        assert!(received.lock().unwrap()[0]
+            .starts_with("GET /subjects/orders-value/versions/latest HTTP/1.1"));
+    }
+
+    #[test]
+    fn test_authentication() {
+        let config = config::parse(
+            "[registry]\nurl = \"https://registry:8081\"\nuser_info = \"ci:secret\"\n",
+        )
+        .unwrap();
+
+        let registry = Registry::configure(&Options::default(), &config, |_| None).unwrap();
+        assert_eq!(registry.url, "https://registry:8081");
+        assert_eq!(
+            registry.headers,
+            vec![("Authorization".to_owned(), "Basic Y2k6c2VjcmV0".to_owned())]
+        );
+
+        let env = |name: &str| (name == "SCHEMA_REGISTRY_TOKEN").then(|| "t0k".to_owned());
+        let registry = Registry::configure(&Options::default(), &config, env).unwrap();
+        assert_eq!(registry.headers[0].1, "Bearer t0k");
+
+        let mut options = Options::default();
+        let values = ["http://other".to_owned()];
+        assert!(options.flag("--registry", values.iter()));
+        assert!(!options.flag("--all", values.iter()));
+        let err = Registry::configure(&options, &config, env).err().unwrap();
+        assert!(
+            err.starts_with("[registry] refusing to send credentials to http://other without TLS")
+        );
+        assert!(options.flag("--registry-allow-http", values.iter()));
+        let registry = Registry::configure(&options, &config, env).unwrap();
+        assert_eq!(registry.url, "http://other");
+        let allowed = config::parse("[registry]\nurl = \"http://registry\"\nallow_http = true\n");
+        let registry = Registry::configure(&Options::default(), &allowed.unwrap(), env).unwrap();
+        assert_eq!(registry.headers[0].1, "Bearer t0k");
+
+        options.timeout = Some("2.5".to_owned());
+        let registry = Registry::configure(&options, &config, env).unwrap();
+        assert_eq!(registry.client.timeout.as_millis(), 2500);
+        options.retries = Some("many".to_owned());
+        assert!(Registry::configure(&options, &config, env).is_err());
+
+        options.retries = None;
+        options.cert = Some("client.pem".to_owned());
+        let err = Registry::configure(&options, &config, env).err().unwrap();
+        assert_eq!(err, "[registry] cert needs an https:// registry URL");
+        options.url = None;
+        let registry = Registry::configure(&options, &config, env).unwrap();
+        assert_eq!(registry.client.tls.cert.as_deref(), Some("client.pem"));
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(base64(b"a"), "YQ==");
+    }
+
+    #[test]
+    fn test_references() {
+        let (url, received) = serve(vec![
+            (404, "{}"),
+            (200, r#"{"id":1}"#),
+            (200, r#"{"version":1,"schema":"{}"}"#),
+            (404, "{}"),
+            (200, r#"{"id":2}"#),
+            (200, r#"{"version":4,"schema":"{}"}"#),
+        ]);
+        let registry = Registry::configure(
+            &Options {
+                url: Some(url),
+                ..Options::default()
+            },
+            &json!({}),
+            |_| None,
+        )
+        .unwrap();
+        let code = r#"
+        interface Order { shipping: Address; billing: Address; origin: Country; }
+        interface Address { street: string; country: Country; }
+        interface Country { code: string; }
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let symbols = Symbols::new(&schemas);
+        let map = config::parse("Address = \"address\"\n").unwrap();
+
+        let (schema, references) = References::new(&registry, &symbols, &map)
+            .resolve(&schemas[0])
+            .unwrap();
+
+        assert_eq!(
+            references,
+            vec![
+                json!({ "name": "Address", "subject": "address", "version": 4 }),
+                json!({ "name": "Country", "subject": "Country", "version": 1 }),
+            ]
+        );
+        let types: Vec<&str> = schema["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, vec!["Address", "Address", "Country"]);
+        let received = received.lock().unwrap();
+        assert!(received[1].starts_with("POST /subjects/Country/versions"));
+        assert!(received[4].starts_with("POST /subjects/address/versions"));
+        assert!(received[4]
+            .contains(r#""references":[{"name":"Country","subject":"Country","version":1}]"#));
+        assert!(received[4].contains(r#"\"type\":\"Country\""#));
+    }
+
+    #[test]
+    fn test_compatibility_level() {
+        let config = config::parse(
+            "[compatibility]\ndefault = \"BACKWARD\"\norders-value = \"FULL_TRANSITIVE\"\nRefund = \"sideways\"\n",
+        )
+        .unwrap();
+        let record = |name: &str| json!({ "type": "record", "name": name, "fields": [] });
+
+        assert_eq!(
+            level(&config, "orders-value", &record("Order")),
+            Ok(Some("FULL_TRANSITIVE"))
+        );
+        assert_eq!(
+            level(&config, "Line", &record("Line")),
+            Ok(Some("BACKWARD"))
+        );
+        assert!(level(&config, "refunds", &record("Refund")).is_err());
+        assert_eq!(level(&json!({}), "Line", &record("Line")), Ok(None));
+
+        let (url, received) = serve(vec![
+            (200, r#"{"compatibilityLevel":"BACKWARD"}"#),
+            (200, r#"{"compatibility":"FULL"}"#),
+        ]);
+        let registry = Registry::configure(
+            &Options {
+                url: Some(url),
+                ..Options::default()
+            },
+            &json!({}),
+            |_| None,
+        )
+        .unwrap();
+        assert_eq!(
+            registry.compatibility("orders-value"),
+            Ok(Some("BACKWARD".to_owned()))
+        );
+        assert_eq!(registry.set_compatibility("orders-value", "FULL"), Ok(()));
+        let received = received.lock().unwrap();
+        assert!(received[0].starts_with("GET /config/orders-value?defaultToGlobal=true HTTP/1.1"));
+        assert!(received[1].starts_with("PUT /config/orders-value HTTP/1.1"));
+        assert!(received[1].ends_with(r#"{"compatibility":"FULL"}"#));
+    }
+
+    #[test]
+    fn test_pull() {
+        let (url, _) = serve(vec![
+            (200, r#"["orders-value","orders-key","events"]"#),
+            (
+                200,
+                r#"{"version":1,"id":4,"schemaType":"PROTOBUF","schema":"syntax = \"proto3\";"}"#,
+            ),
+        ]);
+        let registry = Registry::configure(
+            &Options {
+                url: Some(url),
+                ..Options::default()
+            },
+            &json!({}),
+            |_| None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            registry.subjects(),
+            Ok(vec![
+                "orders-value".to_owned(),
+                "orders-key".to_owned(),
+                "events".to_owned()
+            ])
+        );
+        let err = registry.latest("events").unwrap_err();
+        assert!(err.contains("PROTOBUF"), "{}", err);
+
+        let order =
+            json!({ "type": "record", "name": "Order", "namespace": "com.shop", "fields": [] });
+        assert_eq!(
+            avsc_path("orders-value", &order),
+            std::path::Path::new("com/shop/Order.avsc")
+        );
+        assert_eq!(
+            avsc_path("orders-key", &json!("string")),
+            std::path::Path::new("orders-key.avsc")
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::config;
+use serde_json::{json, Value};
+use std::fs;
+
+// renames.toml maps old names to new ones:
+//
+//   [records]
+//   OldPerson = "Person"
+//
+//   [fields.Person]
+//   name = "fullName"
+pub fn load(path: &str) -> Result<Value, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Error opening the renames file {}: {}", path, err))?;
+    config::parse(&contents).map_err(|err| format!("{}: {}", path, err))
+}
+
+// adds the old names as `aliases` so readers on the old schema keep resolving
+pub fn apply(schemas: &mut [Value], renames: &Value) {
+    for schema in schemas.iter_mut() {
+        let name = schema["name"].as_str().unwrap_or_default().to_owned();
+
+        for (old, new) in renames["records"].as_object().into_iter().flatten() {
+            if *new == name {
+                add_alias(schema, old);
+            }
+        }
+
+        let fields = &renames["fields"][&name];
+        if let Some(entries) = schema["fields"].as_array_mut() {
+            for entry in entries.iter_mut() {
+                for (old, new) in fields.as_object().into_iter().flatten() {
+                    if *new == entry["name"] {
+                        add_alias(entry, old);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn add_alias(value: &mut Value, alias: &str) {
+    if !value["aliases"].is_array() {
+        value["aliases"] = json!([]);
+    }
+    let aliases = value["aliases"].as_array_mut().unwrap();
+    if !aliases.iter().any(|x| x == alias) {
+        aliases.push(json!(alias));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config;
+    use crate::renames::apply;
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_apply_renames() {
+        let code = r#"
+        interface Person {
+            fullName: string;
+            age: number;
+        }
+        "#;
+        let renames = config::parse(
+            r#"
+            [records]
+            OldPerson = "Person"
+            Human = "Person"
+
+            [fields.Person]
+            name = "fullName"
+            "#,
+        )
+        .unwrap();
+
+        let mut schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        apply(&mut schemas, &renames);
+
+        assert_eq!(schemas[0]["aliases"][0], "Human");
+        assert_eq!(schemas[0]["aliases"][1], "OldPerson");
+        assert_eq!(schemas[0]["fields"][0]["aliases"][0], "name");
+        assert!(schemas[0]["fields"][1].get("aliases").is_none());
+    }
+}
@@ -0,0 +1,284 @@
+use serde_json::Value;
+use std::collections::HashSet;
+
+// Plain serde models of resolved schemas, without any Avro dependency: a struct per record, an
+// enum per enum, `Option<T>` for a union with null, `Box` where a record contains itself, and
+// an untagged enum named after the field for any other union.
+pub fn module(schemas: &[Value]) -> String {
+    let mut models = Models::default();
+    for schema in schemas {
+        models.rust_type(schema, "", "");
+    }
+    let mut out = String::from(
+        "// Generated by ts-to-avro, do not edit.\n\nuse serde::{Deserialize, Serialize};\n",
+    );
+    if models.uses_map {
+        out.push_str("use std::collections::HashMap;\n");
+    }
+    for item in &models.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+    out
+}
+
+const DERIVE: &str = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n";
+
+#[derive(Default)]
+struct Models {
+    items: Vec<String>,
+    defined: HashSet<String>,
+    // the records being written, which a field can only hold boxed
+    open: Vec<String>,
+    uses_map: bool,
+}
+
+impl Models {
+    fn rust_type(&mut self, ptype: &Value, owner: &str, field: &str) -> String {
+        match ptype {
+            Value::String(name) => match name.as_str() {
+                "null" => "()".to_owned(),
+                "boolean" => "bool".to_owned(),
+                "int" => "i32".to_owned(),
+                "long" => "i64".to_owned(),
+                "float" => "f32".to_owned(),
+                "double" => "f64".to_owned(),
+                "bytes" => "Vec<u8>".to_owned(),
+                "string" => "String".to_owned(),
+                name => {
+                    let short = pascal_case(name.rsplit('.').next().unwrap_or(name));
+                    match self.open.contains(&short) {
+                        true => format!("Box<{}>", short),
+                        false => short,
+                    }
+                }
+            },
+            Value::Array(members) => {
+                let nullable = members.iter().any(|member| member == "null");
+                let others: Vec<&Value> = members.iter().filter(|x| *x != "null").collect();
+                let inner = match others.as_slice() {
+                    [] => "()".to_owned(),
+                    [only] => self.rust_type(only, owner, field),
+                    _ => self.union(&others, owner, field),
+                };
+                match nullable && !others.is_empty() {
+                    true => format!("Option<{}>", inner),
+                    false => inner,
+                }
+            }
+            Value::Object(object) => {
+                let kind = object
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                match kind.to_ascii_lowercase().as_str() {
+                    "record" => self.record(ptype),
+                    "enum" => self.enumeration(ptype),
+                    "fixed" => "Vec<u8>".to_owned(),
+                    "array" => format!("Vec<{}>", self.rust_type(&ptype["items"], owner, field)),
+                    "map" => {
+                        self.uses_map = true;
+                        let values = self.rust_type(&ptype["values"], owner, field);
+                        format!("HashMap<String, {}>", values)
+                    }
+                    // a logical type is written as the type underneath
+                    _ => self.rust_type(&ptype["type"], owner, field),
+                }
+            }
+            _ => "serde_json::Value".to_owned(),
+        }
+    }
+
+    fn record(&mut self, schema: &Value) -> String {
+        let name = pascal_case(schema["name"].as_str().unwrap_or("Anonymous"));
+        if !self.defined.insert(name.clone()) {
+            return name;
+        }
+        self.open.push(name.clone());
+        let mut item = String::from(DERIVE);
+        item.push_str(&format!("pub struct {} {{\n", name));
+        for field in schema["fields"].as_array().into_iter().flatten() {
+            let field_name = field["name"].as_str().unwrap_or_default();
+            let rust_type = self.rust_type(&field["type"], &name, field_name);
+            let ident = snake_case(field_name);
+            if ident != field_name && !ident.starts_with("r#") {
+                item.push_str(&format!("    #[serde(rename = \"{}\")]\n", field_name));
+            }
+            item.push_str(&format!("    pub {}: {},\n", ident, rust_type));
+        }
+        item.push_str("}\n");
+        self.open.pop();
+        self.items.push(item);
+        name
+    }
+
+    fn enumeration(&mut self, schema: &Value) -> String {
+        let name = pascal_case(schema["name"].as_str().unwrap_or("Anonymous"));
+        if !self.defined.insert(name.clone()) {
+            return name;
+        }
+        let mut item = String::from(DERIVE);
+        item.push_str(&format!("pub enum {} {{\n", name));
+        for symbol in schema["symbols"].as_array().into_iter().flatten() {
+            let symbol = symbol.as_str().unwrap_or_default();
+            let variant = pascal_case(symbol);
+            if variant != symbol {
+                item.push_str(&format!("    #[serde(rename = \"{}\")]\n", symbol));
+            }
+            item.push_str(&format!("    {},\n", variant));
+        }
+        item.push_str("}\n");
+        self.items.push(item);
+        name
+    }
+
+    // `Order.payment: Card | string` becomes `enum OrderPayment { Card(Card), String(String) }`
+    fn union(&mut self, members: &[&Value], owner: &str, field: &str) -> String {
+        let name = format!("{}{}", owner, pascal_case(field));
+        let mut variants = Vec::new();
+        for member in members {
+            let rust_type = self.rust_type(member, owner, field);
+            let variant = match member {
+                Value::String(name) => pascal_case(name.rsplit('.').next().unwrap_or(name)),
+                _ => match member["name"].as_str() {
+                    Some(name) => pascal_case(name),
+                    None => pascal_case(member["type"].as_str().unwrap_or("Value")),
+                },
+            };
+            variants.push(format!("    {}({}),\n", variant, rust_type));
+        }
+        if self.defined.insert(name.clone()) {
+            let mut item = String::from(DERIVE);
+            item.push_str(&format!("#[serde(untagged)]\npub enum {} {{\n", name));
+            variants.iter().for_each(|variant| item.push_str(variant));
+            item.push_str("}\n");
+            self.items.push(item);
+        }
+        name
+    }
+}
+
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
+
+fn words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous = '_';
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            previous = '_';
+            continue;
+        }
+        let starts = previous == '_' || (c.is_ascii_uppercase() && previous.is_ascii_lowercase());
+        match words.last_mut() {
+            Some(word) if !starts => word.push(c),
+            _ => words.push(c.to_string()),
+        }
+        previous = c;
+    }
+    words
+}
+
+// `createdAt` becomes `created_at`, `type` the raw identifier `r#type`
+fn snake_case(name: &str) -> String {
+    let snake = words(name)
+        .iter()
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    match snake.as_str() {
+        "" => "field".to_owned(),
+        snake if KEYWORDS.contains(&snake) => format!("r#{}", snake),
+        snake if snake.starts_with(|c: char| c.is_ascii_digit()) => format!("_{}", snake),
+        _ => snake,
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    let pascal: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect();
+    match pascal.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("_{}", pascal),
+        false => pascal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rust_serde::module;
+    use serde_json::json;
+
+    #[test]
+    fn test_module() {
+        let schema = json!({
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                { "name": "id", "type": "string" },
+                { "name": "createdAt", "type": { "type": "long", "logicalType": "timestamp-millis" } },
+                { "name": "note", "type": ["null", "string"] },
+                { "name": "type", "type": { "type": "enum", "name": "Kind", "symbols": ["retail", "B2B"] } },
+                { "name": "lines", "type": { "type": "array", "items": {
+                    "type": "record", "name": "Line", "fields": [{ "name": "sku", "type": "string" }]
+                } } },
+                { "name": "attributes", "type": { "type": "map", "values": "double" } },
+                { "name": "payment", "type": ["Line", "string"] },
+                { "name": "parent", "type": ["null", "Order"] }
+            ]
+        });
+
+        let module = module(&[schema]);
+
+        assert_eq!(
+            module,
+            r#"// Generated by ts-to-avro, do not edit.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Kind {
+    #[serde(rename = "retail")]
+    Retail,
+    B2B,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Line {
+    pub sku: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OrderPayment {
+    Line(Line),
+    String(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Order {
+    pub id: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    pub note: Option<String>,
+    pub r#type: Kind,
+    pub lines: Vec<Line>,
+    pub attributes: HashMap<String, f64>,
+    pub payment: OrderPayment,
+    pub parent: Option<Box<Order>>,
+}
+"#
+        );
+    }
+}
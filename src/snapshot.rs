@@ -0,0 +1,212 @@
+use crate::resolve_root;
+use crate::symbols::Symbols;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+pub const DEFAULT_DIR: &str = "__schemas__";
+
+// What `check` found, or `update` changed, in the snapshot directory.
+#[derive(Debug, Default, PartialEq)]
+pub struct Outcome {
+    pub snapshots: usize,
+    pub differences: Vec<String>,
+}
+
+// One pretty-printed `<Record>.avsc` per public record, rooted at it, so that a change to
+// the model shows up as a readable diff of the snapshots it affects.
+pub fn check(dir: &Path, schemas: &[Value], private: &BTreeSet<String>) -> Result<Outcome, String> {
+    sync(dir, schemas, private, false)
+}
+
+// writes the snapshots and removes obsolete ones
+pub fn update(
+    dir: &Path,
+    schemas: &[Value],
+    private: &BTreeSet<String>,
+) -> Result<Outcome, String> {
+    fs::create_dir_all(dir)
+        .map_err(|err| format!("Failed to create {}: {}", dir.display(), err))?;
+    sync(dir, schemas, private, true)
+}
+
+// Records are resolved and rendered on a thread per core, each taking the next record left
+// to do. Resolving only reads the symbol table, so records don't wait on one another. Each
+// snapshot is compared, and written, as soon as it is rendered, so that only a few are held
+// at a time however many records the model has.
+fn sync(
+    dir: &Path,
+    schemas: &[Value],
+    private: &BTreeSet<String>,
+    write: bool,
+) -> Result<Outcome, String> {
+    let mut expected = BTreeSet::new();
+    let mut roots = Vec::new();
+    for schema in schemas {
+        match schema["name"].as_str() {
+            Some(name) if schema["type"] == "record" && !private.contains(name) => {
+                let file = format!("{}.avsc", name);
+                if expected.insert(file.clone()) {
+                    roots.push((file, schema));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let symbols = Symbols::new(schemas);
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let (sender, receiver) = mpsc::sync_channel(workers);
+    let mut differences = Vec::new();
+    let mut failed = None;
+    thread::scope(|scope| {
+        for _ in 0..workers.min(roots.len()) {
+            let (sender, next, roots, symbols) = (sender.clone(), &next, &roots, &symbols);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let (_, root) = match roots.get(i) {
+                    Some(root) => root,
+                    None => break,
+                };
+                let rendered = resolve_root(root, symbols).map(|schema| format!("{:#}\n", schema));
+                if sender.send((i, rendered)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        for (i, rendered) in receiver {
+            let result = rendered
+                .map_err(|err| err.to_string())
+                .and_then(|contents| compare(dir, &roots[i].0, contents, write));
+            match result {
+                Ok(Some(difference)) => differences.push((i, difference)),
+                Ok(None) => {}
+                Err(err) => {
+                    failed.get_or_insert(err);
+                }
+            }
+        }
+    });
+    if let Some(err) = failed {
+        return Err(err);
+    }
+    differences.sort();
+
+    let mut outcome = Outcome {
+        snapshots: roots.len(),
+        differences: differences.into_iter().map(|(_, x)| x).collect(),
+    };
+    for file in written(dir) {
+        if expected.contains(&file) {
+            continue;
+        }
+        let path = dir.join(&file);
+        if write {
+            fs::remove_file(&path)
+                .map_err(|err| format!("Failed to update {}: {}", path.display(), err))?;
+        }
+        outcome.differences.push(format!("obsolete {}", file));
+    }
+    Ok(outcome)
+}
+
+fn compare(
+    dir: &Path,
+    file: &str,
+    contents: String,
+    write: bool,
+) -> Result<Option<String>, String> {
+    let path = dir.join(file);
+    let difference = match fs::read_to_string(&path) {
+        Ok(existing) if existing == contents => return Ok(None),
+        Ok(_) => format!("changed {}", file),
+        Err(_) => format!("missing {}", file),
+    };
+    if write {
+        fs::write(&path, contents)
+            .map_err(|err| format!("Failed to update {}: {}", path.display(), err))?;
+    }
+    Ok(Some(difference))
+}
+
+fn written(dir: &Path) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|file| file.ends_with(".avsc"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::{check, update};
+    use crate::{get_schema, Grammar};
+    use std::collections::BTreeSet;
+    use std::fs;
+
+    #[test]
+    fn test_snapshots() {
+        let code = r#"
+        interface Order {
+            line: Line;
+        }
+
+        interface Line {
+            sku: string;
+        }
+        "#;
+        let schemas = get_schema(code, Grammar::TypeScript).unwrap();
+        let private = BTreeSet::from(["Line".to_owned()]);
+        let dir = std::env::temp_dir().join(format!("ts-to-avro-snapshots-{}", std::process::id()));
+
+        let missing = check(&dir, &schemas, &private).unwrap();
+        assert_eq!(missing.snapshots, 1);
+        assert_eq!(missing.differences, vec!["missing Order.avsc"]);
+        assert_eq!(update(&dir, &schemas, &private).unwrap(), missing);
+        assert!(check(&dir, &schemas, &private)
+            .unwrap()
+            .differences
+            .is_empty());
+        let written = fs::read_to_string(dir.join("Order.avsc")).unwrap();
+
+        fs::write(dir.join("Order.avsc"), "{}\n").unwrap();
+        fs::write(dir.join("Refund.avsc"), "{}\n").unwrap();
+        let differences = check(&dir, &schemas, &private).unwrap().differences;
+        update(&dir, &schemas, &private).unwrap();
+        let remaining = check(&dir, &schemas, &private).unwrap().differences;
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(written.starts_with("{\n  \"fields\": [\n"));
+        assert_eq!(
+            differences,
+            vec!["changed Order.avsc", "obsolete Refund.avsc"]
+        );
+        assert!(remaining.is_empty());
+    }
+
+    #[test]
+    fn test_differences_in_declaration_order() {
+        let code: String = (0..40)
+            .map(|i| format!("interface R{} {{ next: R{}; }}\n", i, (i + 1) % 40))
+            .collect();
+        let schemas = get_schema(&code, Grammar::TypeScript).unwrap();
+        let dir = std::env::temp_dir().join(format!("ts-to-avro-order-{}", std::process::id()));
+
+        let outcome = check(&dir, &schemas, &BTreeSet::new()).unwrap();
+
+        assert_eq!(outcome.snapshots, 40);
+        let expected: Vec<String> = (0..40).map(|i| format!("missing R{}.avsc", i)).collect();
+        assert_eq!(outcome.differences, expected);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::avro;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+// What a run produced, printed with `--summary` for CI logs.
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub records: usize,
+    pub enums: usize,
+    pub fields: usize,
+    pub warnings: BTreeMap<String, usize>,
+    pub unresolved: BTreeSet<String>,
+    pub outputs: Vec<String>,
+}
+
+impl Summary {
+    pub fn count(&mut self, schemas: &[Value]) {
+        for schema in schemas {
+            if schema["type"] == "enum" {
+                self.enums += 1;
+                continue;
+            }
+            self.records += 1;
+            self.fields += schema["fields"].as_array().map_or(0, |fields| fields.len());
+        }
+        self.unresolved.extend(avro::unresolved(schemas));
+    }
+
+    pub fn warn(&mut self, category: &str) {
+        *self.warnings.entry(category.to_owned()).or_default() += 1;
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let warnings: Vec<String> = self
+            .warnings
+            .iter()
+            .map(|(category, count)| format!("{} {}", count, category))
+            .collect();
+        let unresolved: Vec<&str> = self.unresolved.iter().map(|name| name.as_str()).collect();
+
+        writeln!(f, "Summary:")?;
+        writeln!(f, "  records converted: {}", self.records)?;
+        writeln!(f, "  enums converted: {}", self.enums)?;
+        writeln!(f, "  fields emitted: {}", self.fields)?;
+        writeln!(f, "  warnings: {}", or_none(&warnings.join(", ")))?;
+        writeln!(f, "  unresolved types: {}", or_none(&unresolved.join(", ")))?;
+        writeln!(f, "  files written: {}", or_none(&self.outputs.join(", ")))
+    }
+}
+
+fn or_none(list: &str) -> &str {
+    match list {
+        "" => "none",
+        list => list,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::summary::Summary;
+    use crate::{get_schema, Grammar};
+
+    #[test]
+    fn test_summary() {
+        let code = r#"
+        interface Order {
+            id: string;
+            status: Status;
+            customer: Customer;
+            lines?: Line[];
+        }
+
+        interface Line {
+            sku: string;
+        }
+
+        enum Status {
+            Open,
+        }
+        "#;
+
+        let mut summary = Summary::default();
+        summary.count(&get_schema(code, Grammar::TypeScript).unwrap());
+        summary.warn("unions");
+        summary.warn("unions");
+        summary.outputs.push("order-key.avsc".to_owned());
+
+        assert_eq!(
+            summary.to_string(),
+            "Summary:
+  records converted: 2
+  enums converted: 1
+  fields emitted: 5
+  warnings: 2 unions
+  unresolved types: Customer
+  files written: order-key.avsc
+"
+        );
+    }
+
+    #[test]
+    fn test_empty_summary() {
+        assert!(Summary::default()
+            .to_string()
+            .contains("  unresolved types: none\n"));
+    }
+}
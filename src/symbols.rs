@@ -0,0 +1,124 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Every declaration of the input files by its full name, `namespace.Name` when it has one,
+// so that references resolve without scanning the schemas. The first declaration of a name
+// wins, as it did when the first match of a scan was used.
+pub struct Symbols<'a> {
+    declarations: HashMap<String, &'a Value>,
+    // full names by short name, to resolve references made across namespaces
+    short: HashMap<&'a str, Vec<String>>,
+}
+
+impl<'a> Symbols<'a> {
+    pub fn new(schemas: &'a [Value]) -> Symbols<'a> {
+        let mut symbols = Symbols {
+            declarations: HashMap::new(),
+            short: HashMap::new(),
+        };
+        for schema in schemas {
+            let name = match schema["name"].as_str() {
+                Some(name) => name,
+                None => continue,
+            };
+            let full = full_name(schema);
+            if symbols.declarations.contains_key(&full) {
+                continue;
+            }
+            symbols.short.entry(name).or_default().push(full.clone());
+            symbols.declarations.insert(full, schema);
+        }
+        symbols
+    }
+
+    // Resolves a reference made from `namespace` the way Avro does, then falls back to a
+    // short name declared in a single namespace, as a TypeScript import of it would be.
+    pub fn lookup(&self, name: &str, namespace: &str) -> Option<(&str, &'a Value)> {
+        if !namespace.is_empty() && !name.contains('.') {
+            let qualified = format!("{}.{}", namespace, name);
+            if let Some((full, declaration)) = self.declarations.get_key_value(&qualified) {
+                return Some((full, declaration));
+            }
+        }
+        if let Some((full, declaration)) = self.declarations.get_key_value(name) {
+            return Some((full, declaration));
+        }
+        match self.short.get(name).map(|fulls| fulls.as_slice()) {
+            Some([full]) => Some((full.as_str(), self.declarations[full])),
+            _ => self.qualified(name),
+        }
+    }
+
+    // `Geo.Point`, qualified by a TypeScript namespace or a namespace import, is the `Point`
+    // declared in a namespace ending in `geo` when there is one, else the only `Point`
+    fn qualified(&self, name: &str) -> Option<(&str, &'a Value)> {
+        let (_, short) = name.rsplit_once('.')?;
+        let fulls = self.short.get(short)?;
+        let suffix = format!(".{}", name.to_ascii_lowercase());
+        let mut matching = fulls
+            .iter()
+            .filter(|full| format!(".{}", full.to_ascii_lowercase()).ends_with(&suffix));
+        let full = match (matching.next(), matching.next(), fulls.as_slice()) {
+            (Some(full), None, _) => full,
+            (None, _, [only]) => only,
+            _ => return None,
+        };
+        Some((full.as_str(), self.declarations[full]))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.declarations.contains_key(name)
+            || self.short.contains_key(name)
+            || self.qualified(name).is_some()
+    }
+}
+
+pub fn full_name(schema: &Value) -> String {
+    let name = schema["name"].as_str().unwrap_or_default();
+    match schema["namespace"].as_str() {
+        Some(namespace) if !namespace.is_empty() && !name.contains('.') => {
+            format!("{}.{}", namespace, name)
+        }
+        _ => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::symbols::Symbols;
+    use serde_json::json;
+
+    #[test]
+    fn test_lookup() {
+        let schemas = vec![
+            json!({ "type": "record", "name": "Order", "namespace": "shop", "fields": [] }),
+            json!({ "type": "record", "name": "Id", "namespace": "shop", "fields": [] }),
+            json!({ "type": "record", "name": "Id", "namespace": "billing", "fields": [] }),
+            json!({ "type": "enum", "name": "Status", "symbols": ["Open"] }),
+            json!({ "type": "enum", "name": "Status", "symbols": ["Closed"] }),
+        ];
+        let symbols = Symbols::new(&schemas);
+
+        assert_eq!(symbols.lookup("Order", "").unwrap().0, "shop.Order");
+        assert_eq!(symbols.lookup("Id", "billing").unwrap().0, "billing.Id");
+        assert_eq!(symbols.lookup("shop.Id", "billing").unwrap().0, "shop.Id");
+        assert!(
+            symbols.lookup("Id", "").is_none(),
+            "ambiguous across namespaces"
+        );
+        assert_eq!(
+            symbols.lookup("Status", "shop").unwrap().1["symbols"],
+            json!(["Open"])
+        );
+        assert_eq!(symbols.lookup("Shop.Order", "").unwrap().0, "shop.Order");
+        assert_eq!(symbols.lookup("Billing.Id", "").unwrap().0, "billing.Id");
+        assert_eq!(
+            symbols.lookup("Geo.Order", "").unwrap().0,
+            "shop.Order",
+            "a TypeScript namespace of the only Order"
+        );
+        assert!(symbols.lookup("Geo.Id", "").is_none());
+        assert!(symbols.contains("Id"));
+        assert!(!symbols.contains("Refund"));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{interactive, merger};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+// The schema of a topic carrying several event types, for TopicNameStrategy: a union of
+// the records listed under `[topics.<name>] records = [...]`, or of every record no other
+// declaration refers to when the topic isn't configured, leaving out private ones.
+pub fn union(
+    schemas: &[Value],
+    topic: &str,
+    config: &Value,
+    private: &BTreeSet<String>,
+) -> Result<Value, String> {
+    let names: Vec<String> = match config["topics"][topic]["records"].as_array() {
+        Some(records) => records
+            .iter()
+            .filter_map(|name| name.as_str())
+            .map(|name| name.to_owned())
+            .collect(),
+        None => interactive::root_candidates(schemas)
+            .into_iter()
+            .filter(|name| !private.contains(name))
+            .collect(),
+    };
+    if names.is_empty() {
+        return Err(format!("No records selected for the topic {}", topic));
+    }
+
+    let mut members = Vec::new();
+    for name in names {
+        let position = schemas
+            .iter()
+            .position(|x| x["name"] == name)
+            .ok_or(format!(
+                "The topic {} lists {}, which is not declared",
+                topic, name
+            ))?;
+        let mut reordered = schemas.to_vec();
+        let record = reordered.remove(position);
+        reordered.insert(0, record);
+        members.push(merger(reordered).map_err(|err| err.to_string())?);
+    }
+    Ok(Value::Array(members))
+}
+
+// `topics.<name>.out`, or `<name>.avsc`
+pub fn path(topic: &str, config: &Value) -> String {
+    match config["topics"][topic]["out"].as_str() {
+        Some(path) => path.to_owned(),
+        None => format!("{}.avsc", topic),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::topic::{path, union};
+    use crate::{config, get_schema, Grammar};
+    use std::collections::BTreeSet;
+
+    const CODE: &str = r#"
+        interface OrderPlaced {
+            order: Order;
+        }
+
+        interface OrderShipped {
+            carrier: string;
+        }
+
+        interface Order {
+            total: number;
+        }
+        "#;
+
+    #[test]
+    fn test_union_of_unreferenced_public_records() {
+        let schemas = get_schema(CODE, Grammar::TypeScript).unwrap();
+
+        let union = union(
+            &schemas,
+            "orders",
+            &serde_json::json!({}),
+            &BTreeSet::from(["OrderShipped".to_owned()]),
+        )
+        .unwrap();
+
+        let members = union.as_array().unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0]["name"], "OrderPlaced");
+        assert_eq!(members[0]["fields"][0]["type"]["name"], "Order");
+    }
+
+    #[test]
+    fn test_configured_topic() {
+        let schemas = get_schema(CODE, Grammar::TypeScript).unwrap();
+        let config = config::parse(
+            r#"
+            [topics.orders]
+            records = ["OrderShipped"]
+            out = "schemas/orders-value.avsc"
+            "#,
+        )
+        .unwrap();
+
+        let union = union(&schemas, "orders", &config, &BTreeSet::new()).unwrap();
+
+        assert_eq!(union.as_array().unwrap().len(), 1);
+        assert_eq!(union[0]["name"], "OrderShipped");
+        assert_eq!(path("orders", &config), "schemas/orders-value.avsc");
+        assert_eq!(path("payments", &config), "payments.avsc");
+
+        let config = config::parse("[topics.orders]\nrecords = [\"Refund\"]\n").unwrap();
+        assert_eq!(
+            super::union(&schemas, "orders", &config, &BTreeSet::new()).unwrap_err(),
+            "The topic orders lists Refund, which is not declared"
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use serde_json::{Map, Value};
+
+// `--transform`, a jq-like expression for one-off edits of the emitted schema. Steps are
+// separated by `|` and each one updates the whole schema:
+//
+//   .owner = "payments"                                   sets a property
+//   del(.fields[] | select(.name == "internal"))          deletes what the path finds
+//   (.fields[] | select(.name == "id")).doc = "The id"    sets a property of what it finds
+//
+// A path is made of `.name`, `."quoted name"`, `[n]`, `[]` for every element, and
+// `select(.path == value)` or `!=` to keep the elements it holds for. Values are JSON.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    Set(Vec<Segment>, Value),
+    Delete(Vec<Segment>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Segment {
+    Field(String),
+    Index(usize),
+    Each,
+    Select(Vec<Segment>, bool, Value),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Key {
+    Field(String),
+    Index(usize),
+}
+
+pub fn parse(expression: &str) -> Result<Vec<Step>, String> {
+    let mut parser = Parser {
+        text: expression,
+        at: 0,
+    };
+    let mut steps = vec![parser.step()?];
+    while parser.eat("|") {
+        steps.push(parser.step()?);
+    }
+    parser.skip_space();
+    match parser.at == expression.len() {
+        true => Ok(steps),
+        false => Err(parser.error("`|`")),
+    }
+}
+
+pub fn apply(schema: &mut Value, steps: &[Step]) {
+    for step in steps {
+        match step {
+            Step::Set(path, value) => {
+                for keys in resolve(schema, path, true) {
+                    if let Some(target) = pointer(schema, &keys, true) {
+                        *target = value.clone();
+                    }
+                }
+            }
+            Step::Delete(path) => {
+                // the last found first, so that removing an element keeps the indices of
+                // the ones before it
+                let mut found = resolve(schema, path, false);
+                found.sort_by(|a, b| compare(b, a));
+                for mut keys in found {
+                    let last = match keys.pop() {
+                        Some(last) => last,
+                        None => continue,
+                    };
+                    match (pointer(schema, &keys, false), last) {
+                        (Some(Value::Object(object)), Key::Field(name)) => {
+                            object.remove(&name);
+                        }
+                        (Some(Value::Array(items)), Key::Index(i)) if i < items.len() => {
+                            items.remove(i);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn compare(a: &[Key], b: &[Key]) -> std::cmp::Ordering {
+    for (a, b) in a.iter().zip(b) {
+        let ordering = match (a, b) {
+            (Key::Index(a), Key::Index(b)) => a.cmp(b),
+            (Key::Field(a), Key::Field(b)) => a.cmp(b),
+            _ => std::cmp::Ordering::Equal,
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+// the concrete keys of everything the path finds; when setting, a missing property is found
+// too so that it can be added
+fn resolve(value: &Value, path: &[Segment], setting: bool) -> Vec<Vec<Key>> {
+    let mut found = vec![Vec::new()];
+    for segment in path {
+        let mut next = Vec::new();
+        for keys in found {
+            let current = pointer_ref(value, &keys);
+            match (segment, current) {
+                (Segment::Field(name), Some(Value::Object(object)))
+                    if setting || object.contains_key(name) =>
+                {
+                    next.push(with(&keys, Key::Field(name.clone())));
+                }
+                (Segment::Field(name), None | Some(Value::Null)) if setting => {
+                    next.push(with(&keys, Key::Field(name.clone())));
+                }
+                (Segment::Index(i), Some(Value::Array(items))) if *i < items.len() => {
+                    next.push(with(&keys, Key::Index(*i)));
+                }
+                (Segment::Each, Some(Value::Array(items))) => {
+                    next.extend((0..items.len()).map(|i| with(&keys, Key::Index(i))));
+                }
+                (Segment::Each, Some(Value::Object(object))) => {
+                    next.extend(
+                        object
+                            .keys()
+                            .map(|name| with(&keys, Key::Field(name.clone()))),
+                    );
+                }
+                (Segment::Select(path, equal, expected), Some(current)) => {
+                    let holds = resolve(current, path, false)
+                        .iter()
+                        .any(|inner| pointer_ref(current, inner) == Some(expected));
+                    if holds == *equal {
+                        next.push(keys);
+                    }
+                }
+                _ => {}
+            }
+        }
+        found = next;
+    }
+    found
+}
+
+fn with(keys: &[Key], key: Key) -> Vec<Key> {
+    let mut keys = keys.to_vec();
+    keys.push(key);
+    keys
+}
+
+fn pointer_ref<'a>(value: &'a Value, keys: &[Key]) -> Option<&'a Value> {
+    keys.iter().try_fold(value, |value, key| match key {
+        Key::Field(name) => value.get(name),
+        Key::Index(i) => value.get(i),
+    })
+}
+
+fn pointer<'a>(value: &'a mut Value, keys: &[Key], create: bool) -> Option<&'a mut Value> {
+    keys.iter().try_fold(value, |value, key| match key {
+        Key::Field(name) => {
+            if create && value.is_null() {
+                *value = Value::Object(Map::new());
+            }
+            match (create, value) {
+                (true, Value::Object(object)) => {
+                    Some(object.entry(name.clone()).or_insert(Value::Null))
+                }
+                (_, value) => value.get_mut(name),
+            }
+        }
+        Key::Index(i) => value.get_mut(i),
+    })
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.text[self.at..]
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.at += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_space();
+        match self.rest().starts_with(token) {
+            true => {
+                self.at += token.len();
+                true
+            }
+            false => false,
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.error(&format!("`{}`", token))),
+        }
+    }
+
+    fn error(&self, expected: &str) -> String {
+        format!(
+            "--transform: expected {} at column {}",
+            expected,
+            self.at + 1
+        )
+    }
+
+    fn step(&mut self) -> Result<Step, String> {
+        if self.eat("del(") {
+            let path = self.selection()?;
+            self.expect(")")?;
+            return Ok(Step::Delete(path));
+        }
+        let mut path = match self.eat("(") {
+            true => {
+                let path = self.selection()?;
+                self.expect(")")?;
+                path
+            }
+            false => Vec::new(),
+        };
+        path.extend(self.path()?);
+        if path.is_empty() {
+            return Err(self.error("a path"));
+        }
+        self.expect("=")?;
+        Ok(Step::Set(path, self.value()?))
+    }
+
+    // a path with `| select(...)` steps, as found within parentheses
+    fn selection(&mut self) -> Result<Vec<Segment>, String> {
+        let mut path = self.path()?;
+        while self.eat("|") {
+            self.expect("select(")?;
+            let inner = self.path()?;
+            let equal = if self.eat("==") {
+                true
+            } else if self.eat("!=") {
+                false
+            } else {
+                return Err(self.error("`==` or `!=`"));
+            };
+            let value = self.value()?;
+            self.expect(")")?;
+            path.push(Segment::Select(inner, equal, value));
+            path.extend(self.path()?);
+        }
+        Ok(path)
+    }
+
+    fn path(&mut self) -> Result<Vec<Segment>, String> {
+        let mut path = Vec::new();
+        loop {
+            self.skip_space();
+            let rest = self.rest();
+            if rest.starts_with("[]") {
+                self.at += 2;
+                path.push(Segment::Each);
+            } else if let Some(inner) = rest.strip_prefix('[') {
+                let digits = inner.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+                match (inner[..digits].parse(), inner[digits..].starts_with(']')) {
+                    (Ok(i), true) => path.push(Segment::Index(i)),
+                    _ => return Err(self.error("an index")),
+                }
+                self.at += digits + 2;
+            } else if rest.starts_with(".\"") {
+                self.at += 1;
+                match self.value()? {
+                    Value::String(name) => path.push(Segment::Field(name)),
+                    _ => return Err(self.error("a name")),
+                }
+            } else if let Some(name) = rest.strip_prefix('.') {
+                let length = name
+                    .find(|c: char| !c.is_alphanumeric() && c != '_')
+                    .unwrap_or(name.len());
+                if length > 0 {
+                    path.push(Segment::Field(name[..length].to_owned()));
+                } else if !rest.starts_with(".[") {
+                    return Err(self.error("a name"));
+                }
+                self.at += 1 + length;
+            } else {
+                return Ok(path);
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_space();
+        let mut values = serde_json::Deserializer::from_str(self.rest()).into_iter::<Value>();
+        match values.next() {
+            Some(Ok(value)) => {
+                self.at += values.byte_offset();
+                Ok(value)
+            }
+            _ => Err(self.error("a JSON value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transform::{apply, parse};
+    use serde_json::json;
+
+    #[test]
+    fn test_transform() {
+        let mut schema = json!({
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                { "name": "id", "type": "string" },
+                { "name": "internal", "type": "string" },
+                { "name": "total", "type": "double" },
+                { "name": "internal", "type": "long" }
+            ]
+        });
+        let steps = parse(
+            ".owner = \"payments\" | .\"x-meta\".tier = 1 \
+             | del(.fields[] | select(.name == \"internal\")) \
+             | (.fields[] | select(.name != \"total\")).doc = \"The id\" | del(.fields[1].type)",
+        )
+        .unwrap();
+        apply(&mut schema, &steps);
+        assert_eq!(
+            schema,
+            json!({
+                "type": "record",
+                "name": "Order",
+                "owner": "payments",
+                "x-meta": { "tier": 1 },
+                "fields": [
+                    { "name": "id", "type": "string", "doc": "The id" },
+                    { "name": "total" }
+                ]
+            })
+        );
+
+        assert_eq!(
+            parse(".owner \"payments\""),
+            Err("--transform: expected `=` at column 8".to_owned())
+        );
+        assert_eq!(
+            parse("del(.fields[] | select(.name))"),
+            Err("--transform: expected `==` or `!=` at column 29".to_owned())
+        );
+        assert_eq!(
+            parse(".doc = nope"),
+            Err("--transform: expected a JSON value at column 8".to_owned())
+        );
+    }
+}
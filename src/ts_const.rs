@@ -0,0 +1,76 @@
+use crate::symbols;
+use serde_json::Value;
+use std::collections::HashSet;
+
+// A TypeScript module exporting each schema as an `as const` literal, so its type is the
+// schema itself, and a frozen map of them by full name, for frontends to import schemas
+// instead of fetching JSON at runtime.
+pub fn module(schemas: &[Value]) -> String {
+    let mut out = String::from("// Generated by ts-to-avro, do not edit.\n");
+    let mut taken = HashSet::new();
+    let mut entries = Vec::new();
+    for schema in schemas {
+        let full = symbols::full_name(schema);
+        let short = schema["name"].as_str().unwrap_or("Anonymous");
+        // a name declared in two namespaces keeps its namespace in the identifier
+        let mut constant = format!("{}Schema", identifier(short));
+        if !taken.insert(constant.clone()) {
+            constant = format!("{}Schema", identifier(&full));
+            taken.insert(constant.clone());
+        }
+        out.push_str(&format!(
+            "\nexport const {} = {:#} as const;\n",
+            constant, schema
+        ));
+        entries.push((full, constant));
+    }
+
+    out.push_str("\nexport const schemas = Object.freeze({\n");
+    for (full, identifier) in &entries {
+        out.push_str(&format!(
+            "  {}: {},\n",
+            Value::from(full.as_str()),
+            identifier
+        ));
+    }
+    out.push_str("} as const);\n\nexport type SchemaName = keyof typeof schemas;\n");
+    out
+}
+
+// `com.shop.order_line` becomes `ComShopOrderLine`
+fn identifier(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ts_const::module;
+    use serde_json::json;
+
+    #[test]
+    fn test_module() {
+        let schemas = vec![
+            json!({ "type": "record", "name": "Order", "namespace": "shop", "fields": [] }),
+            json!({ "type": "record", "name": "Order", "namespace": "billing", "fields": [] }),
+        ];
+
+        let module = module(&schemas);
+
+        assert!(module.starts_with(
+            "// Generated by ts-to-avro, do not edit.\n\nexport const OrderSchema = {\n"
+        ));
+        assert!(module.contains("\n} as const;\n\nexport const BillingOrderSchema = {\n"));
+        assert!(module.contains(
+            "export const schemas = Object.freeze({\n  \"shop.Order\": OrderSchema,\n  \"billing.Order\": BillingOrderSchema,\n} as const);\n"
+        ));
+        assert!(module.ends_with("export type SchemaName = keyof typeof schemas;\n"));
+    }
+}
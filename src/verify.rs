@@ -0,0 +1,964 @@
+use crate::faker;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+// A sanity gate for a generated schema: checks that names, references and defaults are
+// consistent, then writes random records with the Avro binary encoding and reads them back,
+// reporting any record that doesn't survive the round trip. Only the type names of the Avro
+// spec are accepted.
+
+pub fn verify(schema: &Value, records: usize) -> Result<(), Vec<String>> {
+    let mut names = BTreeMap::new();
+    let mut problems = Vec::new();
+    check(schema, "", "", &mut names, &mut problems);
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    for i in 0..records {
+        let result = generate(schema, &names, &mut random, 0).and_then(|datum| {
+            let mut bytes = Vec::new();
+            encode(&datum, &mut bytes);
+            let mut input = bytes.as_slice();
+            let decoded = decode(schema, &names, &mut input)?;
+            match (decoded == datum, input.is_empty()) {
+                (true, true) => Ok(()),
+                (false, _) => Err(format!("read back differently: {:?}", decoded)),
+                (true, false) => Err(format!("{} bytes left over", input.len())),
+            }
+        });
+        if let Err(err) = result {
+            return Err(vec![format!("record {}: {}", i + 1, err)]);
+        }
+    }
+    Ok(())
+}
+
+// Random records matching the schema, in the Avro JSON encoding, for tests of code that
+// serializes the modelled types. The same schema always yields the same records. A field
+// with an `example` always takes it, and a string field with a `faker` method takes one of
+// its values.
+pub fn examples(schema: &Value, count: usize) -> Result<Vec<Value>, Vec<String>> {
+    let mut names = BTreeMap::new();
+    let data = data(schema, count, &mut names)?;
+    Ok(data
+        .iter()
+        .map(|datum| to_json(datum, schema, &names))
+        .collect())
+}
+
+// the same records as `examples`, with the Avro binary encoding
+pub fn encoded(schema: &Value, count: usize) -> Result<Vec<Vec<u8>>, Vec<String>> {
+    let data = data(schema, count, &mut BTreeMap::new())?;
+    Ok(data
+        .iter()
+        .map(|datum| {
+            let mut bytes = Vec::new();
+            encode(datum, &mut bytes);
+            bytes
+        })
+        .collect())
+}
+
+fn data(
+    schema: &Value,
+    count: usize,
+    names: &mut BTreeMap<String, Value>,
+) -> Result<Vec<Datum>, Vec<String>> {
+    let mut problems = Vec::new();
+    check(schema, "", "", names, &mut problems);
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    (0..count)
+        .map(|_| generate(schema, names, &mut random, 0).map_err(|err| vec![err]))
+        .collect()
+}
+
+const PRIMITIVES: [&str; 8] = [
+    "null", "boolean", "int", "long", "float", "double", "bytes", "string",
+];
+
+fn type_of(ptype: &Value) -> &str {
+    match ptype {
+        Value::String(name) => name,
+        Value::Object(object) => match object.get("type").and_then(|x| x.as_str()) {
+            Some("error") => "record",
+            Some(name) => name,
+            None => "",
+        },
+        Value::Array(_) => "union",
+        _ => "",
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    name.split('.').all(|part| {
+        part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+fn full_name(name: &str, namespace: &str) -> String {
+    match name.contains('.') || namespace.is_empty() {
+        true => name.to_owned(),
+        false => format!("{}.{}", namespace, name),
+    }
+}
+
+// resolves a reference by its full name, or by its short name when that's unambiguous
+fn lookup<'a>(names: &'a BTreeMap<String, Value>, name: &str) -> Option<&'a Value> {
+    names.get(name).or_else(|| {
+        let mut matches = names
+            .iter()
+            .filter(|(full, _)| full.rsplit('.').next() == Some(name));
+        match (matches.next(), matches.next()) {
+            (Some((_, definition)), None) => Some(definition),
+            _ => None,
+        }
+    })
+}
+
+fn check(
+    ptype: &Value,
+    namespace: &str,
+    path: &str,
+    names: &mut BTreeMap<String, Value>,
+    problems: &mut Vec<String>,
+) {
+    let at = |message: String| match path.is_empty() {
+        true => message,
+        false => format!("{}: {}", path, message),
+    };
+    match (type_of(ptype), ptype) {
+        (name, Value::String(_)) if PRIMITIVES.contains(&name) => {}
+        (name, Value::String(_)) => {
+            if lookup(names, &full_name(name, namespace)).is_none() && lookup(names, name).is_none()
+            {
+                problems.push(at(format!("unknown type {}", name)));
+            }
+        }
+        ("union", Value::Array(members)) => {
+            let mut seen = Vec::new();
+            for member in members {
+                let kind = match type_of(member) {
+                    "record" | "enum" | "fixed" => member["name"].to_string(),
+                    kind if PRIMITIVES.contains(&kind) || ["array", "map"].contains(&kind) => {
+                        kind.to_owned()
+                    }
+                    name => name.to_owned(),
+                };
+                if member.is_array() {
+                    problems.push(at("unions cannot contain unions".to_owned()));
+                } else if seen.contains(&kind) {
+                    problems.push(at(format!("the union lists {} twice", kind)));
+                }
+                seen.push(kind);
+                check(member, namespace, path, names, problems);
+            }
+        }
+        ("record", _) | ("enum", _) | ("fixed", _) => {
+            let name = ptype["name"].as_str().unwrap_or_default();
+            if !is_name(name) {
+                problems.push(at(format!("invalid name {:?}", name)));
+                return;
+            }
+            let namespace = ptype["namespace"].as_str().unwrap_or(namespace);
+            let full = full_name(name, namespace);
+            if names.contains_key(&full) {
+                problems.push(at(format!("{} is defined twice", full)));
+                return;
+            }
+            names.insert(full.clone(), ptype.clone());
+            let namespace = full.rsplit_once('.').map_or("", |(namespace, _)| namespace);
+
+            match type_of(ptype) {
+                "record" => check_fields(ptype, name, namespace, names, problems),
+                "enum" => check_symbols(ptype, &at, problems),
+                _ if ptype["size"].as_u64().is_none() => {
+                    problems.push(at(format!("fixed {} has no size", name)))
+                }
+                _ => {}
+            }
+        }
+        ("array", _) => check(&ptype["items"], namespace, path, names, problems),
+        ("map", _) => check(&ptype["values"], namespace, path, names, problems),
+        (name, Value::Object(_)) if PRIMITIVES.contains(&name) => {
+            if ptype["logicalType"] == "decimal" {
+                let precision = ptype["precision"].as_u64().unwrap_or_default();
+                if precision == 0 || ptype["scale"].as_u64().unwrap_or_default() > precision {
+                    problems.push(at("invalid decimal precision or scale".to_owned()));
+                }
+            }
+        }
+        (name, _) => problems.push(at(format!("unknown type {:?}", name))),
+    }
+}
+
+fn check_fields(
+    record: &Value,
+    name: &str,
+    namespace: &str,
+    names: &mut BTreeMap<String, Value>,
+    problems: &mut Vec<String>,
+) {
+    let fields = match record["fields"].as_array() {
+        Some(fields) => fields,
+        None => return problems.push(format!("{}: the record has no fields", name)),
+    };
+    let mut seen = Vec::new();
+    for field in fields {
+        let field_name = field["name"].as_str().unwrap_or_default();
+        let path = format!("{}.{}", name, field_name);
+        if !is_name(field_name) || field_name.contains('.') {
+            problems.push(format!("{}: invalid field name", path));
+        }
+        if seen.contains(&field_name) {
+            problems.push(format!("{}: the field is declared twice", path));
+        }
+        seen.push(field_name);
+        check(&field["type"], namespace, &path, names, problems);
+        if let Some(example) = field.get("example") {
+            if from_json(example, &field["type"], names).is_none() {
+                problems.push(format!(
+                    "{}: the example {} doesn't match the type",
+                    path, example
+                ));
+            }
+        }
+        if let Some(method) = field.get("faker") {
+            let method = method.as_str().unwrap_or_default();
+            if faker::fake(method, 0).is_none() {
+                problems.push(format!("{}: unknown faker method {:?}", path, method));
+            } else if fake(method, &field["type"], names, 0).is_none() {
+                problems.push(format!("{}: faker values are strings", path));
+            }
+        }
+        if let Some(default) = field.get("default") {
+            if !matches_default(default, &field["type"], names) {
+                problems.push(format!(
+                    "{}: the default {} doesn't match the type",
+                    path, default
+                ));
+            }
+        }
+    }
+}
+
+fn check_symbols(schema: &Value, at: &dyn Fn(String) -> String, problems: &mut Vec<String>) {
+    let symbols: Vec<&str> = schema["symbols"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|symbol| symbol.as_str())
+        .collect();
+    if symbols.is_empty() {
+        problems.push(at("the enum has no symbols".to_owned()));
+    }
+    for (i, symbol) in symbols.iter().enumerate() {
+        if !is_name(symbol) || symbol.contains('.') {
+            problems.push(at(format!("invalid symbol {:?}", symbol)));
+        }
+        if symbols[..i].contains(symbol) {
+            problems.push(at(format!("the symbol {} is listed twice", symbol)));
+        }
+    }
+    if let Some(default) = schema.get("default") {
+        if !symbols.iter().any(|symbol| default == symbol) {
+            problems.push(at(format!("the default {} is not a symbol", default)));
+        }
+    }
+}
+
+// a union's default is the default of its first branch
+fn matches_default(default: &Value, ptype: &Value, names: &BTreeMap<String, Value>) -> bool {
+    match (type_of(ptype), ptype) {
+        ("union", Value::Array(members)) => members
+            .first()
+            .is_some_and(|first| matches_default(default, first, names)),
+        (name, Value::String(_)) if !PRIMITIVES.contains(&name) => lookup(names, name)
+            .is_some_and(|definition| matches_default(default, definition, names)),
+        ("null", _) => default.is_null(),
+        ("boolean", _) => default.is_boolean(),
+        ("int", _) | ("long", _) => default.is_i64(),
+        ("float", _) | ("double", _) => default.is_number(),
+        ("bytes", _) | ("string", _) | ("fixed", _) => default.is_string(),
+        ("enum", _) => ptype["symbols"]
+            .as_array()
+            .is_some_and(|symbols| symbols.contains(default)),
+        ("array", _) => default.as_array().is_some_and(|items| {
+            items
+                .iter()
+                .all(|item| matches_default(item, &ptype["items"], names))
+        }),
+        ("map", _) => default.as_object().is_some_and(|values| {
+            values
+                .values()
+                .all(|value| matches_default(value, &ptype["values"], names))
+        }),
+        ("record", _) => default.as_object().is_some_and(|object| {
+            ptype["fields"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .all(
+                    |field| match object.get(field["name"].as_str().unwrap_or_default()) {
+                        Some(value) => matches_default(value, &field["type"], names),
+                        None => field.get("default").is_some(),
+                    },
+                )
+        }),
+        _ => false,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Datum {
+    Null,
+    Boolean(bool),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Bytes(Vec<u8>),
+    Record(Vec<Datum>),
+    Enum(usize),
+    Array(Vec<Datum>),
+    Map(Vec<(Vec<u8>, Datum)>),
+    Union(usize, Box<Datum>),
+    Fixed(Vec<u8>),
+}
+
+// xorshift, seeded so that a failing record is reproducible
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+
+    fn bytes(&mut self, max: usize) -> Vec<u8> {
+        let len = self.below(max + 1);
+        (0..len).map(|_| b'a' + self.below(26) as u8).collect()
+    }
+}
+
+// past this depth recursive types take their null branch and collections stay empty
+const MAX_DEPTH: usize = 8;
+
+fn generate(
+    ptype: &Value,
+    names: &BTreeMap<String, Value>,
+    random: &mut Random,
+    depth: usize,
+) -> Result<Datum, String> {
+    if depth > MAX_DEPTH * 4 {
+        return Err("a recursive type has no way to end, such as a nullable field".to_owned());
+    }
+    if let Some(datum) = logical(ptype, random) {
+        return Ok(datum);
+    }
+    let datum = match (type_of(ptype), ptype) {
+        ("union", Value::Array(members)) => {
+            let branch = match (depth > MAX_DEPTH, members.iter().position(|x| x == "null")) {
+                (true, Some(null)) => null,
+                _ => random.below(members.len()),
+            };
+            let value = generate(&members[branch], names, random, depth + 1)?;
+            Datum::Union(branch, Box::new(value))
+        }
+        ("null", _) => Datum::Null,
+        ("boolean", _) => Datum::Boolean(random.below(2) == 1),
+        ("int", _) => Datum::Int(random.next() as i32),
+        ("long", _) => Datum::Long(random.next() as i64),
+        ("float", _) => Datum::Float((random.next() % 1_000_000) as f32 / 8.0),
+        ("double", _) => Datum::Double(random.next() as i64 as f64 / 1024.0),
+        ("bytes", _) | ("string", _) => Datum::Bytes(random.bytes(12)),
+        ("fixed", _) => {
+            let size = ptype["size"].as_u64().unwrap_or_default() as usize;
+            Datum::Fixed((0..size).map(|_| random.next() as u8).collect())
+        }
+        ("enum", _) => Datum::Enum(random.below(ptype["symbols"].as_array().map_or(1, Vec::len))),
+        ("record", _) => {
+            let mut fields = Vec::new();
+            for field in ptype["fields"].as_array().into_iter().flatten() {
+                let annotated = match (field.get("example"), field["faker"].as_str()) {
+                    (Some(example), _) => from_json(example, &field["type"], names),
+                    (None, Some(method)) => fake(method, &field["type"], names, random.next()),
+                    (None, None) => None,
+                };
+                match annotated {
+                    Some(datum) => fields.push(datum),
+                    None => fields.push(generate(&field["type"], names, random, depth + 1)?),
+                }
+            }
+            Datum::Record(fields)
+        }
+        (kind, _) if kind == "array" || kind == "map" => {
+            let len = match depth > MAX_DEPTH {
+                true => 0,
+                false => random.below(4),
+            };
+            let mut items = Vec::new();
+            for _ in 0..len {
+                let inner = match kind {
+                    "array" => &ptype["items"],
+                    _ => &ptype["values"],
+                };
+                items.push(generate(inner, names, random, depth + 1)?);
+            }
+            match kind {
+                "array" => Datum::Array(items),
+                _ => Datum::Map(
+                    items
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, value)| (format!("key{}", i).into_bytes(), value))
+                        .collect(),
+                ),
+            }
+        }
+        (name, _) => match lookup(names, name) {
+            Some(definition) => generate(definition, names, random, depth)?,
+            None => return Err(format!("unknown type {}", name)),
+        },
+    };
+    Ok(datum)
+}
+
+// A value valid for the logical type, as readers converting them would reject others: dates
+// and timestamps fall between 1970 and 2100, and decimals have at most `precision` digits.
+// Logical types this doesn't know are generated as their underlying type.
+fn logical(ptype: &Value, random: &mut Random) -> Option<Datum> {
+    const DAY: u64 = 86_400_000;
+    const MILLIS: u64 = 4_102_444_800_000;
+    let datum = match (type_of(ptype), ptype["logicalType"].as_str()?) {
+        ("string", "uuid") => {
+            let hex = format!("{:016x}{:016x}", random.next(), random.next());
+            let uuid = format!(
+                "{}-{}-{}-{}-{}",
+                &hex[..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..]
+            );
+            Datum::Bytes(uuid.into_bytes())
+        }
+        ("int", "date") => Datum::Int((random.next() % (MILLIS / DAY)) as i32),
+        ("int", "time-millis") => Datum::Int((random.next() % DAY) as i32),
+        ("long", "time-micros") => Datum::Long((random.next() % (DAY * 1000)) as i64),
+        ("long", "timestamp-millis" | "local-timestamp-millis") => {
+            Datum::Long((random.next() % MILLIS) as i64)
+        }
+        ("long", "timestamp-micros" | "local-timestamp-micros") => {
+            Datum::Long((random.next() % (MILLIS * 1000)) as i64)
+        }
+        ("long", "timestamp-nanos" | "local-timestamp-nanos") => {
+            Datum::Long((random.next() % (MILLIS * 1_000_000)) as i64)
+        }
+        (kind @ ("bytes" | "fixed"), "decimal") => {
+            let size = ptype["size"].as_u64().unwrap_or(8).min(8) as u32;
+            let precision = ptype["precision"].as_u64()?.min(18) as u32;
+            let bound = 10u64.pow(precision).min(1 << (size * 8 - 1));
+            let unscaled = (random.next() % bound) as i64;
+            let unscaled = match random.below(2) {
+                0 => unscaled,
+                _ => -unscaled,
+            };
+            // big-endian two's complement, the shortest form for bytes and sign-extended
+            // to the size for fixed
+            let bytes = unscaled.to_be_bytes();
+            match kind {
+                "fixed" => {
+                    let size = ptype["size"].as_u64()? as usize;
+                    let sign = if unscaled < 0 { 0xff } else { 0 };
+                    let mut fixed = vec![sign; size.saturating_sub(8)];
+                    fixed.extend_from_slice(&bytes[8 - size.min(8)..]);
+                    Datum::Fixed(fixed)
+                }
+                _ => {
+                    let redundant = (0..7)
+                        .take_while(|&i| {
+                            (bytes[i] == 0 && bytes[i + 1] < 0x80)
+                                || (bytes[i] == 0xff && bytes[i + 1] >= 0x80)
+                        })
+                        .count();
+                    Datum::Bytes(bytes[redundant..].to_vec())
+                }
+            }
+        }
+        _ => return None,
+    };
+    Some(datum)
+}
+
+// a faker value as a datum of the type, taking the first string branch of a union
+fn fake(method: &str, ptype: &Value, names: &BTreeMap<String, Value>, seed: u64) -> Option<Datum> {
+    match (type_of(ptype), ptype) {
+        ("union", Value::Array(members)) => members.iter().enumerate().find_map(|(i, member)| {
+            let datum = fake(method, member, names, seed)?;
+            Some(Datum::Union(i, Box::new(datum)))
+        }),
+        ("string", _) => Some(Datum::Bytes(faker::fake(method, seed)?.into_bytes())),
+        (name, Value::String(_)) if !PRIMITIVES.contains(&name) => {
+            fake(method, lookup(names, name)?, names, seed)
+        }
+        _ => None,
+    }
+}
+
+// A value as plain JSON, as in an `example`, read as a datum of the type. A union takes the
+// first branch the value fits, and a record field missing from the value takes its default.
+fn from_json(value: &Value, ptype: &Value, names: &BTreeMap<String, Value>) -> Option<Datum> {
+    let datum = match (type_of(ptype), ptype) {
+        ("union", Value::Array(members)) => {
+            return members.iter().enumerate().find_map(|(i, member)| {
+                let datum = from_json(value, member, names)?;
+                Some(Datum::Union(i, Box::new(datum)))
+            })
+        }
+        ("null", _) if value.is_null() => Datum::Null,
+        ("boolean", _) => Datum::Boolean(value.as_bool()?),
+        ("int", _) => Datum::Int(i32::try_from(value.as_i64()?).ok()?),
+        ("long", _) => Datum::Long(value.as_i64()?),
+        ("float", _) => Datum::Float(value.as_f64()? as f32),
+        ("double", _) => Datum::Double(value.as_f64()?),
+        ("string", _) => Datum::Bytes(value.as_str()?.as_bytes().to_vec()),
+        ("bytes", _) => Datum::Bytes(value.as_str()?.chars().map(|c| c as u8).collect()),
+        ("fixed", _) => {
+            let bytes: Vec<u8> = value.as_str()?.chars().map(|c| c as u8).collect();
+            match ptype["size"].as_u64() == Some(bytes.len() as u64) {
+                true => Datum::Fixed(bytes),
+                false => return None,
+            }
+        }
+        ("enum", _) => Datum::Enum(
+            ptype["symbols"]
+                .as_array()?
+                .iter()
+                .position(|symbol| symbol == value)?,
+        ),
+        ("array", _) => Datum::Array(
+            value
+                .as_array()?
+                .iter()
+                .map(|item| from_json(item, &ptype["items"], names))
+                .collect::<Option<_>>()?,
+        ),
+        ("map", _) => Datum::Map(
+            value
+                .as_object()?
+                .iter()
+                .map(|(key, value)| {
+                    Some((
+                        key.as_bytes().to_vec(),
+                        from_json(value, &ptype["values"], names)?,
+                    ))
+                })
+                .collect::<Option<_>>()?,
+        ),
+        ("record", _) => {
+            let object = value.as_object()?;
+            let mut fields = Vec::new();
+            for field in ptype["fields"].as_array()? {
+                let name = field["name"].as_str().unwrap_or_default();
+                let value = object.get(name).or_else(|| field.get("default"))?;
+                fields.push(from_json(value, &field["type"], names)?);
+            }
+            Datum::Record(fields)
+        }
+        (name, Value::String(_)) if !PRIMITIVES.contains(&name) => {
+            return from_json(value, lookup(names, name)?, names)
+        }
+        _ => return None,
+    };
+    Some(datum)
+}
+
+// unions other than null name their branch, as in `{ "string": "abc" }`
+fn to_json(datum: &Datum, ptype: &Value, names: &BTreeMap<String, Value>) -> Value {
+    let ptype = match ptype {
+        Value::String(name) if !PRIMITIVES.contains(&name.as_str()) => {
+            lookup(names, name).unwrap_or(ptype)
+        }
+        _ => ptype,
+    };
+    match datum {
+        Datum::Null => Value::Null,
+        Datum::Boolean(value) => json!(value),
+        Datum::Int(value) => json!(value),
+        Datum::Long(value) => json!(value),
+        Datum::Float(value) => json!(value),
+        Datum::Double(value) => json!(value),
+        Datum::Bytes(bytes) | Datum::Fixed(bytes) => {
+            json!(bytes.iter().map(|&byte| byte as char).collect::<String>())
+        }
+        Datum::Enum(i) => ptype["symbols"][i].clone(),
+        Datum::Record(values) => {
+            let fields = ptype["fields"].as_array().into_iter().flatten();
+            let object: Map<String, Value> = fields
+                .zip(values)
+                .map(|(field, value)| {
+                    let name = field["name"].as_str().unwrap_or_default().to_owned();
+                    (name, to_json(value, &field["type"], names))
+                })
+                .collect();
+            Value::Object(object)
+        }
+        Datum::Array(items) => items
+            .iter()
+            .map(|item| to_json(item, &ptype["items"], names))
+            .collect(),
+        Datum::Map(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    let key = String::from_utf8_lossy(key).into_owned();
+                    (key, to_json(value, &ptype["values"], names))
+                })
+                .collect(),
+        ),
+        Datum::Union(_, value) if **value == Datum::Null => Value::Null,
+        Datum::Union(branch, value) => {
+            let member = &ptype[branch];
+            let name = match member {
+                Value::Object(object) if object.contains_key("name") => {
+                    let namespace = member["namespace"].as_str().unwrap_or_default();
+                    full_name(member["name"].as_str().unwrap_or_default(), namespace)
+                }
+                Value::String(name) => name.to_owned(),
+                _ => type_of(member).to_owned(),
+            };
+            json!({ name: to_json(value, member, names) })
+        }
+    }
+}
+
+pub fn encode_long(value: i64, out: &mut Vec<u8>) {
+    let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    while zigzag >= 0x80 {
+        out.push((zigzag as u8 & 0x7f) | 0x80);
+        zigzag >>= 7;
+    }
+    out.push(zigzag as u8);
+}
+
+fn encode(datum: &Datum, out: &mut Vec<u8>) {
+    match datum {
+        Datum::Null => {}
+        Datum::Boolean(value) => out.push(*value as u8),
+        Datum::Int(value) => encode_long(*value as i64, out),
+        Datum::Long(value) => encode_long(*value, out),
+        Datum::Float(value) => out.extend(value.to_le_bytes()),
+        Datum::Double(value) => out.extend(value.to_le_bytes()),
+        Datum::Bytes(bytes) => {
+            encode_long(bytes.len() as i64, out);
+            out.extend(bytes);
+        }
+        Datum::Fixed(bytes) => out.extend(bytes),
+        Datum::Enum(index) => encode_long(*index as i64, out),
+        Datum::Record(fields) => fields.iter().for_each(|field| encode(field, out)),
+        Datum::Union(branch, value) => {
+            encode_long(*branch as i64, out);
+            encode(value, out);
+        }
+        Datum::Array(items) => {
+            if !items.is_empty() {
+                encode_long(items.len() as i64, out);
+                items.iter().for_each(|item| encode(item, out));
+            }
+            out.push(0);
+        }
+        Datum::Map(entries) => {
+            if !entries.is_empty() {
+                encode_long(entries.len() as i64, out);
+                for (key, value) in entries {
+                    encode(&Datum::Bytes(key.clone()), out);
+                    encode(value, out);
+                }
+            }
+            out.push(0);
+        }
+    }
+}
+
+fn decode_long(input: &mut &[u8]) -> Result<i64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (byte, rest) = input.split_first().ok_or("unexpected end of data")?;
+        *input = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+        }
+    }
+    Err("a variable-length integer is too long".to_owned())
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if input.len() < len {
+        return Err("unexpected end of data".to_owned());
+    }
+    let (taken, rest) = input.split_at(len);
+    *input = rest;
+    Ok(taken)
+}
+
+fn decode(
+    ptype: &Value,
+    names: &BTreeMap<String, Value>,
+    input: &mut &[u8],
+) -> Result<Datum, String> {
+    let datum = match (type_of(ptype), ptype) {
+        ("union", Value::Array(members)) => {
+            let branch = decode_long(input)? as usize;
+            let member = members
+                .get(branch)
+                .ok_or(format!("union branch {} out of range", branch))?;
+            Datum::Union(branch, Box::new(decode(member, names, input)?))
+        }
+        ("null", _) => Datum::Null,
+        ("boolean", _) => Datum::Boolean(take(input, 1)?[0] != 0),
+        ("int", _) => Datum::Int(decode_long(input)? as i32),
+        ("long", _) => Datum::Long(decode_long(input)?),
+        ("float", _) => Datum::Float(f32::from_le_bytes(take(input, 4)?.try_into().unwrap())),
+        ("double", _) => Datum::Double(f64::from_le_bytes(take(input, 8)?.try_into().unwrap())),
+        ("bytes", _) | ("string", _) => {
+            let len = decode_long(input)? as usize;
+            Datum::Bytes(take(input, len)?.to_vec())
+        }
+        ("fixed", _) => {
+            let size = ptype["size"].as_u64().unwrap_or_default() as usize;
+            Datum::Fixed(take(input, size)?.to_vec())
+        }
+        ("enum", _) => Datum::Enum(decode_long(input)? as usize),
+        ("record", _) => {
+            let mut fields = Vec::new();
+            for field in ptype["fields"].as_array().into_iter().flatten() {
+                fields.push(decode(&field["type"], names, input)?);
+            }
+            Datum::Record(fields)
+        }
+        ("array", _) | ("map", _) => {
+            let mut items = Vec::new();
+            let mut entries = Vec::new();
+            loop {
+                let count = decode_long(input)?;
+                if count == 0 {
+                    break;
+                }
+                for _ in 0..count.unsigned_abs() {
+                    match type_of(ptype) {
+                        "array" => items.push(decode(&ptype["items"], names, input)?),
+                        _ => {
+                            let len = decode_long(input)? as usize;
+                            let key = take(input, len)?.to_vec();
+                            entries.push((key, decode(&ptype["values"], names, input)?));
+                        }
+                    }
+                }
+            }
+            match type_of(ptype) {
+                "array" => Datum::Array(items),
+                _ => Datum::Map(entries),
+            }
+        }
+        (name, _) => match lookup(names, name) {
+            Some(definition) => decode(definition, names, input)?,
+            None => return Err(format!("unknown type {}", name)),
+        },
+    };
+    Ok(datum)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::verify::{examples, generate, verify, Datum, Random};
+    use serde_json::{json, Value};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_round_trip() {
+        let schema = json!({
+            "type": "record",
+            "name": "Order",
+            "namespace": "com.acme",
+            "fields": [
+                { "name": "id", "type": "long" },
+                { "name": "note", "type": ["null", "string"], "default": null },
+                { "name": "total", "type": { "type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2 } },
+                {
+                    "name": "lines",
+                    "type": {
+                        "type": "array",
+                        "items": {
+                            "type": "record",
+                            "name": "Line",
+                            "fields": [
+                                { "name": "quantity", "type": "int" },
+                                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["OPEN", "SHIPPED"] } },
+                                { "name": "next", "type": ["null", "Line"], "default": null }
+                            ]
+                        }
+                    }
+                },
+                { "name": "tags", "type": { "type": "map", "values": "Status" } },
+                { "name": "weight", "type": ["float", "double", "boolean"] }
+            ]
+        });
+
+        assert_eq!(verify(&schema, 200), Ok(()));
+    }
+
+    #[test]
+    fn test_inconsistencies() {
+        let schema = json!({
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                { "name": "id", "type": "string", "default": 1 },
+                { "name": "id", "type": "Customer" },
+                { "name": "status", "type": { "type": "enum", "name": "Status", "symbols": ["OPEN", "OPEN"], "default": "DONE" } },
+                { "name": "kind", "type": ["string", "string"] }
+            ]
+        });
+
+        assert_eq!(
+            verify(&schema, 1).unwrap_err(),
+            vec![
+                "Order.id: the default 1 doesn't match the type",
+                "Order.id: the field is declared twice",
+                "Order.id: unknown type Customer",
+                "Order.status: the symbol OPEN is listed twice",
+                "Order.status: the default \"DONE\" is not a symbol",
+                "Order.kind: the union lists string twice"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unending_recursion() {
+        let schema = json!({
+            "type": "record",
+            "name": "Node",
+            "fields": [{ "name": "next", "type": "Node" }]
+        });
+
+        assert_eq!(
+            verify(&schema, 1).unwrap_err(),
+            vec!["record 1: a recursive type has no way to end, such as a nullable field"]
+        );
+    }
+
+    #[test]
+    fn test_annotated_examples() {
+        let schema = json!({
+            "type": "record",
+            "name": "Customer",
+            "fields": [
+                { "name": "email", "type": ["null", "string"], "faker": "internet.email" },
+                { "name": "country", "type": { "type": "enum", "name": "Country", "symbols": ["GB", "PT"] }, "example": "GB" },
+                { "name": "age", "type": "int" }
+            ]
+        });
+
+        let examples = examples(&schema, 20).unwrap();
+
+        for example in &examples {
+            let email = example["email"]["string"].as_str().unwrap();
+            assert!(email.contains('@'), "{}", email);
+            assert_eq!(example["country"], "GB");
+        }
+
+        let schema = json!({
+            "type": "record",
+            "name": "Customer",
+            "fields": [
+                { "name": "age", "type": "int", "faker": "internet.email" },
+                { "name": "email", "type": "string", "faker": "internet.nothing" },
+                { "name": "country", "type": "string", "example": 44 }
+            ]
+        });
+
+        assert_eq!(
+            verify(&schema, 1).unwrap_err(),
+            vec![
+                "Customer.age: faker values are strings",
+                "Customer.email: unknown faker method \"internet.nothing\"",
+                "Customer.country: the example 44 doesn't match the type"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_logical_types() {
+        let ptype = |ptype: Value| {
+            let mut random = Random(0x2545_f491_4f6c_dd1d);
+            (0..50)
+                .map(|_| generate(&ptype, &BTreeMap::new(), &mut random, 0).unwrap())
+                .collect::<Vec<Datum>>()
+        };
+
+        for datum in ptype(json!({ "type": "string", "logicalType": "uuid" })) {
+            let Datum::Bytes(uuid) = datum else { panic!() };
+            let uuid = String::from_utf8(uuid).unwrap();
+            let groups: Vec<usize> = uuid.split('-').map(str::len).collect();
+            assert_eq!(groups, vec![8, 4, 4, 4, 12], "{}", uuid);
+            assert!(uuid.chars().all(|c| c == '-' || c.is_ascii_hexdigit()));
+        }
+        for datum in ptype(json!({ "type": "long", "logicalType": "timestamp-millis" })) {
+            assert!(
+                matches!(datum, Datum::Long(millis) if (0..4_102_444_800_000).contains(&millis))
+            );
+        }
+        for datum in ptype(json!({ "type": "int", "logicalType": "date" })) {
+            assert!(matches!(datum, Datum::Int(days) if (0..47_482).contains(&days)));
+        }
+        let decimal =
+            json!({ "type": "bytes", "logicalType": "decimal", "precision": 4, "scale": 2 });
+        for datum in ptype(decimal) {
+            let Datum::Bytes(bytes) = datum else { panic!() };
+            assert!(!bytes.is_empty() && bytes.len() <= 2, "{:?}", bytes);
+            let sign = if bytes[0] >= 0x80 { -1 } else { 0 };
+            let unscaled = bytes
+                .iter()
+                .fold(sign, |value: i64, &byte| value << 8 | byte as i64);
+            assert!(unscaled.abs() < 10_000, "{}", unscaled);
+        }
+        let decimal = json!({ "type": "fixed", "name": "Amount", "size": 3, "logicalType": "decimal", "precision": 9 });
+        for datum in ptype(decimal) {
+            let Datum::Fixed(bytes) = datum else { panic!() };
+            assert_eq!(bytes.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_type_names() {
+        let mut schema = json!({
+            "type": "Record",
+            "name": "Order",
+            "fields": [{ "name": "total", "type": "Double" }]
+        });
+
+        assert_eq!(
+            verify(&schema, 1).unwrap_err(),
+            vec!["unknown type \"Record\""]
+        );
+        schema["type"] = json!("record");
+        assert_eq!(
+            verify(&schema, 1).unwrap_err(),
+            vec!["Order.total: unknown type Double"]
+        );
+    }
+}